@@ -0,0 +1,362 @@
+//! Batch propagation contract shared by the CPU, CUDA and WebGPU backends.
+//!
+//! A backend receives an [`ElementSet`] once, then repeatedly propagates the
+//! whole set to a [`DispatchTimes`] and writes the results into a
+//! structure-of-arrays [`StateBuffer`]. Failures are reported per object
+//! through [`ObjectStatus`] codes, so one bad element set never fails a frame.
+use std::future::Future;
+use std::pin::Pin;
+
+use sgp4::chrono::NaiveDateTime;
+use thiserror::Error;
+
+use crate::{FailureKind, PropagationError, Sgp4Propagator, StateVector};
+
+/// Errors surfaced by GPU backend implementations.
+#[derive(Debug, Error)]
+pub enum GpuBackendError {
+    #[error("backend is not ready")]
+    NotReady,
+    #[error("invalid dispatch batch size: {0}")]
+    InvalidBatchSize(u32),
+    #[error("expected {expected} time offsets, got {actual}")]
+    TimeCountMismatch { expected: usize, actual: usize },
+    #[error("shader compilation failed: {0}")]
+    ShaderCompile(String),
+    #[error("compute dispatch failed: {0}")]
+    Dispatch(String),
+}
+
+/// Completion of an asynchronous backend dispatch.
+pub type BackendFuture<'a> = Pin<Box<dyn Future<Output = Result<(), GpuBackendError>> + 'a>>;
+
+/// Contract shared by CUDA, WebGPU and CPU compute backends.
+pub trait GpuBackend {
+    /// User-visible backend name.
+    fn name(&self) -> &'static str;
+
+    /// Whether this backend has been initialized and can accept work.
+    fn is_ready(&self) -> bool;
+
+    /// Upload an element set, replacing any previously uploaded one.
+    fn upload_elements(&mut self, elements: &ElementSet) -> Result<(), GpuBackendError>;
+
+    /// Propagate every uploaded object to `times` and write the results,
+    /// including one status code per object, into `states`.
+    ///
+    /// `states` is resized to the uploaded object count. The returned future
+    /// resolves once the results have been read back.
+    fn propagate<'a>(
+        &'a self,
+        times: DispatchTimes<'a>,
+        states: &'a mut StateBuffer,
+    ) -> BackendFuture<'a>;
+}
+
+/// SGP4 constants of one object in the flat layout uploaded to GPU backends.
+///
+/// Produced on the host by [`PackedElements::from_elements`], which runs the
+/// f64 SGP4 initialization once, so kernels only evaluate the time-dependent
+/// part of the model. Angles are in radians, rates per minute and lengths in
+/// Earth radii. Fields that do not apply to an object's `method` are zero.
+/// The WGSL mirror of this struct lives in `webgpu-compute/shaders/sgp4_fp32.wgsl`
+/// and [`crate::fp32`] is the host reference of the kernel that reads it.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PackedElements {
+    /// [`ObjectStatus`] code of the initialization.
+    pub status: u32,
+    /// One of the `fp32::METHOD_*` constants.
+    pub method: u32,
+    /// One of the `fp32::RESONANCE_*` constants.
+    pub resonance: u32,
+    pub inclination: f32,
+    pub right_ascension: f32,
+    pub eccentricity: f32,
+    pub argument_of_perigee: f32,
+    pub mean_anomaly: f32,
+    /// Brouwer mean motion in radians per minute.
+    pub mean_motion: f32,
+    pub right_ascension_dot: f32,
+    pub argument_of_perigee_dot: f32,
+    pub mean_anomaly_dot: f32,
+    pub c1: f32,
+    pub c4: f32,
+    pub k0: f32,
+    pub k1: f32,
+    /// Semi-major axis at epoch.
+    pub a0: f32,
+    /// Near-earth short-period coefficients.
+    pub k2: f32,
+    pub k3: f32,
+    pub k4: f32,
+    pub k5: f32,
+    pub k6: f32,
+    /// Near-earth drag terms, used only when the perigee is above 220 km.
+    pub c5: f32,
+    pub d2: f32,
+    pub d3: f32,
+    pub d4: f32,
+    pub eta: f32,
+    pub k7: f32,
+    pub k8: f32,
+    pub k9: f32,
+    pub k10: f32,
+    pub k11: f32,
+    pub k12: f32,
+    pub k13: f32,
+    /// Deep-space secular rates from lunar and solar gravity.
+    pub eccentricity_dot: f32,
+    pub inclination_dot: f32,
+    /// Solar long-period coefficients, then the Sun's mean anomaly at epoch.
+    pub solar: [f32; 13],
+    /// Lunar long-period coefficients, then the Moon's mean anomaly at epoch.
+    pub lunar: [f32; 13],
+    /// Resonance integrator initial conditions.
+    pub lambda_0: f32,
+    pub lambda_dot_0: f32,
+    pub sidereal_time_0: f32,
+    pub k14: f32,
+    /// `dr1..dr3` for one-day resonance, `d2201..d5433` for half-day.
+    pub resonance_terms: [f32; 10],
+}
+
+/// A batch of objects ready for upload to a backend.
+///
+/// Keeps the f64 propagators for backends that run the reference
+/// implementation alongside the packed layout used by GPU kernels.
+#[derive(Debug, Clone, Default)]
+pub struct ElementSet {
+    propagators: Vec<Sgp4Propagator>,
+    packed: Vec<PackedElements>,
+}
+
+impl ElementSet {
+    pub fn new(propagators: Vec<Sgp4Propagator>) -> Self {
+        let packed = propagators
+            .iter()
+            .map(|propagator| PackedElements::from_elements(&propagator.elements))
+            .collect();
+        Self {
+            propagators,
+            packed,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.propagators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.propagators.is_empty()
+    }
+
+    pub fn propagators(&self) -> &[Sgp4Propagator] {
+        &self.propagators
+    }
+
+    pub fn packed(&self) -> &[PackedElements] {
+        &self.packed
+    }
+}
+
+impl FromIterator<Sgp4Propagator> for ElementSet {
+    fn from_iter<I: IntoIterator<Item = Sgp4Propagator>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+/// Target times for one dispatch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DispatchTimes<'a> {
+    /// Every object is propagated to the same UTC instant.
+    Uniform(NaiveDateTime),
+    /// Object `i` is propagated to `minutes[i]` minutes past its own epoch.
+    MinutesSinceEpoch(&'a [f64]),
+}
+
+impl DispatchTimes<'_> {
+    /// Minutes since each object's epoch.
+    ///
+    /// Instants that cannot be represented relative to an epoch come back as
+    /// `NaN`, which backends report as [`ObjectStatus::InvalidTime`].
+    pub fn minutes_since_epoch(
+        &self,
+        propagators: &[Sgp4Propagator],
+    ) -> Result<Vec<f64>, GpuBackendError> {
+        match *self {
+            DispatchTimes::Uniform(time) => Ok(propagators
+                .iter()
+                .map(|propagator| propagator.minutes_since_epoch(time).unwrap_or(f64::NAN))
+                .collect()),
+            DispatchTimes::MinutesSinceEpoch(minutes) => {
+                if minutes.len() != propagators.len() {
+                    return Err(GpuBackendError::TimeCountMismatch {
+                        expected: propagators.len(),
+                        actual: minutes.len(),
+                    });
+                }
+                Ok(minutes.to_vec())
+            }
+        }
+    }
+}
+
+/// Per-object outcome of a dispatch.
+///
+/// Codes 1–6 follow the error codes of Vallado's reference SGP4
+/// implementation so that GPU kernels can report them as plain integers.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectStatus {
+    Ok = 0,
+    /// Mean eccentricity outside `[0, 1)` or semi-major axis below 0.95 Earth radii.
+    EccentricityOutOfRange = 1,
+    MeanMotionNegative = 2,
+    /// Perturbed eccentricity outside `[0, 1]`.
+    PerturbedEccentricityOutOfRange = 3,
+    SemiLatusRectumNegative = 4,
+    /// Epoch elements are sub-orbital.
+    SubOrbital = 5,
+    /// The satellite has decayed below the Earth's surface.
+    Decayed = 6,
+    /// The target time was non-finite or not representable.
+    InvalidTime = 7,
+    /// The element set or its catalog number could not be read.
+    InvalidElements = 8,
+    /// The slot has not been written by a dispatch.
+    Pending = 255,
+}
+
+impl ObjectStatus {
+    /// Decode a status word read back from a backend.
+    pub fn from_code(code: u32) -> Option<Self> {
+        Some(match code {
+            0 => Self::Ok,
+            1 => Self::EccentricityOutOfRange,
+            2 => Self::MeanMotionNegative,
+            3 => Self::PerturbedEccentricityOutOfRange,
+            4 => Self::SemiLatusRectumNegative,
+            5 => Self::SubOrbital,
+            6 => Self::Decayed,
+            7 => Self::InvalidTime,
+            8 => Self::InvalidElements,
+            255 => Self::Pending,
+            _ => return None,
+        })
+    }
+
+    pub fn code(self) -> u32 {
+        self as u32
+    }
+
+    pub fn is_ok(self) -> bool {
+        self == Self::Ok
+    }
+}
+
+impl From<FailureKind> for ObjectStatus {
+    fn from(kind: FailureKind) -> Self {
+        match kind {
+            FailureKind::EccentricityOutOfRange => Self::EccentricityOutOfRange,
+            FailureKind::MeanMotionNegative => Self::MeanMotionNegative,
+            FailureKind::PerturbedEccentricityOutOfRange => Self::PerturbedEccentricityOutOfRange,
+            FailureKind::SemiLatusRectumNegative => Self::SemiLatusRectumNegative,
+            FailureKind::Decayed => Self::Decayed,
+            FailureKind::NonFiniteInput => Self::InvalidTime,
+        }
+    }
+}
+
+impl From<&PropagationError> for ObjectStatus {
+    fn from(error: &PropagationError) -> Self {
+        match error {
+            PropagationError::Propagation(failure) => failure.kind.into(),
+            PropagationError::EpochConversion(_) => Self::InvalidTime,
+            PropagationError::Tle(_) | PropagationError::NoradId(_) => Self::InvalidElements,
+        }
+    }
+}
+
+/// Propagated TEME states for a batch, stored as one array per component.
+///
+/// Objects whose propagation failed keep `NaN` components and a non-`Ok`
+/// entry in `status`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StateBuffer {
+    pub x_km: Vec<f64>,
+    pub y_km: Vec<f64>,
+    pub z_km: Vec<f64>,
+    pub vx_km_s: Vec<f64>,
+    pub vy_km_s: Vec<f64>,
+    pub vz_km_s: Vec<f64>,
+    pub status: Vec<ObjectStatus>,
+}
+
+impl StateBuffer {
+    /// Buffer sized for `len` objects, with every slot pending.
+    pub fn with_len(len: usize) -> Self {
+        let mut buffer = Self::default();
+        buffer.resize(len);
+        buffer
+    }
+
+    /// Number of object slots.
+    pub fn len(&self) -> usize {
+        self.status.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.status.is_empty()
+    }
+
+    /// Resize every component array to `len` slots.
+    pub fn resize(&mut self, len: usize) {
+        for component in [
+            &mut self.x_km,
+            &mut self.y_km,
+            &mut self.z_km,
+            &mut self.vx_km_s,
+            &mut self.vy_km_s,
+            &mut self.vz_km_s,
+        ] {
+            component.resize(len, f64::NAN);
+        }
+        self.status.resize(len, ObjectStatus::Pending);
+    }
+
+    /// Store the outcome of propagating object `index`.
+    pub fn set(&mut self, index: usize, result: Result<StateVector, ObjectStatus>) {
+        let (position, velocity, status) = match result {
+            Ok(state) => (state.position_km, state.velocity_km_s, ObjectStatus::Ok),
+            Err(status) => ([f64::NAN; 3], [f64::NAN; 3], status),
+        };
+        self.x_km[index] = position[0];
+        self.y_km[index] = position[1];
+        self.z_km[index] = position[2];
+        self.vx_km_s[index] = velocity[0];
+        self.vy_km_s[index] = velocity[1];
+        self.vz_km_s[index] = velocity[2];
+        self.status[index] = status;
+    }
+
+    /// State of object `index`, or `None` if it failed to propagate.
+    pub fn state(&self, index: usize) -> Option<StateVector> {
+        if !self.status.get(index)?.is_ok() {
+            return None;
+        }
+        Some(StateVector {
+            position_km: [self.x_km[index], self.y_km[index], self.z_km[index]],
+            velocity_km_s: [
+                self.vx_km_s[index],
+                self.vy_km_s[index],
+                self.vz_km_s[index],
+            ],
+        })
+    }
+
+    /// Number of objects that propagated successfully.
+    pub fn ok_count(&self) -> usize {
+        self.status.iter().filter(|status| status.is_ok()).count()
+    }
+}
@@ -0,0 +1,174 @@
+//! Accuracy of the f32 GPU kernel measured against the f64 reference.
+//!
+//! GPU backends evaluate SGP4 in single precision, so they can only claim the
+//! accuracy this module measures: both [`Sgp4Propagator`] and
+//! [`Fp32Propagator`] are stepped over the same window and the largest
+//! position and velocity differences are recorded per object.
+use std::fmt;
+
+use crate::backend::ObjectStatus;
+use crate::fp32::Fp32Propagator;
+use crate::{NoradId, Sgp4Propagator};
+
+/// How far the f32 kernel drifts from the f64 reference for one object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectDrift {
+    pub norad_id: NoradId,
+    /// Number of times at which both implementations produced a state.
+    pub samples: usize,
+    pub max_position_km: f64,
+    /// Minutes since epoch of the largest position difference.
+    pub max_position_at: f64,
+    pub max_velocity_km_s: f64,
+    /// Minutes since epoch of the largest velocity difference.
+    pub max_velocity_at: f64,
+    /// First time and status at which both implementations failed the same
+    /// way. Sampling stops there.
+    pub failure: Option<(f64, ObjectStatus)>,
+    /// First time at which only one implementation failed, or both failed
+    /// with different statuses. Sampling stops there.
+    pub divergence: Option<f64>,
+}
+
+impl ObjectDrift {
+    /// Step both propagators from epoch to `span_minutes` every
+    /// `step_minutes` and record the worst differences.
+    pub fn measure(propagator: &Sgp4Propagator, span_minutes: f64, step_minutes: f64) -> Self {
+        let fp32 = Fp32Propagator::from(propagator);
+        let mut drift = Self {
+            norad_id: propagator.norad_id(),
+            samples: 0,
+            max_position_km: 0.0,
+            max_position_at: 0.0,
+            max_velocity_km_s: 0.0,
+            max_velocity_at: 0.0,
+            failure: None,
+            divergence: None,
+        };
+
+        let steps = (span_minutes / step_minutes).floor() as usize;
+        for step in 0..=steps {
+            let minutes = step as f64 * step_minutes;
+            match (
+                propagator.propagate_minutes(minutes),
+                fp32.propagate_minutes(minutes),
+            ) {
+                (Ok(reference), Ok(state)) => {
+                    drift.samples += 1;
+                    let position = distance(reference.position_km, state.position_km);
+                    let velocity = distance(reference.velocity_km_s, state.velocity_km_s);
+                    if position > drift.max_position_km {
+                        drift.max_position_km = position;
+                        drift.max_position_at = minutes;
+                    }
+                    if velocity > drift.max_velocity_km_s {
+                        drift.max_velocity_km_s = velocity;
+                        drift.max_velocity_at = minutes;
+                    }
+                }
+                (Err(error), Err(status)) if ObjectStatus::from(&error) == status => {
+                    drift.failure = Some((minutes, status));
+                    break;
+                }
+                _ => {
+                    drift.divergence = Some(minutes);
+                    break;
+                }
+            }
+        }
+        drift
+    }
+}
+
+/// Drift of every object in a catalog over a common window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorBudget {
+    pub span_minutes: f64,
+    pub step_minutes: f64,
+    pub objects: Vec<ObjectDrift>,
+}
+
+impl ErrorBudget {
+    /// Measure every propagator from epoch to `span_minutes`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step_minutes` is not positive.
+    pub fn measure(propagators: &[Sgp4Propagator], span_minutes: f64, step_minutes: f64) -> Self {
+        assert!(step_minutes > 0.0, "step must be positive");
+        Self {
+            span_minutes,
+            step_minutes,
+            objects: propagators
+                .iter()
+                .map(|propagator| ObjectDrift::measure(propagator, span_minutes, step_minutes))
+                .collect(),
+        }
+    }
+
+    /// Largest position difference across the catalog in km.
+    pub fn max_position_km(&self) -> f64 {
+        self.objects
+            .iter()
+            .map(|object| object.max_position_km)
+            .fold(0.0, f64::max)
+    }
+
+    /// Largest velocity difference across the catalog in km/s.
+    pub fn max_velocity_km_s(&self) -> f64 {
+        self.objects
+            .iter()
+            .map(|object| object.max_velocity_km_s)
+            .fold(0.0, f64::max)
+    }
+
+    /// Objects for which the two implementations disagreed on success.
+    pub fn divergences(&self) -> impl Iterator<Item = &ObjectDrift> {
+        self.objects
+            .iter()
+            .filter(|object| object.divergence.is_some())
+    }
+}
+
+impl fmt::Display for ErrorBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "FP32 vs f64 SGP4 over {} min, sampled every {} min",
+            self.span_minutes, self.step_minutes
+        )?;
+        writeln!(
+            f,
+            "{:>7}  {:>7}  {:>10}  {:>8}  {:>10}  {:>8}  NOTE",
+            "NORAD", "SAMPLES", "MAX DR KM", "AT MIN", "MAX DV M/S", "AT MIN"
+        )?;
+        for object in &self.objects {
+            write!(
+                f,
+                "{:>7}  {:>7}  {:>10.4}  {:>8.0}  {:>10.4}  {:>8.0}",
+                object.norad_id,
+                object.samples,
+                object.max_position_km,
+                object.max_position_at,
+                object.max_velocity_km_s * 1000.0,
+                object.max_velocity_at,
+            )?;
+            if let Some(minutes) = object.divergence {
+                write!(f, "  diverged at {minutes} min")?;
+            } else if let Some((minutes, status)) = object.failure {
+                write!(f, "  {status:?} at {minutes} min")?;
+            }
+            writeln!(f)?;
+        }
+        write!(
+            f,
+            "worst: {:.4} km, {:.4} m/s",
+            self.max_position_km(),
+            self.max_velocity_km_s() * 1000.0
+        )
+    }
+}
+
+fn distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+    (0..3).map(|i| (a[i] - b[i]).powi(2)).sum::<f64>().sqrt()
+}
@@ -0,0 +1,982 @@
+//! Single-precision SGP4 kernel shared with the GPU shaders.
+//!
+//! GPU backends cannot afford SGP4's f64 initialization per frame, and WGSL
+//! has no f64 at all. Initialization therefore runs once on the host in f64
+//! and is stored as f32 constants in [`PackedElements`]; [`propagate`] then
+//! evaluates the time-dependent part of the model in f32, statement for
+//! statement the same as `webgpu-compute/shaders/sgp4_fp32.wgsl`. Any change
+//! here must be mirrored in the shader, and the parity tests compare the two.
+//!
+//! The math follows the [`sgp4`] crate with the WGS-84 geopotential and IAU
+//! sidereal time, so results stay comparable with [`crate::Sgp4Propagator`].
+use std::f64::consts::{PI, TAU};
+
+use crate::backend::{ObjectStatus, PackedElements};
+use crate::{PropagationError, Sgp4Propagator, StateVector};
+
+/// `PackedElements::method` for near-earth orbits with a perigee below 220 km,
+/// which use the truncated drag model.
+pub const METHOD_NEAR_EARTH_LOW_PERIGEE: u32 = 0;
+/// `PackedElements::method` for the remaining near-earth orbits.
+pub const METHOD_NEAR_EARTH: u32 = 1;
+/// `PackedElements::method` for orbits with a period of 225 minutes or more.
+pub const METHOD_DEEP_SPACE: u32 = 2;
+
+/// `PackedElements::resonance` for deep-space orbits without geopotential
+/// resonance.
+pub const RESONANCE_NONE: u32 = 0;
+/// `PackedElements::resonance` for geosynchronous (one-day) orbits.
+pub const RESONANCE_ONE_DAY: u32 = 1;
+/// `PackedElements::resonance` for Molniya-type (half-day) orbits.
+pub const RESONANCE_HALF_DAY: u32 = 2;
+
+// WGS-84 geopotential, as used by `sgp4::WGS84`.
+const AE: f64 = 6378.137;
+const KE: f64 = 0.07436685316871385;
+const J2: f64 = 0.00108262998905;
+const J3: f64 = -0.00000253215306;
+const J4: f64 = -0.00000161098761;
+
+const SOLAR_ECCENTRICITY: f64 = 0.01675;
+const LUNAR_ECCENTRICITY: f64 = 0.05490;
+const SOLAR_MEAN_MOTION: f64 = 1.19459e-5;
+const LUNAR_MEAN_MOTION: f64 = 1.5835218e-4;
+const SOLAR_PERTURBATION_COEFFICIENT: f64 = 2.9864797e-6;
+const LUNAR_PERTURBATION_COEFFICIENT: f64 = 4.7968065e-7;
+#[allow(clippy::excessive_precision)]
+const SIDEREAL_SPEED: f64 = 4.37526908801129966e-3;
+
+/// Resonance integrator step in minutes.
+const RESONANCE_STEP: f32 = 720.0;
+/// Upper bound on integrator steps (about 137 years), so that a huge time
+/// offset cannot stall a GPU invocation.
+const MAX_RESONANCE_STEPS: u32 = 100_000;
+const LAMBDA31: f32 = 0.13130908;
+const LAMBDA22: f32 = 2.8843198;
+const LAMBDA33: f32 = 0.37448087;
+const G22: f32 = 5.7686396;
+const G32: f32 = 0.95240898;
+const G44: f32 = 1.8014998;
+const G52: f32 = 1.050833;
+const G54: f32 = 4.4108898;
+
+/// Kepler iterations stop once the correction drops below this, which is
+/// about the f32 resolution of an angle below 2π.
+const KEPLER_TOLERANCE: f32 = 1.0e-7;
+
+impl PackedElements {
+    /// Run SGP4 initialization in f64 and pack the result for the f32 kernel.
+    ///
+    /// Initialization failures are not returned: they are recorded in
+    /// `status` so that the object still occupies its slot in a batch.
+    pub fn from_elements(elements: &sgp4::Elements) -> Self {
+        match initialize(elements) {
+            Ok(packed) => packed,
+            Err(status) => Self {
+                status: status.code(),
+                ..Self::default()
+            },
+        }
+    }
+
+    /// Outcome of initialization; anything but [`ObjectStatus::Ok`] makes
+    /// every propagation of this object fail with the same status.
+    pub fn status(&self) -> ObjectStatus {
+        ObjectStatus::from_code(self.status).unwrap_or(ObjectStatus::EccentricityOutOfRange)
+    }
+}
+
+/// Port of `sgp4::Constants::from_elements`, flattened into the packed layout.
+fn initialize(elements: &sgp4::Elements) -> Result<PackedElements, ObjectStatus> {
+    let inclination = elements.inclination.to_radians();
+    let right_ascension = elements.right_ascension.to_radians();
+    let e0 = elements.eccentricity;
+    let argument_of_perigee = elements.argument_of_perigee.to_radians();
+    let mean_anomaly = elements.mean_anomaly.to_radians();
+    let kozai_mean_motion = elements.mean_motion * (PI / 720.0);
+    let drag_term = elements.drag_term;
+
+    if kozai_mean_motion <= 0.0 {
+        return Err(ObjectStatus::MeanMotionNegative);
+    }
+    if !(0.0..1.0).contains(&e0) {
+        return Err(ObjectStatus::EccentricityOutOfRange);
+    }
+
+    // Kozai to Brouwer mean motion.
+    let n0 = {
+        let a1 = (KE / kozai_mean_motion).powf(2.0 / 3.0);
+        let p0 = 0.75 * J2 * (3.0 * inclination.cos().powi(2) - 1.0) / (1.0 - e0 * e0).powf(1.5);
+        let d1 = p0 / a1.powi(2);
+        let d0 =
+            p0 / (a1 * (1.0 - d1.powi(2) - d1 * (1.0 / 3.0 + 134.0 * d1.powi(2) / 81.0))).powi(2);
+        kozai_mean_motion / (1.0 + d0)
+    };
+    if n0 <= 0.0 {
+        return Err(ObjectStatus::MeanMotionNegative);
+    }
+
+    let p1 = inclination.cos();
+    let p2 = 1.0 - e0 * e0;
+    let k6 = 3.0 * p1 * p1 - 1.0;
+    let a0 = (KE / n0).powf(2.0 / 3.0);
+    let p3 = a0 * (1.0 - e0);
+    let (s, p6) = {
+        let p4 = AE * (p3 - 1.0);
+        let p5 = if p4 < 98.0 {
+            20.0
+        } else if p4 < 156.0 {
+            p4 - 78.0
+        } else {
+            78.0
+        };
+        (p5 / AE + 1.0, ((120.0 - p5) / AE).powi(4))
+    };
+    let xi = 1.0 / (a0 - s);
+    let p7 = p6 * xi.powi(4);
+    let eta = a0 * e0 * xi;
+    let p8 = (1.0 - eta * eta).abs();
+    let p9 = p7 / p8.powf(3.5);
+    let c1 = drag_term
+        * (p9
+            * n0
+            * (a0 * (1.0 + 1.5 * eta * eta + e0 * eta * (4.0 + eta * eta))
+                + 0.375 * J2 * xi / p8 * k6 * (8.0 + 3.0 * eta * eta * (8.0 + eta * eta))));
+    let p10 = 1.0 / (a0 * p2).powi(2);
+    let b0 = p2.sqrt();
+    let p11 = 1.5 * J2 * p10 * n0;
+    let p12 = 0.5 * p11 * J2 * p10;
+    let p13 = -0.46875 * J4 * p10.powi(2) * n0;
+    let p14 =
+        -p11 * p1 + (0.5 * p12 * (4.0 - 19.0 * p1 * p1) + 2.0 * p13 * (3.0 - 7.0 * p1 * p1)) * p1;
+    let k14 = -0.5 * p11 * (1.0 - 5.0 * p1 * p1)
+        + 0.0625 * p12 * (7.0 - 114.0 * p1 * p1 + 395.0 * p1.powi(4))
+        + p13 * (3.0 - 36.0 * p1 * p1 + 49.0 * p1.powi(4));
+    let p15 =
+        n0 + 0.5 * p11 * b0 * k6 + 0.0625 * p12 * b0 * (13.0 - 78.0 * p1 * p1 + 137.0 * p1.powi(4));
+    let c4 = drag_term
+        * (2.0
+            * n0
+            * p9
+            * a0
+            * p2
+            * (eta * (2.0 + 0.5 * eta * eta) + e0 * (0.5 + 2.0 * eta * eta)
+                - J2 * xi / (a0 * p8)
+                    * (-3.0 * k6 * (1.0 - 2.0 * e0 * eta + eta * eta * (1.5 - 0.5 * e0 * eta))
+                        + 0.75
+                            * (1.0 - p1 * p1)
+                            * (2.0 * eta * eta - e0 * eta * (1.0 + eta * eta))
+                            * (2.0 * argument_of_perigee).cos())));
+    let k0 = 3.5 * p2 * (-p11 * p1) * c1;
+    let k1 = 1.5 * c1;
+
+    let mut packed = PackedElements {
+        status: ObjectStatus::Ok.code(),
+        inclination: inclination as f32,
+        right_ascension: right_ascension as f32,
+        eccentricity: e0 as f32,
+        argument_of_perigee: argument_of_perigee as f32,
+        mean_anomaly: mean_anomaly as f32,
+        mean_motion: n0 as f32,
+        c1: c1 as f32,
+        c4: c4 as f32,
+        k0: k0 as f32,
+        k1: k1 as f32,
+        a0: a0 as f32,
+        ..PackedElements::default()
+    };
+
+    if n0 > TAU / 225.0 {
+        let j3_over_j2 = J3 / J2;
+        let k5_denominator = if (1.0 + p1).abs() > 1.5e-12 {
+            1.0 + p1
+        } else {
+            1.5e-12
+        };
+        packed.right_ascension_dot = p14 as f32;
+        packed.argument_of_perigee_dot = k14 as f32;
+        packed.mean_anomaly_dot = p15 as f32;
+        packed.k2 = (-0.5 * j3_over_j2 * inclination.sin()) as f32;
+        packed.k3 = (1.0 - p1 * p1) as f32;
+        packed.k4 = (7.0 * p1 * p1 - 1.0) as f32;
+        packed.k5 =
+            (-0.25 * j3_over_j2 * inclination.sin() * (3.0 + 5.0 * p1) / k5_denominator) as f32;
+        packed.k6 = k6 as f32;
+
+        if p3 < 220.0 / AE + 1.0 {
+            packed.method = METHOD_NEAR_EARTH_LOW_PERIGEE;
+        } else {
+            let d2 = 4.0 * a0 * xi * c1 * c1;
+            let p16 = d2 * xi * c1 / 3.0;
+            let d3 = (17.0 * a0 + s) * p16;
+            let d4 = 0.5 * p16 * a0 * xi * (221.0 * a0 + 31.0 * s) * c1;
+            packed.method = METHOD_NEAR_EARTH;
+            packed.c5 = (drag_term
+                * (2.0
+                    * p9
+                    * a0
+                    * p2
+                    * (1.0 + 2.75 * (eta * eta + eta * e0) + eta * e0 * eta * eta)))
+                as f32;
+            packed.d2 = d2 as f32;
+            packed.d3 = d3 as f32;
+            packed.d4 = d4 as f32;
+            packed.eta = eta as f32;
+            packed.k7 = mean_anomaly.sin() as f32;
+            packed.k8 = (d2 + 2.0 * c1 * c1) as f32;
+            packed.k9 = (0.25 * (3.0 * d3 + c1 * (12.0 * d2 + 10.0 * c1 * c1))) as f32;
+            packed.k10 = (0.2
+                * (3.0 * d4
+                    + 12.0 * c1 * d3
+                    + 6.0 * d2 * d2
+                    + 15.0 * c1 * c1 * (2.0 * d2 + c1 * c1))) as f32;
+            // Near-circular orbits drop these terms; zero constants do the same.
+            if e0 > 1.0e-4 {
+                packed.k11 = (1.0 + eta * mean_anomaly.cos()).powi(3) as f32;
+                packed.k12 = (drag_term
+                    * (-2.0 * p7 * xi * j3_over_j2 * n0 * inclination.sin() / e0)
+                    * argument_of_perigee.cos()) as f32;
+                packed.k13 = (-2.0 / 3.0 * p7 * drag_term / (e0 * eta)) as f32;
+            }
+        }
+        return Ok(packed);
+    }
+
+    // Deep space: lunar and solar perturbations, plus resonance for
+    // geosynchronous and half-day orbits.
+    let epoch = elements.epoch();
+    let d1900 = (epoch + 100.0) * 365.25;
+    let third_body = |sin_inclination,
+                      cos_inclination,
+                      sin_delta_right_ascension,
+                      cos_delta_right_ascension,
+                      eccentricity,
+                      sin_argument_of_perigee,
+                      cos_argument_of_perigee,
+                      coefficient,
+                      mean_motion,
+                      mean_anomaly_0| {
+        ThirdBody {
+            inclination_0: inclination,
+            eccentricity_0: e0,
+            argument_of_perigee_0: argument_of_perigee,
+            n0,
+            sin_inclination,
+            cos_inclination,
+            sin_delta_right_ascension,
+            cos_delta_right_ascension,
+            eccentricity,
+            sin_argument_of_perigee,
+            cos_argument_of_perigee,
+            coefficient,
+            mean_motion,
+            mean_anomaly_0,
+            p2,
+            b0,
+        }
+        .perturbations_and_dots()
+    };
+
+    let (solar, solar_dots) = third_body(
+        0.39785416,
+        0.91744867,
+        right_ascension.sin(),
+        right_ascension.cos(),
+        SOLAR_ECCENTRICITY,
+        -0.98088458,
+        0.1945905,
+        SOLAR_PERTURBATION_COEFFICIENT,
+        SOLAR_MEAN_MOTION,
+        (6.2565837 + 0.017201977 * d1900) % TAU,
+    );
+
+    let lunar_epsilon = (4.5236020 - 9.2422029e-4 * d1900) % TAU;
+    let lunar_cos_inclination = 0.91375164 - 0.03568096 * lunar_epsilon.cos();
+    let lunar_sin_inclination = (1.0 - lunar_cos_inclination.powi(2)).sqrt();
+    let lunar_sin_right_ascension = 0.089683511 * lunar_epsilon.sin() / lunar_sin_inclination;
+    let lunar_cos_right_ascension = (1.0 - lunar_sin_right_ascension.powi(2)).sqrt();
+    let lunar_argument_of_perigee = 5.8351514
+        + 0.001944368 * d1900
+        + (0.39785416 * lunar_epsilon.sin() / lunar_sin_inclination).atan2(
+            lunar_cos_right_ascension * lunar_epsilon.cos()
+                + 0.91744867 * lunar_sin_right_ascension * lunar_epsilon.sin(),
+        )
+        - lunar_epsilon;
+    let (lunar, lunar_dots) = third_body(
+        lunar_sin_inclination,
+        lunar_cos_inclination,
+        right_ascension.sin() * lunar_cos_right_ascension
+            - right_ascension.cos() * lunar_sin_right_ascension,
+        lunar_cos_right_ascension * right_ascension.cos()
+            + lunar_sin_right_ascension * right_ascension.sin(),
+        LUNAR_ECCENTRICITY,
+        lunar_argument_of_perigee.sin(),
+        lunar_argument_of_perigee.cos(),
+        LUNAR_PERTURBATION_COEFFICIENT,
+        LUNAR_MEAN_MOTION,
+        (-1.1151842 + 0.228027132 * d1900) % TAU,
+    );
+
+    let right_ascension_dot = solar_dots.right_ascension + lunar_dots.right_ascension;
+    let argument_of_perigee_dot = solar_dots.argument_of_perigee + lunar_dots.argument_of_perigee;
+    let mean_anomaly_dot = solar_dots.mean_anomaly + lunar_dots.mean_anomaly;
+
+    packed.method = METHOD_DEEP_SPACE;
+    packed.right_ascension_dot = (p14 + right_ascension_dot) as f32;
+    packed.argument_of_perigee_dot = (k14 + argument_of_perigee_dot) as f32;
+    packed.mean_anomaly_dot = (p15 + mean_anomaly_dot) as f32;
+    packed.eccentricity_dot = (solar_dots.eccentricity + lunar_dots.eccentricity) as f32;
+    packed.inclination_dot = (solar_dots.inclination + lunar_dots.inclination) as f32;
+    packed.solar = solar.map(|value| value as f32);
+    packed.lunar = lunar.map(|value| value as f32);
+
+    let one_day = n0 < 0.0052359877 && n0 > 0.0034906585;
+    let half_day = (8.26e-3..=9.24e-3).contains(&n0) && e0 >= 0.5;
+    if !(one_day || half_day) {
+        return Ok(packed);
+    }
+
+    let sidereal_time_0 = sgp4::iau_epoch_to_sidereal_time(epoch);
+    let sin_i = inclination.sin();
+    let (lambda_0, lambda_dot_0, terms) = if one_day {
+        let p17 = 3.0 * (n0 / a0).powi(2);
+        let dr1 = p17
+            * (0.9375 * sin_i * sin_i * (1.0 + 3.0 * p1) - 0.75 * (1.0 + p1))
+            * (1.0 + 2.0 * e0 * e0)
+            * 2.1460748e-6
+            / a0;
+        let dr2 = 2.0
+            * p17
+            * (0.75 * (1.0 + p1).powi(2))
+            * (1.0 + e0 * e0 * (-2.5 + 0.8125 * e0 * e0))
+            * 1.7891679e-6;
+        let dr3 = 3.0
+            * p17
+            * (1.875 * (1.0 + p1).powi(3))
+            * (1.0 + e0 * e0 * (-6.0 + 6.60937 * e0 * e0))
+            * 2.2123015e-7
+            / a0;
+        packed.resonance = RESONANCE_ONE_DAY;
+        (
+            (mean_anomaly + right_ascension + argument_of_perigee - sidereal_time_0) % TAU,
+            p15 + (k14 + p14) - SIDEREAL_SPEED
+                + mean_anomaly_dot
+                + argument_of_perigee_dot
+                + right_ascension_dot
+                - n0,
+            [dr1, dr2, dr3, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+        )
+    } else {
+        packed.resonance = RESONANCE_HALF_DAY;
+        (
+            (mean_anomaly + right_ascension + right_ascension - sidereal_time_0 - sidereal_time_0)
+                % TAU,
+            p15 + mean_anomaly_dot + 2.0 * (p14 + right_ascension_dot - SIDEREAL_SPEED) - n0,
+            half_day_terms(n0, a0, e0, sin_i, p1),
+        )
+    };
+    packed.lambda_0 = lambda_0 as f32;
+    packed.lambda_dot_0 = lambda_dot_0 as f32;
+    packed.sidereal_time_0 = sidereal_time_0 as f32;
+    packed.k14 = k14 as f32;
+    packed.resonance_terms = terms.map(|value| value as f32);
+    Ok(packed)
+}
+
+/// Half-day resonance coefficients `d2201`, `d2211`, `d3210`, `d3222`,
+/// `d4410`, `d4422`, `d5220`, `d5232`, `d5421` and `d5433`.
+fn half_day_terms(n0: f64, a0: f64, e0: f64, sin_i: f64, p1: f64) -> [f64; 10] {
+    let p18 = 3.0 * n0 * n0 * (1.0 / a0).powi(2);
+    let p19 = p18 * (1.0 / a0);
+    let p20 = p19 * (1.0 / a0);
+    let p21 = p20 * (1.0 / a0);
+    let f220 = 0.75 * (1.0 + 2.0 * p1 + p1 * p1);
+    let cubic = |c: [f64; 4]| c[0] + c[1] * e0 + c[2] * e0 * e0 + c[3] * e0.powi(3);
+
+    let (g211, g310, g322, g410, g422) = if e0 <= 0.65 {
+        (
+            3.616 - 13.247 * e0 + 16.29 * e0 * e0,
+            cubic([-19.302, 117.39, -228.419, 156.591]),
+            cubic([-18.9068, 109.7927, -214.6334, 146.5816]),
+            cubic([-41.122, 242.694, -471.094, 313.953]),
+            cubic([-146.407, 841.88, -1629.014, 1083.435]),
+        )
+    } else {
+        (
+            cubic([-72.099, 331.819, -508.738, 266.724]),
+            cubic([-346.844, 1582.851, -2415.925, 1246.113]),
+            cubic([-342.585, 1554.908, -2366.899, 1215.972]),
+            cubic([-1052.797, 4758.686, -7193.992, 3651.957]),
+            cubic([-3581.69, 16178.11, -24462.77, 12422.52]),
+        )
+    };
+    let g520 = if e0 <= 0.65 {
+        cubic([-532.114, 3017.977, -5740.032, 3708.276])
+    } else if e0 < 0.715 {
+        1464.74 - 4664.75 * e0 + 3763.64 * e0 * e0
+    } else {
+        cubic([-5149.66, 29936.92, -54087.36, 31324.56])
+    };
+    let (g532, g521, g533) = if e0 < 0.7 {
+        (
+            cubic([-853.666, 4690.25, -8624.77, 5341.4]),
+            cubic([-822.71072, 4568.6173, -8491.4146, 5337.524]),
+            cubic([-919.2277, 4988.61, -9064.77, 5542.21]),
+        )
+    } else {
+        (
+            cubic([-40023.88, 170470.89, -242699.48, 115605.82]),
+            cubic([-51752.104, 218913.95, -309468.16, 146349.42]),
+            cubic([-37995.78, 161616.52, -229838.2, 109377.94]),
+        )
+    };
+
+    [
+        p18 * 1.7891679e-6 * f220 * (-0.306 - (e0 - 0.64) * 0.44),
+        p18 * 1.7891679e-6 * (1.5 * sin_i * sin_i) * g211,
+        p19 * 3.7393792e-7 * (1.875 * sin_i * (1.0 - 2.0 * p1 - 3.0 * p1 * p1)) * g310,
+        p19 * 3.7393792e-7 * (-1.875 * sin_i * (1.0 + 2.0 * p1 - 3.0 * p1 * p1)) * g322,
+        2.0 * p20 * 7.3636953e-9 * (35.0 * sin_i * sin_i * f220) * g410,
+        2.0 * p20 * 7.3636953e-9 * (39.375 * sin_i.powi(4)) * g422,
+        p21 * 1.1428639e-7
+            * (9.84375
+                * sin_i
+                * (sin_i * sin_i * (1.0 - 2.0 * p1 - 5.0 * p1 * p1)
+                    + 0.33333333 * (-2.0 + 4.0 * p1 + 6.0 * p1 * p1)))
+            * g520,
+        p21 * 1.1428639e-7
+            * (sin_i
+                * (4.92187512 * sin_i * sin_i * (-2.0 - 4.0 * p1 + 10.0 * p1 * p1)
+                    + 6.56250012 * (1.0 + 2.0 * p1 - 3.0 * p1 * p1)))
+            * g532,
+        2.0 * p21
+            * 2.1765803e-9
+            * (29.53125 * sin_i * (2.0 - 8.0 * p1 + p1 * p1 * (-12.0 + 8.0 * p1 + 10.0 * p1 * p1)))
+            * g521,
+        2.0 * p21
+            * 2.1765803e-9
+            * (29.53125 * sin_i * (-2.0 - 8.0 * p1 + p1 * p1 * (12.0 + 8.0 * p1 - 10.0 * p1 * p1)))
+            * g533,
+    ]
+}
+
+/// Secular rates contributed by one perturbing body.
+struct Dots {
+    inclination: f64,
+    right_ascension: f64,
+    eccentricity: f64,
+    argument_of_perigee: f64,
+    mean_anomaly: f64,
+}
+
+/// Inputs of the lunar or solar perturbation set-up.
+struct ThirdBody {
+    inclination_0: f64,
+    eccentricity_0: f64,
+    argument_of_perigee_0: f64,
+    n0: f64,
+    sin_inclination: f64,
+    cos_inclination: f64,
+    sin_delta_right_ascension: f64,
+    cos_delta_right_ascension: f64,
+    eccentricity: f64,
+    sin_argument_of_perigee: f64,
+    cos_argument_of_perigee: f64,
+    coefficient: f64,
+    mean_motion: f64,
+    mean_anomaly_0: f64,
+    p2: f64,
+    b0: f64,
+}
+
+impl ThirdBody {
+    /// Long-period coefficients `kx0..kx11` followed by the body's mean
+    /// anomaly at epoch, and the secular rates.
+    fn perturbations_and_dots(&self) -> ([f64; 13], Dots) {
+        let (sin_w, cos_w) = (self.sin_argument_of_perigee, self.cos_argument_of_perigee);
+        let (sin_o, cos_o) = (
+            self.sin_delta_right_ascension,
+            self.cos_delta_right_ascension,
+        );
+        let (sin_i3, cos_i3) = (self.sin_inclination, self.cos_inclination);
+        let (sin_i0, cos_i0) = (self.inclination_0.sin(), self.inclination_0.cos());
+        let (sin_w0, cos_w0) = (
+            self.argument_of_perigee_0.sin(),
+            self.argument_of_perigee_0.cos(),
+        );
+        let e2 = self.eccentricity_0 * self.eccentricity_0;
+
+        let ax1 = cos_w * cos_o + sin_w * cos_i3 * sin_o;
+        let ax3 = -sin_w * cos_o + cos_w * cos_i3 * sin_o;
+        let ax7 = -cos_w * sin_o + sin_w * cos_i3 * cos_o;
+        let ax8 = sin_w * sin_i3;
+        let ax9 = sin_w * sin_o + cos_w * cos_i3 * cos_o;
+        let ax10 = cos_w * sin_i3;
+        let ax2 = cos_i0 * ax7 + sin_i0 * ax8;
+        let ax4 = cos_i0 * ax9 + sin_i0 * ax10;
+        let ax5 = -sin_i0 * ax7 + cos_i0 * ax8;
+        let ax6 = -sin_i0 * ax9 + cos_i0 * ax10;
+
+        let xx1 = ax1 * cos_w0 + ax2 * sin_w0;
+        let xx2 = ax3 * cos_w0 + ax4 * sin_w0;
+        let xx3 = -ax1 * sin_w0 + ax2 * cos_w0;
+        let xx4 = -ax3 * sin_w0 + ax4 * cos_w0;
+        let xx5 = ax5 * sin_w0;
+        let xx6 = ax6 * sin_w0;
+        let xx7 = ax5 * cos_w0;
+        let xx8 = ax6 * cos_w0;
+
+        let zx31 = 12.0 * xx1 * xx1 - 3.0 * xx3 * xx3;
+        let zx32 = 24.0 * xx1 * xx2 - 6.0 * xx3 * xx4;
+        let zx33 = 12.0 * xx2 * xx2 - 3.0 * xx4 * xx4;
+        let zx11 = -6.0 * ax1 * ax5 + e2 * (-24.0 * xx1 * xx7 - 6.0 * xx3 * xx5);
+        let zx13 = -6.0 * ax3 * ax6 + e2 * (-24.0 * xx2 * xx8 - 6.0 * xx4 * xx6);
+        let zx21 = 6.0 * ax2 * ax5 + e2 * (24.0 * xx1 * xx5 - 6.0 * xx3 * xx7);
+        let zx23 = 6.0 * ax4 * ax6 + e2 * (24.0 * xx2 * xx6 - 6.0 * xx4 * xx8);
+        let zx1 = (3.0 * (ax1 * ax1 + ax2 * ax2) + zx31 * e2) * 2.0 + self.p2 * zx31;
+        let zx3 = (3.0 * (ax3 * ax3 + ax4 * ax4) + zx33 * e2) * 2.0 + self.p2 * zx33;
+
+        let px0 = self.coefficient / self.n0;
+        let px1 = -0.5 * px0 / self.b0;
+        let px2 = px0 * self.b0;
+        let px3 = -15.0 * self.eccentricity_0 * px2;
+        let n3 = self.mean_motion;
+
+        let right_ascension_dot =
+            if (5.2359877e-2..=PI - 5.2359877e-2).contains(&self.inclination_0) {
+                -n3 * px1 * (zx21 + zx23) / sin_i0
+            } else {
+                0.0
+            };
+
+        let coefficients = [
+            2.0 * px3 * (xx2 * xx3 + xx1 * xx4),
+            2.0 * px3 * (xx2 * xx4 - xx1 * xx3),
+            2.0 * px1
+                * (-6.0 * (ax1 * ax6 + ax3 * ax5)
+                    + e2 * (-24.0 * (xx2 * xx7 + xx1 * xx8) - 6.0 * (xx3 * xx6 + xx4 * xx5))),
+            2.0 * px1 * (zx13 - zx11),
+            -2.0 * px0 * ((6.0 * (ax1 * ax3 + ax2 * ax4) + zx32 * e2) * 2.0 + self.p2 * zx32),
+            -2.0 * px0 * (zx3 - zx1),
+            -2.0 * px0 * (-21.0 - 9.0 * e2) * self.eccentricity,
+            2.0 * px2 * zx32,
+            2.0 * px2 * (zx33 - zx31),
+            -18.0 * px2 * self.eccentricity,
+            -2.0 * px1
+                * (6.0 * (ax4 * ax5 + ax2 * ax6)
+                    + e2 * (24.0 * (xx2 * xx5 + xx1 * xx6) - 6.0 * (xx4 * xx7 + xx3 * xx8))),
+            -2.0 * px1 * (zx23 - zx21),
+            self.mean_anomaly_0,
+        ];
+        let dots = Dots {
+            inclination: px1 * n3 * (zx11 + zx13),
+            right_ascension: right_ascension_dot,
+            eccentricity: px3 * n3 * (xx1 * xx3 + xx2 * xx4),
+            argument_of_perigee: px2 * n3 * (zx31 + zx33 - 6.0) - cos_i0 * right_ascension_dot,
+            mean_anomaly: -n3 * px0 * (zx1 + zx3 - 14.0 - 6.0 * e2),
+        };
+        (coefficients, dots)
+    }
+}
+
+// f32 copies of the constants used by the kernel.
+const AE_F32: f32 = AE as f32;
+const KE_F32: f32 = KE as f32;
+const J2_F32: f32 = J2 as f32;
+const J3_OVER_J2_F32: f32 = (J3 / J2) as f32;
+const VELOCITY_SCALE_F32: f32 = (AE * KE / 60.0) as f32;
+const SIDEREAL_SPEED_F32: f32 = SIDEREAL_SPEED as f32;
+const TAU_F32: f32 = std::f32::consts::TAU;
+const PI_F32: f32 = std::f32::consts::PI;
+
+/// Mean elements and short-period coefficients at the target time.
+struct MeanState {
+    inclination: f32,
+    right_ascension: f32,
+    eccentricity: f32,
+    argument_of_perigee: f32,
+    mean_anomaly: f32,
+    mean_motion: f32,
+    a: f32,
+    k2: f32,
+    k3: f32,
+    k4: f32,
+    k5: f32,
+    k6: f32,
+}
+
+/// SGP4 propagator that reproduces the GPU kernels' f32 arithmetic on the host.
+///
+/// Use it to predict what a GPU backend will return for an object, or to
+/// measure how far FP32 results drift from [`Sgp4Propagator`]; see
+/// [`crate::error_budget`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fp32Propagator {
+    elements: PackedElements,
+}
+
+impl Fp32Propagator {
+    /// Create a propagator from a pair of TLE lines.
+    pub fn from_tle(line1: &str, line2: &str) -> Result<Self, PropagationError> {
+        Ok(Self::from(&Sgp4Propagator::from_tle(None, line1, line2)?))
+    }
+
+    /// Constants in the layout uploaded to GPU backends.
+    pub fn packed(&self) -> &PackedElements {
+        &self.elements
+    }
+
+    /// Propagate a state by minutes since the TLE epoch.
+    ///
+    /// The offset is rounded to f32 first, as it is for GPU dispatches.
+    pub fn propagate_minutes(&self, minutes_since_epoch: f64) -> Result<StateVector, ObjectStatus> {
+        propagate(&self.elements, minutes_since_epoch as f32)
+    }
+}
+
+impl From<&Sgp4Propagator> for Fp32Propagator {
+    fn from(propagator: &Sgp4Propagator) -> Self {
+        Self {
+            elements: PackedElements::from_elements(&propagator.elements),
+        }
+    }
+}
+
+/// Propagate one packed object `minutes_since_epoch` minutes in f32.
+///
+/// This is the host reference of the WGSL kernel; the returned state is the
+/// f32 result widened to f64, as a GPU readback would be.
+pub fn propagate(
+    elements: &PackedElements,
+    minutes_since_epoch: f32,
+) -> Result<StateVector, ObjectStatus> {
+    let status = elements.status();
+    if !status.is_ok() {
+        return Err(status);
+    }
+    if !minutes_since_epoch.is_finite() {
+        return Err(ObjectStatus::InvalidTime);
+    }
+    let t = minutes_since_epoch;
+
+    let p22 = elements.right_ascension + elements.right_ascension_dot * t + elements.k0 * t * t;
+    let p23 = elements.argument_of_perigee + elements.argument_of_perigee_dot * t;
+    let mean = if elements.method == METHOD_DEEP_SPACE {
+        deep_space_mean_state(elements, t, p22, p23)?
+    } else {
+        near_earth_mean_state(elements, t, p22, p23)?
+    };
+
+    // Long-period periodics and Kepler's equation.
+    let p37 = 1.0 / (mean.a * (1.0 - mean.eccentricity * mean.eccentricity));
+    let axn = mean.eccentricity * mean.argument_of_perigee.cos();
+    let ayn = mean.eccentricity * mean.argument_of_perigee.sin() + p37 * mean.k2;
+    let p38 = (mean.mean_anomaly + mean.argument_of_perigee + p37 * mean.k5 * axn) % TAU_F32;
+
+    let mut ew = p38;
+    for _ in 0..10 {
+        let delta =
+            (p38 - ayn * ew.cos() + axn * ew.sin() - ew) / (1.0 - ew.cos() * axn - ew.sin() * ayn);
+        if delta.abs() < KEPLER_TOLERANCE {
+            break;
+        }
+        ew += delta.clamp(-0.95, 0.95);
+    }
+
+    // Short-period periodics.
+    let p39 = axn * axn + ayn * ayn;
+    let pl = mean.a * (1.0 - p39);
+    if pl < 0.0 {
+        return Err(ObjectStatus::SemiLatusRectumNegative);
+    }
+    let p40 = axn * ew.sin() - ayn * ew.cos();
+    let r = mean.a * (1.0 - (axn * ew.cos() + ayn * ew.sin()));
+    let r_dot = mean.a.sqrt() * p40 / r;
+    let b = (1.0 - p39).sqrt();
+    let p41 = p40 / (1.0 + b);
+    let p42 = mean.a / r * (ew.sin() - ayn - axn * p41);
+    let p43 = mean.a / r * (ew.cos() - axn + ayn * p41);
+    let u = p42.atan2(p43);
+    let p44 = 2.0 * p43 * p42;
+    let p45 = 1.0 - 2.0 * p42 * p42;
+    let p46 = 0.5 * J2_F32 / pl / pl;
+    let rk = r * (1.0 - 1.5 * p46 * b * mean.k6) + 0.5 * (0.5 * J2_F32 / pl) * mean.k3 * p45;
+    // Below one Earth radius.
+    if rk < 1.0 {
+        return Err(ObjectStatus::Decayed);
+    }
+    let uk = u - 0.25 * p46 * mean.k4 * p44;
+    let inclination_k =
+        mean.inclination + 1.5 * p46 * mean.inclination.cos() * mean.inclination.sin() * p45;
+    let right_ascension_k = mean.right_ascension + 1.5 * p46 * mean.inclination.cos() * p44;
+    let rk_dot = r_dot - mean.mean_motion * (0.5 * J2_F32 / pl) * mean.k3 * p44 / KE_F32;
+    let rfk_dot = pl.sqrt() / r
+        + mean.mean_motion * (0.5 * J2_F32 / pl) * (mean.k3 * p45 + 1.5 * mean.k6) / KE_F32;
+
+    let (sin_o, cos_o) = (right_ascension_k.sin(), right_ascension_k.cos());
+    let (sin_i, cos_i) = (inclination_k.sin(), inclination_k.cos());
+    let (sin_u, cos_u) = (uk.sin(), uk.cos());
+    let u0 = -sin_o * cos_i * sin_u + cos_o * cos_u;
+    let u1 = cos_o * cos_i * sin_u + sin_o * cos_u;
+    let u2 = sin_i * sin_u;
+    let v0 = -sin_o * cos_i * cos_u - cos_o * sin_u;
+    let v1 = cos_o * cos_i * cos_u - sin_o * sin_u;
+    let v2 = sin_i * cos_u;
+
+    let position = [rk * u0 * AE_F32, rk * u1 * AE_F32, rk * u2 * AE_F32];
+    let velocity = [
+        (rk_dot * u0 + rfk_dot * v0) * VELOCITY_SCALE_F32,
+        (rk_dot * u1 + rfk_dot * v1) * VELOCITY_SCALE_F32,
+        (rk_dot * u2 + rfk_dot * v2) * VELOCITY_SCALE_F32,
+    ];
+    Ok(StateVector {
+        position_km: position.map(f64::from),
+        velocity_km_s: velocity.map(f64::from),
+    })
+}
+
+fn near_earth_mean_state(
+    elements: &PackedElements,
+    t: f32,
+    p22: f32,
+    p23: f32,
+) -> Result<MeanState, ObjectStatus> {
+    let e = elements;
+    let t2 = t * t;
+    let p24 = e.mean_anomaly + e.mean_anomaly_dot * t;
+    let (argument_of_perigee, mean_anomaly, a, p27) = if e.method == METHOD_NEAR_EARTH_LOW_PERIGEE {
+        let c1t = 1.0 - e.c1 * t;
+        (
+            p23,
+            p24 + e.mean_motion * e.k1 * t2,
+            e.a0 * c1t * c1t,
+            e.eccentricity - e.c4 * t,
+        )
+    } else {
+        let damping = 1.0 + e.eta * p24.cos();
+        let p25 = e.k13 * (damping * damping * damping - e.k11) + e.k12 * t;
+        let p26 = p24 + p25;
+        let t3 = t2 * t;
+        let t4 = t3 * t;
+        let c1t = 1.0 - e.c1 * t - e.d2 * t2 - e.d3 * t3 - e.d4 * t4;
+        (
+            p23 - p25,
+            p26 + e.mean_motion * (e.k1 * t2 + e.k8 * t3 + t4 * (e.k9 + t * e.k10)),
+            e.a0 * c1t * c1t,
+            e.eccentricity - (e.c4 * t + e.c5 * (p26.sin() - e.k7)),
+        )
+    };
+    if !(-0.001..1.0).contains(&p27) {
+        return Err(ObjectStatus::EccentricityOutOfRange);
+    }
+    Ok(MeanState {
+        inclination: e.inclination,
+        right_ascension: p22,
+        eccentricity: p27.max(1.0e-6),
+        argument_of_perigee,
+        mean_anomaly,
+        mean_motion: KE_F32 / (a * a.sqrt()),
+        a,
+        k2: e.k2,
+        k3: e.k3,
+        k4: e.k4,
+        k5: e.k5,
+        k6: e.k6,
+    })
+}
+
+fn deep_space_mean_state(
+    elements: &PackedElements,
+    t: f32,
+    p22: f32,
+    p23: f32,
+) -> Result<MeanState, ObjectStatus> {
+    let e = elements;
+    let (p28, p29) = if e.resonance == RESONANCE_NONE {
+        (e.a0, e.mean_anomaly + e.mean_anomaly_dot * t)
+    } else {
+        integrate_resonance(e, t, p22, p23)?
+    };
+
+    let solar = third_body_periodics(
+        &e.solar,
+        SOLAR_ECCENTRICITY as f32,
+        SOLAR_MEAN_MOTION as f32,
+        t,
+    );
+    let lunar = third_body_periodics(
+        &e.lunar,
+        LUNAR_ECCENTRICITY as f32,
+        LUNAR_MEAN_MOTION as f32,
+        t,
+    );
+    let delta_eccentricity = solar[0] + lunar[0];
+    let delta_inclination = solar[1] + lunar[1];
+    let delta_mean_anomaly = solar[2] + lunar[2];
+    let p4 = solar[3] + lunar[3];
+    let p5 = solar[4] + lunar[4];
+
+    let inclination = e.inclination + e.inclination_dot * t + delta_inclination;
+    let (sin_i, cos_i) = (inclination.sin(), inclination.cos());
+    let (right_ascension, argument_of_perigee) = if inclination >= 0.2 {
+        (p22 + p5 / sin_i, p23 + p4 - cos_i * (p5 / sin_i))
+    } else {
+        // Lyddane modification for low inclinations.
+        let (sin_o, cos_o) = (p22.sin(), p22.cos());
+        let p30 = (sin_i * sin_o + (p5 * cos_o + delta_inclination * cos_i * sin_o))
+            .atan2(sin_i * cos_o + (-p5 * sin_o + delta_inclination * cos_i * cos_o));
+        let p22_wrapped = p22 % TAU_F32;
+        let right_ascension = if p30 < p22_wrapped - PI_F32 {
+            p30 + TAU_F32
+        } else if p30 > p22_wrapped + PI_F32 {
+            p30 - TAU_F32
+        } else {
+            p30
+        };
+        (
+            right_ascension,
+            p23 + p4 + cos_i * (p22_wrapped - right_ascension)
+                - delta_inclination * p22_wrapped * sin_i,
+        )
+    };
+
+    let p31 = e.eccentricity + e.eccentricity_dot * t - e.c4 * t;
+    if !(-0.001..1.0).contains(&p31) {
+        return Err(ObjectStatus::EccentricityOutOfRange);
+    }
+    let eccentricity = p31.max(1.0e-6) + delta_eccentricity;
+    if !(0.0..=1.0).contains(&eccentricity) {
+        return Err(ObjectStatus::PerturbedEccentricityOutOfRange);
+    }
+    let c1t = 1.0 - e.c1 * t;
+    let a = p28 * c1t * c1t;
+    let k5_denominator = if (1.0 + cos_i).abs() > 1.5e-12 {
+        1.0 + cos_i
+    } else {
+        1.5e-12
+    };
+    Ok(MeanState {
+        inclination,
+        right_ascension,
+        eccentricity,
+        argument_of_perigee,
+        mean_anomaly: p29 + delta_mean_anomaly + e.mean_motion * e.k1 * t * t,
+        mean_motion: KE_F32 / (a * a.sqrt()),
+        a,
+        k2: -0.5 * J3_OVER_J2_F32 * sin_i,
+        k3: 1.0 - cos_i * cos_i,
+        k4: 7.0 * cos_i * cos_i - 1.0,
+        k5: -0.25 * J3_OVER_J2_F32 * sin_i * (3.0 + 5.0 * cos_i) / k5_denominator,
+        k6: 3.0 * cos_i * cos_i - 1.0,
+    })
+}
+
+/// Lunar or solar long-period terms: eccentricity, inclination and mean
+/// anomaly offsets, then the argument of perigee and node terms.
+fn third_body_periodics(
+    coefficients: &[f32; 13],
+    eccentricity: f32,
+    mean_motion: f32,
+    t: f32,
+) -> [f32; 5] {
+    let k = coefficients;
+    let mean_anomaly = k[12] + mean_motion * t;
+    let fx = mean_anomaly + 2.0 * eccentricity * mean_anomaly.sin();
+    let sin_fx = fx.sin();
+    let f2 = 0.5 * sin_fx * sin_fx - 0.25;
+    let f3 = -0.5 * sin_fx * fx.cos();
+    [
+        k[0] * f2 + k[1] * f3,
+        k[2] * f2 + k[3] * f3,
+        k[4] * f2 + k[5] * f3 + k[6] * sin_fx,
+        k[7] * f2 + k[8] * f3 + k[9] * sin_fx,
+        k[10] * f2 + k[11] * f3,
+    ]
+}
+
+/// Integrate the resonance equations from epoch to `t` in 720-minute steps.
+///
+/// Unlike the [`sgp4`] crate, no integrator state is carried between calls:
+/// every GPU invocation starts from epoch. Returns the semi-major axis and
+/// the mean anomaly.
+fn integrate_resonance(
+    e: &PackedElements,
+    t: f32,
+    p22: f32,
+    p23: f32,
+) -> Result<(f32, f32), ObjectStatus> {
+    let sidereal_time = (e.sidereal_time_0 + t * SIDEREAL_SPEED_F32) % TAU_F32;
+    let step = if t > 0.0 {
+        RESONANCE_STEP
+    } else {
+        -RESONANCE_STEP
+    };
+    let half_step_squared = RESONANCE_STEP * RESONANCE_STEP / 2.0;
+    let d = &e.resonance_terms;
+
+    let mut ti = 0.0f32;
+    let mut mean_motion = e.mean_motion;
+    let mut lambda = e.lambda_0;
+    for _ in 0..MAX_RESONANCE_STEPS {
+        let lambda_dot = mean_motion + e.lambda_dot_0;
+        let (ni_dot, ni_ddot) = if e.resonance == RESONANCE_ONE_DAY {
+            (
+                d[0] * (lambda - LAMBDA31).sin()
+                    + d[1] * (2.0 * (lambda - LAMBDA22)).sin()
+                    + d[2] * (3.0 * (lambda - LAMBDA33)).sin(),
+                (d[0] * (lambda - LAMBDA31).cos()
+                    + 2.0 * d[1] * (2.0 * (lambda - LAMBDA22)).cos()
+                    + 3.0 * d[2] * (3.0 * (lambda - LAMBDA33)).cos())
+                    * lambda_dot,
+            )
+        } else {
+            let w = e.argument_of_perigee + e.k14 * ti;
+            (
+                d[0] * (2.0 * w + lambda - G22).sin()
+                    + d[1] * (lambda - G22).sin()
+                    + d[2] * (w + lambda - G32).sin()
+                    + d[3] * (-w + lambda - G32).sin()
+                    + d[4] * (2.0 * w + 2.0 * lambda - G44).sin()
+                    + d[5] * (2.0 * lambda - G44).sin()
+                    + d[6] * (w + lambda - G52).sin()
+                    + d[7] * (-w + lambda - G52).sin()
+                    + d[8] * (w + 2.0 * lambda - G54).sin()
+                    + d[9] * (-w + 2.0 * lambda - G54).sin(),
+                (d[0] * (2.0 * w + lambda - G22).cos()
+                    + d[1] * (lambda - G22).cos()
+                    + d[2] * (w + lambda - G32).cos()
+                    + d[3] * (-w + lambda - G32).cos()
+                    + d[6] * (w + lambda - G52).cos()
+                    + d[7] * (-w + lambda - G52).cos()
+                    + 2.0
+                        * (d[4] * (2.0 * w + 2.0 * lambda - G44).cos()
+                            + d[5] * (2.0 * lambda - G44).cos()
+                            + d[8] * (w + 2.0 * lambda - G54).cos()
+                            + d[9] * (-w + 2.0 * lambda - G54).cos()))
+                    * lambda_dot,
+            )
+        };
+
+        let done = if t > 0.0 {
+            t - step < ti
+        } else {
+            t - step > ti
+        };
+        if done {
+            let dt = t - ti;
+            let n = mean_motion + ni_dot * dt + ni_ddot * dt * dt * 0.5;
+            let lambda_t = lambda + lambda_dot * dt + ni_dot * dt * dt * 0.5;
+            let mean_anomaly = if e.resonance == RESONANCE_ONE_DAY {
+                lambda_t - p22 - p23 + sidereal_time
+            } else {
+                lambda_t - 2.0 * p22 + 2.0 * sidereal_time
+            };
+            return Ok(((KE_F32 / n).powf(2.0 / 3.0), mean_anomaly));
+        }
+
+        ti += step;
+        mean_motion += ni_dot * step + ni_ddot * half_step_squared;
+        lambda += lambda_dot * step + ni_dot * half_step_squared;
+    }
+    Err(ObjectStatus::InvalidTime)
+}
@@ -0,0 +1,366 @@
+//! Reference frame conversions for propagated states.
+//!
+//! SGP4 produces states in the True Equator, Mean Equinox (TEME) frame. This
+//! module converts them to the Earth-fixed ITRF (ECEF) frame and to the
+//! inertial GCRF/J2000 frame following Vallado et al., "Revisiting Spacetrack
+//! Report #3" (AIAA 2006-6753): GMST from the IAU-82 model, optional polar
+//! motion, and the IAU-76/FK5 precession with IAU-80 nutation.
+//!
+//! States carry their frame as a type parameter and their epoch as a field,
+//! so a conversion can never be applied to the wrong frame or paired with the
+//! wrong instant.
+use std::fmt;
+use std::marker::PhantomData;
+
+use sgp4::chrono::NaiveDateTime;
+
+use crate::math::{
+    add, cross, mat_mul, mat_vec, rot1, rot2, rot3, sub, transpose, Mat3, Vec3, ARCSEC_TO_RAD,
+};
+use crate::time::{julian_date_ut1, tt_centuries, J2000_JD, SECONDS_PER_DAY};
+use crate::StateVector;
+
+/// Nominal Earth rotation rate in rad/s (Vallado, IERS conventions).
+pub const EARTH_ROTATION_RATE_RAD_S: f64 = 7.292_115_146_706_979e-5;
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Marker trait implemented by the supported reference frames.
+pub trait Frame: private::Sealed {
+    /// Short human-readable frame name.
+    const NAME: &'static str;
+}
+
+/// True Equator, Mean Equinox frame used by SGP4 output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Teme;
+
+/// International Terrestrial Reference Frame (Earth-centred, Earth-fixed).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Itrf;
+
+/// Geocentric Celestial Reference Frame, realised through IAU-76/FK5 J2000.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gcrf;
+
+impl private::Sealed for Teme {}
+impl private::Sealed for Itrf {}
+impl private::Sealed for Gcrf {}
+
+impl Frame for Teme {
+    const NAME: &'static str = "TEME";
+}
+
+impl Frame for Itrf {
+    const NAME: &'static str = "ITRF";
+}
+
+impl Frame for Gcrf {
+    const NAME: &'static str = "GCRF";
+}
+
+/// Position (km) and velocity (km/s) at a UTC epoch in frame `F`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameState<F: Frame> {
+    pub position_km: [f64; 3],
+    pub velocity_km_s: [f64; 3],
+    pub epoch: NaiveDateTime,
+    frame: PhantomData<F>,
+}
+
+/// State expressed in the TEME frame.
+pub type TemeState = FrameState<Teme>;
+/// State expressed in the Earth-fixed ITRF frame.
+pub type ItrfState = FrameState<Itrf>;
+/// State expressed in the GCRF/J2000 frame.
+pub type GcrfState = FrameState<Gcrf>;
+
+impl<F: Frame> FrameState<F> {
+    /// Build a state that is already known to be expressed in frame `F`.
+    pub fn new(position_km: [f64; 3], velocity_km_s: [f64; 3], epoch: NaiveDateTime) -> Self {
+        Self {
+            position_km,
+            velocity_km_s,
+            epoch,
+            frame: PhantomData,
+        }
+    }
+
+    /// Name of the frame this state is expressed in.
+    pub fn frame_name(&self) -> &'static str {
+        F::NAME
+    }
+}
+
+impl<F: Frame> fmt::Display for FrameState<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} @ {} r = [{:.6}, {:.6}, {:.6}] km v = [{:.9}, {:.9}, {:.9}] km/s",
+            F::NAME,
+            self.epoch,
+            self.position_km[0],
+            self.position_km[1],
+            self.position_km[2],
+            self.velocity_km_s[0],
+            self.velocity_km_s[1],
+            self.velocity_km_s[2]
+        )
+    }
+}
+
+impl StateVector {
+    /// Tag a raw SGP4 output with the UTC instant it was propagated to.
+    pub fn into_teme(self, epoch: NaiveDateTime) -> TemeState {
+        TemeState::new(self.position_km, self.velocity_km_s, epoch)
+    }
+}
+
+/// Earth orientation parameters for a single day.
+///
+/// All fields default to zero, which is the usual choice when no IERS
+/// bulletin is available; the resulting error is at most a few tens of
+/// metres on the Earth's surface.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EarthOrientation {
+    /// UT1 − UTC in seconds.
+    pub dut1_s: f64,
+    /// Excess length of day in seconds.
+    pub lod_s: f64,
+    /// Polar motion x coordinate in arcseconds.
+    pub xp_arcsec: f64,
+    /// Polar motion y coordinate in arcseconds.
+    pub yp_arcsec: f64,
+    /// Celestial pole offset in longitude (IAU-80) in arcseconds.
+    pub ddpsi_arcsec: f64,
+    /// Celestial pole offset in obliquity (IAU-80) in arcseconds.
+    pub ddeps_arcsec: f64,
+}
+
+/// Greenwich mean sidereal time (IAU-82) in radians for a UT1 Julian date.
+pub fn gmst_iau82(jd_ut1: f64) -> f64 {
+    let t = (jd_ut1 - J2000_JD) / 36_525.0;
+    let seconds = -6.2e-6 * t.powi(3)
+        + 0.093104 * t.powi(2)
+        + (876_600.0 * 3600.0 + 8_640_184.812_866) * t
+        + 67_310.548_41;
+    (seconds * std::f64::consts::PI / 43_200.0).rem_euclid(std::f64::consts::TAU)
+}
+
+impl TemeState {
+    /// Rotate into the Earth-fixed ITRF frame.
+    pub fn to_itrf(&self, eop: &EarthOrientation) -> ItrfState {
+        let st = rot3(gmst_iau82(julian_date_ut1(self.epoch, eop.dut1_s)));
+        let pm = polar_motion(eop);
+        let omega = earth_rotation(eop);
+
+        let r_pef = mat_vec(&st, self.position_km);
+        let v_pef = sub(mat_vec(&st, self.velocity_km_s), cross(omega, r_pef));
+
+        let pm_t = transpose(&pm);
+        ItrfState::new(mat_vec(&pm_t, r_pef), mat_vec(&pm_t, v_pef), self.epoch)
+    }
+
+    /// Rotate into the inertial GCRF/J2000 frame.
+    pub fn to_gcrf(&self, eop: &EarthOrientation) -> GcrfState {
+        let m = teme_to_gcrf_matrix(self.epoch, eop);
+        GcrfState::new(
+            mat_vec(&m, self.position_km),
+            mat_vec(&m, self.velocity_km_s),
+            self.epoch,
+        )
+    }
+}
+
+impl ItrfState {
+    /// Rotate back into the TEME frame.
+    pub fn to_teme(&self, eop: &EarthOrientation) -> TemeState {
+        let st_t = transpose(&rot3(gmst_iau82(julian_date_ut1(self.epoch, eop.dut1_s))));
+        let pm = polar_motion(eop);
+        let omega = earth_rotation(eop);
+
+        let r_pef = mat_vec(&pm, self.position_km);
+        let v_pef = mat_vec(&pm, self.velocity_km_s);
+
+        TemeState::new(
+            mat_vec(&st_t, r_pef),
+            mat_vec(&st_t, add(v_pef, cross(omega, r_pef))),
+            self.epoch,
+        )
+    }
+
+    /// Rotate into the inertial GCRF/J2000 frame.
+    pub fn to_gcrf(&self, eop: &EarthOrientation) -> GcrfState {
+        self.to_teme(eop).to_gcrf(eop)
+    }
+}
+
+impl GcrfState {
+    /// Rotate back into the TEME frame.
+    pub fn to_teme(&self, eop: &EarthOrientation) -> TemeState {
+        let m = transpose(&teme_to_gcrf_matrix(self.epoch, eop));
+        TemeState::new(
+            mat_vec(&m, self.position_km),
+            mat_vec(&m, self.velocity_km_s),
+            self.epoch,
+        )
+    }
+
+    /// Rotate into the Earth-fixed ITRF frame.
+    pub fn to_itrf(&self, eop: &EarthOrientation) -> ItrfState {
+        self.to_teme(eop).to_itrf(eop)
+    }
+}
+
+fn earth_rotation(eop: &EarthOrientation) -> Vec3 {
+    [
+        0.0,
+        0.0,
+        EARTH_ROTATION_RATE_RAD_S * (1.0 - eop.lod_s / SECONDS_PER_DAY),
+    ]
+}
+
+/// Polar motion matrix `W` such that `r_pef = W r_itrf` (IAU-80 convention).
+fn polar_motion(eop: &EarthOrientation) -> Mat3 {
+    let xp = eop.xp_arcsec * ARCSEC_TO_RAD;
+    let yp = eop.yp_arcsec * ARCSEC_TO_RAD;
+    mat_mul(&rot1(yp), &rot2(xp))
+}
+
+/// Matrix taking TEME vectors to GCRF/J2000 at the given UTC instant.
+fn teme_to_gcrf_matrix(epoch: NaiveDateTime, eop: &EarthOrientation) -> Mat3 {
+    let ttt = tt_centuries(epoch);
+    let nutation = nutation_iau80(ttt, eop);
+
+    // TEME -> TOD: undo the equation of the equinoxes.
+    let eqe = nutation.delta_psi * nutation.mean_obliquity.cos();
+    let teme_to_tod = rot3(-eqe);
+
+    // TOD -> MOD.
+    let tod_to_mod = mat_mul(
+        &mat_mul(&rot1(-nutation.mean_obliquity), &rot3(nutation.delta_psi)),
+        &rot1(nutation.true_obliquity),
+    );
+
+    // MOD -> J2000 (IAU-76 precession).
+    let (zeta, theta, z) = precession_iau76(ttt);
+    let mod_to_j2000 = mat_mul(&mat_mul(&rot3(zeta), &rot2(-theta)), &rot3(z));
+
+    mat_mul(&mat_mul(&mod_to_j2000, &tod_to_mod), &teme_to_tod)
+}
+
+/// IAU-76 precession angles (zeta, theta, z) in radians.
+fn precession_iau76(ttt: f64) -> (f64, f64, f64) {
+    let t2 = ttt * ttt;
+    let t3 = t2 * ttt;
+    let zeta = 2306.2181 * ttt + 0.30188 * t2 + 0.017998 * t3;
+    let theta = 2004.3109 * ttt - 0.42665 * t2 - 0.041833 * t3;
+    let z = 2306.2181 * ttt + 1.09468 * t2 + 0.018203 * t3;
+    (
+        zeta * ARCSEC_TO_RAD,
+        theta * ARCSEC_TO_RAD,
+        z * ARCSEC_TO_RAD,
+    )
+}
+
+struct Nutation {
+    delta_psi: f64,
+    mean_obliquity: f64,
+    true_obliquity: f64,
+}
+
+/// IAU-1980 nutation series coefficients: multipliers of (l, l', F, D, Ω)
+/// followed by Δψ (A + B·T) and Δε (C + D·T) in units of 0.1 mas.
+///
+/// Terms below 0.5 mas are omitted; their combined effect stays under a few
+/// centimetres at GEO distances.
+#[rustfmt::skip]
+const NUTATION_IAU80: [([i8; 5], f64, f64, f64, f64); 48] = [
+    ([ 0,  0,  0,  0, 1], -171_996.0, -174.2, 92_025.0,  8.9),
+    ([ 0,  0,  2, -2, 2],  -13_187.0,   -1.6,  5_736.0, -3.1),
+    ([ 0,  0,  2,  0, 2],   -2_274.0,   -0.2,    977.0, -0.5),
+    ([ 0,  0,  0,  0, 2],    2_062.0,    0.2,   -895.0,  0.5),
+    ([ 0,  1,  0,  0, 0],    1_426.0,   -3.4,     54.0, -0.1),
+    ([ 1,  0,  0,  0, 0],      712.0,    0.1,     -7.0,  0.0),
+    ([ 0,  1,  2, -2, 2],     -517.0,    1.2,    224.0, -0.6),
+    ([ 0,  0,  2,  0, 1],     -386.0,   -0.4,    200.0,  0.0),
+    ([ 1,  0,  2,  0, 2],     -301.0,    0.0,    129.0, -0.1),
+    ([ 0, -1,  2, -2, 2],      217.0,   -0.5,    -95.0,  0.3),
+    ([ 1,  0,  0, -2, 0],     -158.0,    0.0,     -1.0,  0.0),
+    ([ 0,  0,  2, -2, 1],      129.0,    0.1,    -70.0,  0.0),
+    ([-1,  0,  2,  0, 2],      123.0,    0.0,    -53.0,  0.0),
+    ([ 1,  0,  0,  0, 1],       63.0,    0.1,    -33.0,  0.0),
+    ([ 0,  0,  0,  2, 0],       63.0,    0.0,     -2.0,  0.0),
+    ([-1,  0,  2,  2, 2],      -59.0,    0.0,     26.0,  0.0),
+    ([-1,  0,  0,  0, 1],      -58.0,   -0.1,     32.0,  0.0),
+    ([ 1,  0,  2,  0, 1],      -51.0,    0.0,     27.0,  0.0),
+    ([ 2,  0,  0, -2, 0],       48.0,    0.0,      1.0,  0.0),
+    ([-2,  0,  2,  0, 1],       46.0,    0.0,    -24.0,  0.0),
+    ([ 0,  0,  2,  2, 2],      -38.0,    0.0,     16.0,  0.0),
+    ([ 2,  0,  2,  0, 2],      -31.0,    0.0,     13.0,  0.0),
+    ([ 2,  0,  0,  0, 0],       29.0,    0.0,     -1.0,  0.0),
+    ([ 1,  0,  2, -2, 2],       29.0,    0.0,    -12.0,  0.0),
+    ([ 0,  0,  2,  0, 0],       26.0,    0.0,     -1.0,  0.0),
+    ([ 0,  0,  2, -2, 0],      -22.0,    0.0,      0.0,  0.0),
+    ([-1,  0,  2,  0, 1],       21.0,    0.0,    -10.0,  0.0),
+    ([ 0,  2,  0,  0, 0],       17.0,   -0.1,      0.0,  0.0),
+    ([ 0,  2,  2, -2, 2],      -16.0,    0.1,      7.0,  0.0),
+    ([-1,  0,  0,  2, 1],       16.0,    0.0,     -8.0,  0.0),
+    ([ 0,  1,  0,  0, 1],      -15.0,    0.0,      9.0,  0.0),
+    ([ 1,  0,  0, -2, 1],      -13.0,    0.0,      7.0,  0.0),
+    ([ 0, -1,  0,  0, 1],      -12.0,    0.0,      6.0,  0.0),
+    ([ 2,  0, -2,  0, 0],       11.0,    0.0,      0.0,  0.0),
+    ([-1,  0,  2,  2, 1],      -10.0,    0.0,      5.0,  0.0),
+    ([ 1,  0,  2,  2, 2],       -8.0,    0.0,      3.0,  0.0),
+    ([ 0, -1,  2,  0, 2],       -7.0,    0.0,      3.0,  0.0),
+    ([ 0,  0,  2,  2, 1],       -7.0,    0.0,      3.0,  0.0),
+    ([ 1,  1,  0, -2, 0],       -7.0,    0.0,      0.0,  0.0),
+    ([ 0,  1,  2,  0, 2],        7.0,    0.0,     -3.0,  0.0),
+    ([-2,  0,  0,  2, 1],       -6.0,    0.0,      3.0,  0.0),
+    ([ 0,  0,  0,  2, 1],       -6.0,    0.0,      3.0,  0.0),
+    ([ 2,  0,  2, -2, 2],        6.0,    0.0,     -3.0,  0.0),
+    ([ 1,  0,  0,  2, 0],        6.0,    0.0,      0.0,  0.0),
+    ([ 1,  0,  2, -2, 1],        6.0,    0.0,     -3.0,  0.0),
+    ([ 0,  0,  0, -2, 1],       -5.0,    0.0,      3.0,  0.0),
+    ([ 0, -1,  2, -2, 1],       -5.0,    0.0,      3.0,  0.0),
+    ([ 2,  0,  2,  0, 1],       -5.0,    0.0,      3.0,  0.0),
+];
+
+fn nutation_iau80(ttt: f64, eop: &EarthOrientation) -> Nutation {
+    let t2 = ttt * ttt;
+    let t3 = t2 * ttt;
+
+    let mean_obliquity =
+        (84_381.448 - 46.8150 * ttt - 0.00059 * t2 + 0.001813 * t3) * ARCSEC_TO_RAD;
+
+    // Delaunay arguments (IAU-80), in degrees.
+    let l = ((0.064 * ttt + 31.310) * ttt + 1717915922.6330) * ttt / 3600.0 + 134.96298139;
+    let l_prime = ((-0.012 * ttt - 0.577) * ttt + 129596581.2240) * ttt / 3600.0 + 357.52772333;
+    let f = ((0.011 * ttt - 13.257) * ttt + 1739527263.1370) * ttt / 3600.0 + 93.27191028;
+    let d = ((0.019 * ttt - 6.891) * ttt + 1602961601.3280) * ttt / 3600.0 + 297.85036306;
+    let omega = ((0.008 * ttt + 7.455) * ttt - 6962890.5390) * ttt / 3600.0 + 125.04452222;
+    let args = [l, l_prime, f, d, omega].map(|deg| (deg % 360.0).to_radians());
+
+    let mut delta_psi = 0.0;
+    let mut delta_eps = 0.0;
+    for (multipliers, a, b, c, d) in NUTATION_IAU80.iter().rev() {
+        let angle: f64 = multipliers
+            .iter()
+            .zip(args.iter())
+            .map(|(m, arg)| f64::from(*m) * arg)
+            .sum();
+        delta_psi += (a + b * ttt) * angle.sin();
+        delta_eps += (c + d * ttt) * angle.cos();
+    }
+
+    let delta_psi = (delta_psi * 1e-4 + eop.ddpsi_arcsec) * ARCSEC_TO_RAD;
+    let delta_eps = (delta_eps * 1e-4 + eop.ddeps_arcsec) * ARCSEC_TO_RAD;
+
+    Nutation {
+        delta_psi,
+        mean_obliquity,
+        true_obliquity: mean_obliquity + delta_eps,
+    }
+}
@@ -0,0 +1,94 @@
+//! WGS-84 geodetic coordinates.
+use crate::frames::ItrfState;
+
+/// WGS-84 equatorial radius in km.
+pub const WGS84_A_KM: f64 = 6378.137;
+/// WGS-84 flattening.
+pub const WGS84_F: f64 = 1.0 / 298.257_223_563;
+
+const WGS84_E2: f64 = WGS84_F * (2.0 - WGS84_F);
+
+/// Geodetic latitude/longitude (degrees) and ellipsoidal height (km).
+///
+/// Longitude is normalised to `[-180, 180)` degrees, east positive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Geodetic {
+    pub latitude_deg: f64,
+    pub longitude_deg: f64,
+    pub height_km: f64,
+}
+
+impl Geodetic {
+    /// Convert an Earth-fixed position in km to WGS-84 geodetic coordinates.
+    ///
+    /// Iterates `tan φ = (z + e² N sin φ) / p`, which converges to
+    /// sub-millimetre accuracy in a handful of steps for any point outside
+    /// the Earth's core.
+    pub fn from_ecef(position_km: [f64; 3]) -> Self {
+        let [x, y, z] = position_km;
+        let p = x.hypot(y);
+        let longitude = y.atan2(x);
+
+        if p < 1e-9 {
+            let b = WGS84_A_KM * (1.0 - WGS84_F);
+            return Self {
+                latitude_deg: 90.0f64.copysign(z),
+                longitude_deg: 0.0,
+                height_km: z.abs() - b,
+            };
+        }
+
+        let mut latitude = z.atan2(p * (1.0 - WGS84_E2));
+        for _ in 0..10 {
+            let next = (z + WGS84_E2 * prime_vertical_radius(latitude) * latitude.sin()).atan2(p);
+            let converged = (next - latitude).abs() < 1e-12;
+            latitude = next;
+            if converged {
+                break;
+            }
+        }
+
+        // This form of the height stays well conditioned near the poles.
+        let (sin_lat, cos_lat) = latitude.sin_cos();
+        let height =
+            p * cos_lat + z * sin_lat - WGS84_A_KM * (1.0 - WGS84_E2 * sin_lat * sin_lat).sqrt();
+
+        Self {
+            latitude_deg: latitude.to_degrees(),
+            longitude_deg: normalize_longitude_deg(longitude.to_degrees()),
+            height_km: height,
+        }
+    }
+
+    /// Earth-fixed position in km of this geodetic point.
+    pub fn to_ecef(&self) -> [f64; 3] {
+        let latitude = self.latitude_deg.to_radians();
+        let longitude = self.longitude_deg.to_radians();
+        let (sin_lat, cos_lat) = latitude.sin_cos();
+        let n = prime_vertical_radius(latitude);
+
+        [
+            (n + self.height_km) * cos_lat * longitude.cos(),
+            (n + self.height_km) * cos_lat * longitude.sin(),
+            (n * (1.0 - WGS84_E2) + self.height_km) * sin_lat,
+        ]
+    }
+}
+
+impl ItrfState {
+    /// Sub-satellite point and height above the WGS-84 ellipsoid.
+    pub fn geodetic(&self) -> Geodetic {
+        Geodetic::from_ecef(self.position_km)
+    }
+}
+
+/// Radius of curvature in the prime vertical at a geodetic latitude (rad).
+fn prime_vertical_radius(latitude: f64) -> f64 {
+    let sin_lat = latitude.sin();
+    WGS84_A_KM / (1.0 - WGS84_E2 * sin_lat * sin_lat).sqrt()
+}
+
+/// Wrap a longitude in degrees into `[-180, 180)`.
+pub fn normalize_longitude_deg(longitude_deg: f64) -> f64 {
+    (longitude_deg + 180.0).rem_euclid(360.0) - 180.0
+}
@@ -0,0 +1,126 @@
+//! Sub-satellite ground tracks sampled over a time window.
+use sgp4::chrono::{Duration, NaiveDateTime};
+use thiserror::Error;
+
+use crate::frames::EarthOrientation;
+use crate::geodetic::Geodetic;
+use crate::{PropagationError, Sgp4Propagator};
+
+/// Errors returned while generating a ground track.
+#[derive(Debug, Error)]
+pub enum GroundTrackError {
+    #[error("ground track step must be positive, got {0}")]
+    InvalidStep(Duration),
+    #[error("ground track window ends ({end}) before it starts ({start})")]
+    InvalidWindow {
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+    },
+    #[error(transparent)]
+    Propagation(#[from] PropagationError),
+}
+
+/// One sample of a ground track.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GroundTrackPoint {
+    pub time: NaiveDateTime,
+    pub geodetic: Geodetic,
+}
+
+/// A ground track split into segments that never cross the antimeridian.
+///
+/// When the track crosses ±180° longitude, the current segment ends with an
+/// interpolated point at exactly `180.0` (or `-180.0`) and the next segment
+/// starts with the matching point on the other side, so each segment can be
+/// drawn as a plain polyline on a 2D map or globe.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GroundTrack {
+    pub segments: Vec<Vec<GroundTrackPoint>>,
+}
+
+impl GroundTrack {
+    /// Iterate over every point in time order, including the interpolated
+    /// antimeridian crossings.
+    pub fn points(&self) -> impl Iterator<Item = &GroundTrackPoint> {
+        self.segments.iter().flatten()
+    }
+}
+
+/// Sample the ground track of `propagator` from `start` to `end` inclusive.
+///
+/// Samples are taken every `step`; the final sample is always at `end`.
+pub fn ground_track(
+    propagator: &Sgp4Propagator,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    step: Duration,
+    eop: &EarthOrientation,
+) -> Result<GroundTrack, GroundTrackError> {
+    if step <= Duration::zero() {
+        return Err(GroundTrackError::InvalidStep(step));
+    }
+    if end < start {
+        return Err(GroundTrackError::InvalidWindow { start, end });
+    }
+
+    let mut track = GroundTrack::default();
+    let mut segment: Vec<GroundTrackPoint> = Vec::new();
+    let mut time = start;
+
+    loop {
+        let geodetic = propagator.propagate_teme(time)?.to_itrf(eop).geodetic();
+        let point = GroundTrackPoint { time, geodetic };
+
+        if let Some(previous) = segment.last().copied() {
+            if let Some((leaving, entering)) = antimeridian_crossing(&previous, &point) {
+                segment.push(leaving);
+                track.segments.push(std::mem::take(&mut segment));
+                segment.push(entering);
+            }
+        }
+        segment.push(point);
+
+        if time >= end {
+            break;
+        }
+        time = (time + step).min(end);
+    }
+
+    track.segments.push(segment);
+    Ok(track)
+}
+
+/// Interpolated boundary points when the path from `a` to `b` crosses ±180°.
+fn antimeridian_crossing(
+    a: &GroundTrackPoint,
+    b: &GroundTrackPoint,
+) -> Option<(GroundTrackPoint, GroundTrackPoint)> {
+    let lon_a = a.geodetic.longitude_deg;
+    let lon_b = b.geodetic.longitude_deg;
+    if (lon_b - lon_a).abs() <= 180.0 {
+        return None;
+    }
+
+    let (edge, unwrapped_b) = if lon_b < lon_a {
+        (180.0, lon_b + 360.0)
+    } else {
+        (-180.0, lon_b - 360.0)
+    };
+    let fraction = (edge - lon_a) / (unwrapped_b - lon_a);
+
+    let span_us = (b.time - a.time).num_microseconds().unwrap_or(0) as f64;
+    let time = a.time + Duration::microseconds((span_us * fraction).round() as i64);
+    let lerp = |from: f64, to: f64| from + (to - from) * fraction;
+    let latitude_deg = lerp(a.geodetic.latitude_deg, b.geodetic.latitude_deg);
+    let height_km = lerp(a.geodetic.height_km, b.geodetic.height_km);
+
+    let at = |longitude_deg: f64| GroundTrackPoint {
+        time,
+        geodetic: Geodetic {
+            latitude_deg,
+            longitude_deg,
+            height_km,
+        },
+    };
+    Some((at(edge), at(-edge)))
+}
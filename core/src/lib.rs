@@ -5,6 +5,32 @@
 use sgp4::{self, chrono::NaiveDateTime, DatetimeToMinutesSinceEpochError, MinutesSinceEpoch};
 use thiserror::Error;
 
+mod math;
+mod roots;
+
+pub mod backend;
+pub mod conjunction;
+pub mod error_budget;
+pub mod fp32;
+pub mod frames;
+pub mod geodetic;
+pub mod ground_track;
+pub mod norad;
+pub mod observer;
+pub mod omm;
+pub mod passes;
+pub mod sun;
+pub mod time;
+pub mod tle;
+
+pub use backend::{
+    DispatchTimes, ElementSet, GpuBackend, GpuBackendError, ObjectStatus, PackedElements,
+    StateBuffer,
+};
+pub use frames::{EarthOrientation, GcrfState, ItrfState, TemeState};
+pub use geodetic::Geodetic;
+pub use norad::{NoradId, NoradIdError};
+
 /// Position (km) and velocity (km/s) expressed in the TEME frame.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct StateVector {
@@ -17,37 +43,48 @@ pub struct StateVector {
 pub enum PropagationError {
     #[error("failed to parse TLE: {0}")]
     Tle(#[from] sgp4::TleError),
-    #[error("failed to construct SGP4 constants: {0}")]
-    Elements(#[from] sgp4::ElementsError),
     #[error("failed to convert datetime to minutes since epoch: {0:?}")]
     EpochConversion(#[from] DatetimeToMinutesSinceEpochError),
     #[error("propagation error: {0}")]
-    Propagation(#[from] sgp4::Error),
+    Propagation(#[from] PropagationFailure),
+    #[error("{0}")]
+    NoradId(#[from] NoradIdError),
 }
 
-/// Errors surfaced by GPU backend implementations.
-#[derive(Debug, Error)]
-pub enum GpuBackendError {
-    #[error("backend is not ready")]
-    NotReady,
-    #[error("invalid dispatch batch size: {0}")]
-    InvalidBatchSize(u32),
-    #[error("shader compilation failed: {0}")]
-    ShaderCompile(String),
-    #[error("compute dispatch failed: {0}")]
-    Dispatch(String),
+/// Why SGP4 could not produce a state for an object.
+///
+/// Follows the error traps of Vallado's reference implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Error)]
+pub enum FailureKind {
+    /// Mean eccentricity outside `[-0.001, 1)`, or epoch eccentricity outside
+    /// `[0, 1)`.
+    #[error("mean eccentricity out of range")]
+    EccentricityOutOfRange,
+    #[error("mean motion is negative")]
+    MeanMotionNegative,
+    /// Eccentricity after lunar-solar perturbations outside `[0, 1]`.
+    #[error("perturbed eccentricity out of range")]
+    PerturbedEccentricityOutOfRange,
+    #[error("semi-latus rectum is negative")]
+    SemiLatusRectumNegative,
+    /// The propagated position is below the Earth's equatorial radius.
+    #[error("orbit has decayed")]
+    Decayed,
+    /// The requested time was NaN or infinite.
+    #[error("non-finite input")]
+    NonFiniteInput,
 }
 
-/// Contract shared by CUDA and WebGPU compute backends.
-pub trait GpuBackend {
-    /// User-visible backend name.
-    fn name(&self) -> &'static str;
-
-    /// Whether this backend has been initialized and can accept work.
-    fn is_ready(&self) -> bool;
-
-    /// Dispatch one FP32 SGP4 kernel step for the given orbital batch size.
-    fn dispatch_sgp4_fp32_step(&self, batch_size: u32) -> Result<(), GpuBackendError>;
+/// An SGP4 failure for one object at one time.
+///
+/// Failures of the epoch elements are reported at minute 0, as the
+/// reference implementation does when it initializes a satellite.
+#[derive(Debug, Clone, Copy, PartialEq, Error)]
+#[error("{kind} for object {norad_id} at {minutes_since_epoch} min since epoch")]
+pub struct PropagationFailure {
+    pub norad_id: NoradId,
+    pub minutes_since_epoch: f64,
+    pub kind: FailureKind,
 }
 
 /// Thin wrapper around the Vallado SGP4 implementation from the [`sgp4`] crate.
@@ -55,6 +92,22 @@ pub trait GpuBackend {
 pub struct Sgp4Propagator {
     constants: sgp4::Constants,
     elements: sgp4::Elements,
+    norad_id: NoradId,
+    afspc_compatibility: bool,
+}
+
+/// Classify an initialization failure as a failure at epoch.
+fn epoch_failure(norad_id: NoradId, error: sgp4::ElementsError) -> PropagationError {
+    let kind = match error {
+        sgp4::ElementsError::KozaiElementsError(_) => FailureKind::MeanMotionNegative,
+        sgp4::ElementsError::OutOfRangeEpochEccentricity(_) => FailureKind::EccentricityOutOfRange,
+    };
+    PropagationFailure {
+        norad_id,
+        minutes_since_epoch: 0.0,
+        kind,
+    }
+    .into()
 }
 
 impl Sgp4Propagator {
@@ -65,21 +118,92 @@ impl Sgp4Propagator {
         line2: &str,
     ) -> Result<Self, PropagationError> {
         let elements = sgp4::Elements::from_tle(object_name, line1.as_bytes(), line2.as_bytes())?;
-        let constants = sgp4::Constants::from_elements(&elements)?;
+        Self::from_elements(elements)
+    }
+
+    /// Create a propagator from mean elements, such as those read from an
+    /// OMM by the [`omm`] parsers.
+    pub fn from_elements(elements: sgp4::Elements) -> Result<Self, PropagationError> {
+        let norad_id = NoradId::new(elements.norad_id)?;
+        let constants = sgp4::Constants::from_elements(&elements)
+            .map_err(|error| epoch_failure(norad_id, error))?;
+        Ok(Self {
+            constants,
+            elements,
+            norad_id,
+            afspc_compatibility: false,
+        })
+    }
+
+    /// Create a propagator that reproduces the AFSPC operational code: WGS-72
+    /// constants and the AFSPC sidereal time and epoch expressions.
+    ///
+    /// This is the mode the published verification output (AIAA 2006-6753)
+    /// was generated in. [`Sgp4Propagator::from_tle`] uses WGS-84 and the
+    /// improved expressions, which differ from it by up to a few kilometres.
+    /// The f32 kernels always use the improved mode.
+    pub fn from_tle_afspc(
+        object_name: Option<String>,
+        line1: &str,
+        line2: &str,
+    ) -> Result<Self, PropagationError> {
+        let elements = sgp4::Elements::from_tle(object_name, line1.as_bytes(), line2.as_bytes())?;
+        let norad_id = NoradId::new(elements.norad_id)?;
+        let constants = sgp4::Constants::from_elements_afspc_compatibility_mode(&elements)
+            .map_err(|error| epoch_failure(norad_id, error))?;
         Ok(Self {
             constants,
             elements,
+            norad_id,
+            afspc_compatibility: true,
         })
     }
 
     /// Propagate a state by minutes since the TLE epoch.
+    ///
+    /// Positions below the Earth's equatorial radius are reported as
+    /// [`FailureKind::Decayed`], matching the reference implementation.
     pub fn propagate_minutes(
         &self,
         minutes_since_epoch: f64,
     ) -> Result<StateVector, PropagationError> {
-        let prediction = self
-            .constants
-            .propagate(MinutesSinceEpoch(minutes_since_epoch))?;
+        let failure = |kind| PropagationFailure {
+            norad_id: self.norad_id,
+            minutes_since_epoch,
+            kind,
+        };
+        if !minutes_since_epoch.is_finite() {
+            return Err(failure(FailureKind::NonFiniteInput).into());
+        }
+
+        let minutes = MinutesSinceEpoch(minutes_since_epoch);
+        let (prediction, geopotential) = if self.afspc_compatibility {
+            (
+                self.constants.propagate_afspc_compatibility_mode(minutes),
+                sgp4::WGS72,
+            )
+        } else {
+            (self.constants.propagate(minutes), sgp4::WGS84)
+        };
+        let prediction = prediction.map_err(|error| {
+            failure(match error {
+                sgp4::Error::OutOfRangeEccentricity { .. } => FailureKind::EccentricityOutOfRange,
+                sgp4::Error::OutOfRangePerturbedEccentricity { .. } => {
+                    FailureKind::PerturbedEccentricityOutOfRange
+                }
+                sgp4::Error::NegativeSemiLatusRectum { .. } => FailureKind::SemiLatusRectumNegative,
+            })
+        })?;
+
+        let radius = prediction
+            .position
+            .iter()
+            .map(|x| x * x)
+            .sum::<f64>()
+            .sqrt();
+        if radius < geopotential.ae {
+            return Err(failure(FailureKind::Decayed).into());
+        }
         Ok(StateVector {
             position_km: prediction.position,
             velocity_km_s: prediction.velocity,
@@ -91,12 +215,47 @@ impl Sgp4Propagator {
         &self,
         datetime: NaiveDateTime,
     ) -> Result<StateVector, PropagationError> {
-        let minutes = self.elements.datetime_to_minutes_since_epoch(&datetime)?;
-        self.propagate_minutes(minutes.0)
+        self.propagate_minutes(self.minutes_since_epoch(datetime)?)
+    }
+
+    /// Minutes between the TLE epoch and a UTC timestamp.
+    pub fn minutes_since_epoch(&self, datetime: NaiveDateTime) -> Result<f64, PropagationError> {
+        Ok(self.elements.datetime_to_minutes_since_epoch(&datetime)?.0)
+    }
+
+    /// Propagate to a UTC timestamp and tag the result as a TEME state.
+    pub fn propagate_teme(&self, datetime: NaiveDateTime) -> Result<TemeState, PropagationError> {
+        Ok(self.propagate_datetime(datetime)?.into_teme(datetime))
     }
 
     /// Epoch of the source TLE as a naive UTC datetime.
     pub fn epoch(&self) -> NaiveDateTime {
         self.elements.datetime
     }
+
+    /// NORAD catalog number of the source element set.
+    pub fn norad_id(&self) -> NoradId {
+        self.norad_id
+    }
+
+    /// Mean elements the propagator was initialized from.
+    pub fn elements(&self) -> &sgp4::Elements {
+        &self.elements
+    }
+
+    /// The elements as TLE lines 1 and 2, see [`tle::format_elements`].
+    pub fn to_tle(&self) -> Result<(String, String), tle::TleFormatError> {
+        tle::format_elements(&self.elements)
+    }
+
+    /// The elements as a single-message OMM in the given encoding, see
+    /// [`omm::write`].
+    pub fn to_omm(&self, format: omm::OmmFormat) -> String {
+        omm::write([&self.elements], format)
+    }
+
+    /// Object name supplied with the element set, if any.
+    pub fn object_name(&self) -> Option<&str> {
+        self.elements.object_name.as_deref()
+    }
 }
@@ -0,0 +1,125 @@
+//! NORAD catalog numbers.
+//!
+//! The catalog has outgrown the five digits of the classic TLE. Numbers up
+//! to 339 999 fit a TLE through the Alpha-5 scheme, where the leading digit
+//! is replaced by a letter (`A` = 10 … `Z` = 33, skipping `I` and `O`), and
+//! OMM carries up to nine digits. [`NoradId`] holds any of them.
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+/// Errors raised while reading or encoding a catalog number.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum NoradIdError {
+    #[error("catalog number {0} exceeds nine digits")]
+    OutOfRange(u64),
+    #[error("invalid catalog number `{0}`")]
+    Invalid(String),
+    #[error("catalog number {0} cannot be written in the five TLE columns")]
+    NotAlpha5(NoradId),
+}
+
+/// A NORAD catalog number of up to nine digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NoradId(u32);
+
+/// Alpha-5 letters in order of value, starting at 10.
+const ALPHA5_LETTERS: &[u8; 24] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
+
+impl NoradId {
+    /// Largest catalog number OMM can carry.
+    pub const MAX: Self = Self(999_999_999);
+    /// Largest catalog number a TLE can carry, `Z9999`.
+    pub const MAX_ALPHA5: Self = Self(339_999);
+
+    pub fn new(id: u64) -> Result<Self, NoradIdError> {
+        if id > u64::from(Self::MAX.0) {
+            return Err(NoradIdError::OutOfRange(id));
+        }
+        Ok(Self(id as u32))
+    }
+
+    pub fn get(self) -> u32 {
+        self.0
+    }
+
+    /// Decode the five catalog-number columns of a TLE, which hold either
+    /// plain digits or Alpha-5. Leading blanks are allowed.
+    pub fn from_alpha5(field: &str) -> Result<Self, NoradIdError> {
+        let invalid = || NoradIdError::Invalid(field.to_string());
+        let trimmed = field.trim_start();
+        if field.len() > 5 || !trimmed.is_ascii() {
+            return Err(invalid());
+        }
+        let (head, tail) = trimmed.split_at(trimmed.len().min(1));
+        if !tail.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        match head.bytes().next() {
+            Some(digit) if digit.is_ascii_digit() => {
+                Ok(Self(trimmed.parse().map_err(|_| invalid())?))
+            }
+            Some(letter) if tail.len() == 4 => {
+                let value = ALPHA5_LETTERS
+                    .iter()
+                    .position(|&candidate| candidate == letter)
+                    .ok_or_else(invalid)? as u32
+                    + 10;
+                Ok(Self(
+                    value * 10_000 + tail.parse::<u32>().map_err(|_| invalid())?,
+                ))
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Encode for the five catalog-number columns of a TLE, zero-padded
+    /// below 100 000 and in Alpha-5 up to [`NoradId::MAX_ALPHA5`].
+    pub fn to_alpha5(self) -> Result<String, NoradIdError> {
+        if self > Self::MAX_ALPHA5 {
+            return Err(NoradIdError::NotAlpha5(self));
+        }
+        if self.0 < 100_000 {
+            return Ok(format!("{:05}", self.0));
+        }
+        let letter = ALPHA5_LETTERS[(self.0 / 10_000 - 10) as usize] as char;
+        Ok(format!("{letter}{:04}", self.0 % 10_000))
+    }
+}
+
+impl fmt::Display for NoradId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl FromStr for NoradId {
+    type Err = NoradIdError;
+
+    /// Parse a decimal catalog number, or an Alpha-5 one such as `A2345`.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        if text.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return Self::from_alpha5(text);
+        }
+        let id: u64 = text
+            .parse()
+            .map_err(|_| NoradIdError::Invalid(text.to_string()))?;
+        Self::new(id)
+    }
+}
+
+impl TryFrom<u64> for NoradId {
+    type Error = NoradIdError;
+
+    fn try_from(id: u64) -> Result<Self, Self::Error> {
+        Self::new(id)
+    }
+}
+
+impl From<NoradId> for u64 {
+    fn from(id: NoradId) -> Self {
+        u64::from(id.0)
+    }
+}
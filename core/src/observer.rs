@@ -0,0 +1,94 @@
+//! Topocentric look angles for ground observers.
+use sgp4::chrono::NaiveDateTime;
+
+use crate::frames::{EarthOrientation, ItrfState};
+use crate::geodetic::Geodetic;
+use crate::math::{dot, sub, Vec3};
+use crate::{PropagationError, Sgp4Propagator};
+
+/// A ground site with an optional minimum elevation for visibility.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Observer {
+    pub location: Geodetic,
+    /// Minimum elevation in degrees below which the satellite is treated as
+    /// obstructed (terrain, buildings, antenna limits).
+    pub elevation_mask_deg: Option<f64>,
+}
+
+/// Where to point from an observer to see a satellite.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LookAngles {
+    /// Azimuth in degrees clockwise from true north, in `[0, 360)`.
+    pub azimuth_deg: f64,
+    /// Elevation in degrees above the local horizon.
+    pub elevation_deg: f64,
+    /// Slant range in km.
+    pub range_km: f64,
+    /// Rate of change of the slant range in km/s (positive when receding).
+    pub range_rate_km_s: f64,
+}
+
+impl Observer {
+    /// Observer at the given site with no elevation mask.
+    pub fn new(location: Geodetic) -> Self {
+        Self {
+            location,
+            elevation_mask_deg: None,
+        }
+    }
+
+    /// Set the minimum elevation in degrees for the satellite to count as visible.
+    pub fn with_elevation_mask(mut self, elevation_mask_deg: f64) -> Self {
+        self.elevation_mask_deg = Some(elevation_mask_deg);
+        self
+    }
+
+    /// Effective elevation mask in degrees (the horizon when unset).
+    pub fn mask_deg(&self) -> f64 {
+        self.elevation_mask_deg.unwrap_or(0.0)
+    }
+
+    /// Whether the look angles clear the observer's elevation mask.
+    pub fn is_visible(&self, look: &LookAngles) -> bool {
+        look.elevation_deg >= self.mask_deg()
+    }
+
+    /// Look angles to a satellite propagated to `time`.
+    pub fn look_angles(
+        &self,
+        propagator: &Sgp4Propagator,
+        time: NaiveDateTime,
+        eop: &EarthOrientation,
+    ) -> Result<LookAngles, PropagationError> {
+        let itrf = propagator.propagate_teme(time)?.to_itrf(eop);
+        Ok(self.look_angles_to(&itrf))
+    }
+
+    /// Look angles to an Earth-fixed satellite state.
+    pub fn look_angles_to(&self, satellite: &ItrfState) -> LookAngles {
+        let rho = sub(satellite.position_km, self.location.to_ecef());
+        let (east, north, up) = self.enu_axes();
+
+        let e = dot(rho, east);
+        let n = dot(rho, north);
+        let u = dot(rho, up);
+        let range_km = dot(rho, rho).sqrt();
+
+        LookAngles {
+            azimuth_deg: e.atan2(n).to_degrees().rem_euclid(360.0),
+            elevation_deg: (u / range_km).clamp(-1.0, 1.0).asin().to_degrees(),
+            range_km,
+            range_rate_km_s: dot(rho, satellite.velocity_km_s) / range_km,
+        }
+    }
+
+    fn enu_axes(&self) -> (Vec3, Vec3, Vec3) {
+        let (sin_lat, cos_lat) = self.location.latitude_deg.to_radians().sin_cos();
+        let (sin_lon, cos_lon) = self.location.longitude_deg.to_radians().sin_cos();
+        (
+            [-sin_lon, cos_lon, 0.0],
+            [-sin_lat * cos_lon, -sin_lat * sin_lon, cos_lat],
+            [cos_lat * cos_lon, cos_lat * sin_lon, sin_lat],
+        )
+    }
+}
@@ -0,0 +1,486 @@
+//! CCSDS Orbit Mean-elements Messages (OMM, CCSDS 502.0-B).
+//!
+//! CelesTrak and Space-Track publish general perturbations data as OMM in
+//! XML, KVN and JSON. Unlike TLEs, OMM carries catalog numbers of any width.
+//! Every format is reduced to the same keyword/value pairs and converted to
+//! [`sgp4::Elements`], which [`crate::Sgp4Propagator::from_elements`] turns
+//! into a propagator. [`write`] produces any of the three encodings from
+//! elements.
+use std::collections::HashMap;
+
+use sgp4::chrono::NaiveDateTime;
+use thiserror::Error;
+
+use crate::NoradId;
+
+/// Errors raised while reading an OMM.
+#[derive(Debug, Error)]
+pub enum OmmError {
+    #[error("invalid OMM XML: {0}")]
+    Xml(#[from] roxmltree::Error),
+    #[error("invalid OMM JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("line {line}: expected `KEYWORD = value`")]
+    KvnSyntax { line: usize },
+    #[error("OMM JSON must be an object or an array of objects")]
+    JsonShape,
+    #[error("OMM is missing {0}")]
+    MissingField(&'static str),
+    #[error("invalid {field} `{value}`")]
+    InvalidField { field: &'static str, value: String },
+    #[error("unsupported mean element theory `{0}`, expected SGP4")]
+    UnsupportedTheory(String),
+}
+
+/// Encodings of an OMM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OmmFormat {
+    Xml,
+    Kvn,
+    Json,
+}
+
+impl OmmFormat {
+    /// Guess the encoding from how the text starts, or `None` if it does not
+    /// look like an OMM. Only an XML declaration or an `ndm` or `omm` root
+    /// element counts as XML, so an HTML error page is not mistaken for one.
+    pub fn detect(text: &str) -> Option<Self> {
+        let text = text.trim_start_matches('\u{feff}').trim_start();
+        match text.chars().next()? {
+            '<' if ["<?xml", "<ndm", "<omm"]
+                .iter()
+                .any(|tag| starts_with_tag(text, tag)) =>
+            {
+                Some(Self::Xml)
+            }
+            '<' => None,
+            '[' | '{' => Some(Self::Json),
+            _ if text.starts_with("CCSDS_OMM_VERS") => Some(Self::Kvn),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `text` opens with `tag`, in any case, followed by the end of the
+/// tag name rather than a longer name such as `<ommx`.
+fn starts_with_tag(text: &str, tag: &str) -> bool {
+    text.get(..tag.len())
+        .is_some_and(|head| head.eq_ignore_ascii_case(tag))
+        && text[tag.len()..]
+            .chars()
+            .next()
+            .is_none_or(|c| c.is_whitespace() || c == '>' || c == '/')
+}
+
+/// Parse every message in `text` in the given encoding.
+pub fn parse(text: &str, format: OmmFormat) -> Result<Vec<sgp4::Elements>, OmmError> {
+    parse_each(text, format)?.into_iter().collect()
+}
+
+/// Parse every message in `text`, keeping each message's outcome apart so
+/// one bad message does not discard the rest. Only errors that make the
+/// whole document unreadable fail the call.
+pub fn parse_each(
+    text: &str,
+    format: OmmFormat,
+) -> Result<Vec<Result<sgp4::Elements, OmmError>>, OmmError> {
+    match format {
+        OmmFormat::Xml => parse_xml_each(text),
+        OmmFormat::Kvn => Ok(parse_kvn_each(text)),
+        OmmFormat::Json => parse_json_each(text),
+    }
+}
+
+/// Parse an XML document holding one `<omm>` or an `<ndm>` of several.
+pub fn parse_xml(text: &str) -> Result<Vec<sgp4::Elements>, OmmError> {
+    parse_xml_each(text)?.into_iter().collect()
+}
+
+/// Parse KVN text holding one or more messages, each opened by
+/// `CCSDS_OMM_VERS`.
+pub fn parse_kvn(text: &str) -> Result<Vec<sgp4::Elements>, OmmError> {
+    parse_kvn_each(text).into_iter().collect()
+}
+
+/// Parse the JSON flavour served by CelesTrak and Space-Track: an array of
+/// flat objects keyed by OMM keyword, or a single such object. Numbers may
+/// be JSON numbers or strings.
+pub fn parse_json(text: &str) -> Result<Vec<sgp4::Elements>, OmmError> {
+    parse_json_each(text)?.into_iter().collect()
+}
+
+fn parse_xml_each(text: &str) -> Result<Vec<Result<sgp4::Elements, OmmError>>, OmmError> {
+    let document = roxmltree::Document::parse(text)?;
+    Ok(document
+        .descendants()
+        .filter(|node| node.has_tag_name("omm"))
+        .map(|omm| {
+            // Keywords are unique within a message, so the nesting of
+            // header, metadata and data sections can be flattened.
+            let fields = omm
+                .descendants()
+                .filter(|node| {
+                    node.is_element() && !node.children().any(|child| child.is_element())
+                })
+                .filter_map(|node| Some((node.tag_name().name(), node.text()?.trim())))
+                .collect();
+            elements_from_fields(&fields)
+        })
+        .collect())
+}
+
+fn parse_kvn_each(text: &str) -> Vec<Result<sgp4::Elements, OmmError>> {
+    // A syntax error spoils the message it occurs in; lines before the first
+    // `CCSDS_OMM_VERS` form a message of their own.
+    let mut messages: Vec<Result<HashMap<&str, &str>, OmmError>> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("COMMENT") {
+            continue;
+        }
+        let entry = line.split_once('=').map(|(keyword, value)| {
+            let keyword = keyword.trim();
+            (keyword, kvn_value(keyword, value))
+        });
+        if matches!(entry, Some(("CCSDS_OMM_VERS", _))) || messages.is_empty() {
+            messages.push(Ok(HashMap::new()));
+        }
+        let message = messages.last_mut().expect("a message was opened");
+        match (entry, message) {
+            (Some((keyword, value)), Ok(fields)) => {
+                fields.insert(keyword, value);
+            }
+            (None, message @ Ok(_)) => *message = Err(OmmError::KvnSyntax { line: index + 1 }),
+            (_, Err(_)) => {}
+        }
+    }
+    messages
+        .into_iter()
+        .map(|message| {
+            let fields = message?;
+            if !fields.contains_key("CCSDS_OMM_VERS") {
+                return Err(OmmError::MissingField("CCSDS_OMM_VERS"));
+            }
+            elements_from_fields(&fields)
+        })
+        .collect()
+}
+
+/// KVN keywords whose values are text, and so never carry units.
+const KVN_TEXT_KEYWORDS: &[&str] = &[
+    "CCSDS_OMM_VERS",
+    "CREATION_DATE",
+    "ORIGINATOR",
+    "MESSAGE_ID",
+    "OBJECT_NAME",
+    "OBJECT_ID",
+    "CENTER_NAME",
+    "REF_FRAME",
+    "REF_FRAME_EPOCH",
+    "TIME_SYSTEM",
+    "MEAN_ELEMENT_THEORY",
+    "EPOCH",
+    "CLASSIFICATION_TYPE",
+    "COV_REF_FRAME",
+];
+
+/// The value of `keyword`, without the trailing unit a numeric value may
+/// carry, e.g. the `[rev/day]` of `15.5 [rev/day]`.
+fn kvn_value<'a>(keyword: &str, value: &'a str) -> &'a str {
+    let value = value.trim();
+    if KVN_TEXT_KEYWORDS.contains(&keyword) || keyword.starts_with("USER_DEFINED_") {
+        return value;
+    }
+    match value
+        .strip_suffix(']')
+        .and_then(|rest| rest.rsplit_once('['))
+    {
+        Some((number, _unit)) => number.trim(),
+        None => value,
+    }
+}
+
+fn parse_json_each(text: &str) -> Result<Vec<Result<sgp4::Elements, OmmError>>, OmmError> {
+    let value: serde_json::Value = serde_json::from_str(text)?;
+    let objects = match &value {
+        serde_json::Value::Array(items) => items.iter().collect(),
+        object @ serde_json::Value::Object(_) => vec![object],
+        _ => return Err(OmmError::JsonShape),
+    };
+    Ok(objects
+        .into_iter()
+        .map(|object| {
+            let object = object.as_object().ok_or(OmmError::JsonShape)?;
+            let values: Vec<(&str, String)> = object
+                .iter()
+                .filter_map(|(keyword, value)| match value {
+                    serde_json::Value::String(text) => Some((keyword.as_str(), text.clone())),
+                    serde_json::Value::Number(number) => {
+                        Some((keyword.as_str(), number.to_string()))
+                    }
+                    _ => None,
+                })
+                .collect();
+            let fields = values
+                .iter()
+                .map(|(keyword, value)| (*keyword, value.as_str()))
+                .collect();
+            elements_from_fields(&fields)
+        })
+        .collect())
+}
+
+/// Write elements as a catalog in the given encoding: an `<ndm>` of `<omm>`
+/// messages, concatenated KVN messages, or a JSON array in the CelesTrak
+/// layout. Every parser in this module reads the result back.
+pub fn write<'a>(
+    elements: impl IntoIterator<Item = &'a sgp4::Elements>,
+    format: OmmFormat,
+) -> String {
+    let messages = elements.into_iter().map(fields_from_elements);
+    match format {
+        OmmFormat::Xml => write_xml(messages),
+        OmmFormat::Kvn => write_kvn(messages),
+        OmmFormat::Json => write_json(messages),
+    }
+}
+
+/// Keyword and value of one field, in CCSDS order.
+type Field = (&'static str, Value);
+
+enum Value {
+    Text(String),
+    Number(String),
+}
+
+impl Value {
+    fn as_str(&self) -> &str {
+        match self {
+            Value::Text(text) | Value::Number(text) => text,
+        }
+    }
+}
+
+/// Keywords that go in the `<metadata>` section; the rest are data.
+const METADATA: [&str; 6] = [
+    "OBJECT_NAME",
+    "OBJECT_ID",
+    "CENTER_NAME",
+    "REF_FRAME",
+    "TIME_SYSTEM",
+    "MEAN_ELEMENT_THEORY",
+];
+/// Keywords that go in the `<meanElements>` section.
+const MEAN_ELEMENTS: [&str; 7] = [
+    "EPOCH",
+    "MEAN_MOTION",
+    "ECCENTRICITY",
+    "INCLINATION",
+    "RA_OF_ASC_NODE",
+    "ARG_OF_PERICENTER",
+    "MEAN_ANOMALY",
+];
+
+fn fields_from_elements(elements: &sgp4::Elements) -> Vec<Field> {
+    let text = |value: &str| Value::Text(value.to_string());
+    let number = |value: f64| Value::Number(value.to_string());
+    let integer = |value: u64| Value::Number(value.to_string());
+    let classification = match elements.classification {
+        sgp4::Classification::Unclassified => "U",
+        sgp4::Classification::Classified => "C",
+        sgp4::Classification::Secret => "S",
+    };
+    let mut fields = Vec::with_capacity(20);
+    if let Some(name) = &elements.object_name {
+        fields.push(("OBJECT_NAME", text(name)));
+    }
+    if let Some(id) = &elements.international_designator {
+        fields.push(("OBJECT_ID", text(id)));
+    }
+    fields.extend([
+        ("CENTER_NAME", text("EARTH")),
+        ("REF_FRAME", text("TEME")),
+        ("TIME_SYSTEM", text("UTC")),
+        ("MEAN_ELEMENT_THEORY", text("SGP4")),
+        (
+            "EPOCH",
+            text(
+                &elements
+                    .datetime
+                    .format("%Y-%m-%dT%H:%M:%S%.6f")
+                    .to_string(),
+            ),
+        ),
+        ("MEAN_MOTION", number(elements.mean_motion)),
+        ("ECCENTRICITY", number(elements.eccentricity)),
+        ("INCLINATION", number(elements.inclination)),
+        ("RA_OF_ASC_NODE", number(elements.right_ascension)),
+        ("ARG_OF_PERICENTER", number(elements.argument_of_perigee)),
+        ("MEAN_ANOMALY", number(elements.mean_anomaly)),
+        ("EPHEMERIS_TYPE", integer(elements.ephemeris_type.into())),
+        ("CLASSIFICATION_TYPE", text(classification)),
+        ("NORAD_CAT_ID", integer(elements.norad_id)),
+        ("ELEMENT_SET_NO", integer(elements.element_set_number)),
+        ("REV_AT_EPOCH", integer(elements.revolution_number)),
+        ("BSTAR", number(elements.drag_term)),
+        ("MEAN_MOTION_DOT", number(elements.mean_motion_dot)),
+        ("MEAN_MOTION_DDOT", number(elements.mean_motion_ddot)),
+    ]);
+    fields
+}
+
+fn write_xml(messages: impl Iterator<Item = Vec<Field>>) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<ndm>\n");
+    for fields in messages {
+        out.push_str("<omm id=\"CCSDS_OMM_VERS\" version=\"2.0\">\n");
+        out.push_str("<header><CREATION_DATE/><ORIGINATOR/></header>\n");
+        out.push_str("<body><segment>\n<metadata>\n");
+        let section = |out: &mut String, keywords: &mut dyn Iterator<Item = &Field>| {
+            for (keyword, value) in keywords {
+                let escaped = value
+                    .as_str()
+                    .replace('&', "&amp;")
+                    .replace('<', "&lt;")
+                    .replace('>', "&gt;");
+                out.push_str(&format!("<{keyword}>{escaped}</{keyword}>\n"));
+            }
+        };
+        section(
+            &mut out,
+            &mut fields
+                .iter()
+                .filter(|(keyword, _)| METADATA.contains(keyword)),
+        );
+        out.push_str("</metadata>\n<data>\n<meanElements>\n");
+        section(
+            &mut out,
+            &mut fields
+                .iter()
+                .filter(|(keyword, _)| MEAN_ELEMENTS.contains(keyword)),
+        );
+        out.push_str("</meanElements>\n<tleParameters>\n");
+        section(
+            &mut out,
+            &mut fields.iter().filter(|(keyword, _)| {
+                !METADATA.contains(keyword) && !MEAN_ELEMENTS.contains(keyword)
+            }),
+        );
+        out.push_str("</tleParameters>\n</data>\n</segment></body>\n</omm>\n");
+    }
+    out.push_str("</ndm>\n");
+    out
+}
+
+fn write_kvn(messages: impl Iterator<Item = Vec<Field>>) -> String {
+    let mut out = String::new();
+    for fields in messages {
+        out.push_str("CCSDS_OMM_VERS = 2.0\n");
+        for (keyword, value) in &fields {
+            out.push_str(&format!("{keyword} = {}\n", value.as_str()));
+        }
+    }
+    out
+}
+
+fn write_json(messages: impl Iterator<Item = Vec<Field>>) -> String {
+    let objects: Vec<String> = messages
+        .map(|fields| {
+            let members: Vec<String> = fields
+                .iter()
+                .map(|(keyword, value)| match value {
+                    Value::Text(text) => format!(
+                        "    \"{keyword}\": {}",
+                        serde_json::Value::from(text.as_str())
+                    ),
+                    Value::Number(number) => format!("    \"{keyword}\": {number}"),
+                })
+                .collect();
+            format!("{{\n{}\n}}", members.join(",\n"))
+        })
+        .collect();
+    format!("[{}]\n", objects.join(", "))
+}
+
+fn elements_from_fields(fields: &HashMap<&str, &str>) -> Result<sgp4::Elements, OmmError> {
+    if let Some(theory) = fields.get("MEAN_ELEMENT_THEORY") {
+        if !theory.eq_ignore_ascii_case("SGP4") {
+            return Err(OmmError::UnsupportedTheory(theory.to_string()));
+        }
+    }
+
+    let text = |field: &'static str| -> Result<&str, OmmError> {
+        fields
+            .get(field)
+            .copied()
+            .filter(|value| !value.is_empty())
+            .ok_or(OmmError::MissingField(field))
+    };
+    let invalid = |field: &'static str, value: &str| OmmError::InvalidField {
+        field,
+        value: value.to_string(),
+    };
+    let number = |field: &'static str| -> Result<f64, OmmError> {
+        let value = text(field)?;
+        value.parse().map_err(|_| invalid(field, value))
+    };
+    let optional_number = |field: &'static str| -> Result<f64, OmmError> {
+        match text(field) {
+            Err(OmmError::MissingField(_)) => Ok(0.0),
+            _ => number(field),
+        }
+    };
+    let optional_integer = |field: &'static str| -> Result<u64, OmmError> {
+        match text(field) {
+            Err(_) => Ok(0),
+            Ok(value) => value.parse().map_err(|_| invalid(field, value)),
+        }
+    };
+
+    let norad_id = text("NORAD_CAT_ID")?;
+    let classification = match fields.get("CLASSIFICATION_TYPE").copied() {
+        None | Some("") | Some("U") => sgp4::Classification::Unclassified,
+        Some("C") => sgp4::Classification::Classified,
+        Some("S") => sgp4::Classification::Secret,
+        Some(other) => return Err(invalid("CLASSIFICATION_TYPE", other)),
+    };
+    let ephemeris_type = optional_integer("EPHEMERIS_TYPE")?;
+
+    Ok(sgp4::Elements {
+        object_name: fields.get("OBJECT_NAME").map(|name| name.to_string()),
+        international_designator: fields
+            .get("OBJECT_ID")
+            .filter(|id| !id.is_empty())
+            .map(|id| id.to_string()),
+        norad_id: norad_id
+            .parse::<NoradId>()
+            .map_err(|_| invalid("NORAD_CAT_ID", norad_id))?
+            .into(),
+        classification,
+        datetime: parse_epoch(text("EPOCH")?)?,
+        mean_motion_dot: optional_number("MEAN_MOTION_DOT")?,
+        mean_motion_ddot: optional_number("MEAN_MOTION_DDOT")?,
+        drag_term: number("BSTAR")?,
+        element_set_number: optional_integer("ELEMENT_SET_NO")?,
+        inclination: number("INCLINATION")?,
+        right_ascension: number("RA_OF_ASC_NODE")?,
+        eccentricity: number("ECCENTRICITY")?,
+        argument_of_perigee: number("ARG_OF_PERICENTER")?,
+        mean_anomaly: number("MEAN_ANOMALY")?,
+        mean_motion: number("MEAN_MOTION")?,
+        revolution_number: optional_integer("REV_AT_EPOCH")?,
+        ephemeris_type: u8::try_from(ephemeris_type)
+            .map_err(|_| invalid("EPHEMERIS_TYPE", &ephemeris_type.to_string()))?,
+    })
+}
+
+/// Parse a CCSDS epoch in calendar (`2020-07-12T21:16:01.000416`) or
+/// day-of-year (`2020-194T21:16:01.000416`) form, with an optional `Z`.
+fn parse_epoch(value: &str) -> Result<NaiveDateTime, OmmError> {
+    let trimmed = value.trim_end_matches('Z');
+    NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%dT%H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(trimmed, "%Y-%jT%H:%M:%S%.f"))
+        .map_err(|_| OmmError::InvalidField {
+            field: "EPOCH",
+            value: value.to_string(),
+        })
+}
@@ -0,0 +1,238 @@
+//! Satellite pass prediction for ground observers.
+//!
+//! Passes are found by sampling the elevation above the observer's mask on a
+//! coarse grid, then refining each rise and set with Brent's method and the
+//! culmination with a golden-section search. Local elevation peaks that stay
+//! below the mask between samples are refined too, so short grazing passes
+//! are not lost to the sampling step.
+use sgp4::chrono::{Duration, NaiveDateTime};
+
+use crate::frames::{EarthOrientation, TemeState};
+use crate::observer::{LookAngles, Observer};
+use crate::roots::{brent_root, golden_max};
+use crate::sun::{is_sunlit, sun_position_km};
+use crate::{PropagationError, Sgp4Propagator};
+
+/// Time tolerance in seconds for refined events.
+const EVENT_TOLERANCE_S: f64 = 1e-3;
+
+/// A single instant within a pass.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PassEvent {
+    pub time: NaiveDateTime,
+    pub look: LookAngles,
+}
+
+/// Optical visibility of a pass from the observer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PassVisibility {
+    /// The satellite is sunlit while the observer is in darkness for at
+    /// least part of the pass.
+    Visible,
+    /// The observer is dark but the satellite stays in Earth's shadow.
+    Eclipsed,
+    /// The observer is in daylight for the whole pass.
+    Daylight,
+}
+
+/// Acquisition, culmination and loss of signal for one pass.
+///
+/// Passes already in progress at the start of the search window, or still in
+/// progress at its end, are clipped to the window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pass {
+    pub aos: PassEvent,
+    pub culmination: PassEvent,
+    pub los: PassEvent,
+    pub visibility: PassVisibility,
+}
+
+impl Pass {
+    /// Highest elevation reached during the pass in degrees.
+    pub fn max_elevation_deg(&self) -> f64 {
+        self.culmination.look.elevation_deg
+    }
+
+    /// Time between acquisition and loss of signal.
+    pub fn duration(&self) -> Duration {
+        self.los.time - self.aos.time
+    }
+}
+
+/// Predicts passes of one satellite over one observer.
+#[derive(Debug, Clone)]
+pub struct PassPredictor<'a> {
+    propagator: &'a Sgp4Propagator,
+    observer: Observer,
+    eop: EarthOrientation,
+    step: Duration,
+    twilight_deg: f64,
+}
+
+impl<'a> PassPredictor<'a> {
+    /// Predictor with a 60 s search step and civil twilight (−6°) as the
+    /// darkness threshold for visibility.
+    pub fn new(propagator: &'a Sgp4Propagator, observer: Observer) -> Self {
+        Self {
+            propagator,
+            observer,
+            eop: EarthOrientation::default(),
+            step: Duration::seconds(60),
+            twilight_deg: -6.0,
+        }
+    }
+
+    /// Earth orientation parameters used for the TEME to ITRF rotation.
+    pub fn with_eop(mut self, eop: EarthOrientation) -> Self {
+        self.eop = eop;
+        self
+    }
+
+    /// Coarse sampling step. Non-positive steps fall back to one second.
+    pub fn with_step(mut self, step: Duration) -> Self {
+        self.step = if step > Duration::zero() {
+            step
+        } else {
+            Duration::seconds(1)
+        };
+        self
+    }
+
+    /// Sun elevation in degrees below which the observer counts as dark.
+    pub fn with_twilight_deg(mut self, twilight_deg: f64) -> Self {
+        self.twilight_deg = twilight_deg;
+        self
+    }
+
+    /// Find every pass between `start` and `end`.
+    pub fn passes(
+        &self,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+    ) -> Result<Vec<Pass>, PropagationError> {
+        if end <= start {
+            return Ok(Vec::new());
+        }
+
+        let span = seconds_between(start, end);
+        let step = self.step.num_milliseconds() as f64 / 1000.0;
+        let at = |offset: f64| start + Duration::microseconds((offset * 1e6).round() as i64);
+        let height = |offset: f64| -> Result<f64, PropagationError> {
+            Ok(self.look(at(offset))?.elevation_deg - self.observer.mask_deg())
+        };
+
+        let mut samples = Vec::new();
+        let mut offset = 0.0;
+        loop {
+            samples.push((offset, height(offset)?));
+            if offset >= span {
+                break;
+            }
+            offset = (offset + step).min(span);
+        }
+
+        // Rise/set crossings, in time order, as (offset, rising).
+        let mut crossings = Vec::new();
+        for (i, pair) in samples.windows(2).enumerate() {
+            let (t0, h0) = pair[0];
+            let (t1, h1) = pair[1];
+            if (h0 < 0.0) != (h1 < 0.0) {
+                let root = brent_root(height, t0, t1, h0, h1, EVENT_TOLERANCE_S)?;
+                crossings.push((root, h1 >= 0.0));
+            } else if h0 < 0.0 && h1 < 0.0 && i + 2 < samples.len() {
+                // A peak hiding between samples i, i+1, i+2.
+                let (t2, h2) = samples[i + 2];
+                if h1 > h0 && h1 >= h2 {
+                    let (peak, peak_height) = golden_max(height, t0, t2, EVENT_TOLERANCE_S)?;
+                    if peak_height >= 0.0 {
+                        let tol = EVENT_TOLERANCE_S;
+                        let rise = brent_root(height, t0, peak, h0, peak_height, tol)?;
+                        let set = brent_root(height, peak, t2, peak_height, h2, tol)?;
+                        crossings.push((rise, true));
+                        crossings.push((set, false));
+                    }
+                }
+            }
+        }
+        crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut windows = Vec::new();
+        let mut rise = if samples[0].1 >= 0.0 { Some(0.0) } else { None };
+        for (offset, rising) in crossings {
+            match (rising, rise) {
+                (true, None) => rise = Some(offset),
+                (false, Some(aos)) => {
+                    windows.push((aos, offset));
+                    rise = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(aos) = rise {
+            windows.push((aos, span));
+        }
+
+        windows
+            .into_iter()
+            .filter(|(aos, los)| los > aos)
+            .map(|(aos, los)| {
+                let (peak, _) = golden_max(height, aos, los, EVENT_TOLERANCE_S)?;
+                let event = |offset: f64| -> Result<PassEvent, PropagationError> {
+                    let time = at(offset);
+                    Ok(PassEvent {
+                        time,
+                        look: self.look(time)?,
+                    })
+                };
+                let aos = event(aos)?;
+                let los = event(los)?;
+                Ok(Pass {
+                    aos,
+                    culmination: event(peak)?,
+                    los,
+                    visibility: self.visibility(aos.time, los.time)?,
+                })
+            })
+            .collect()
+    }
+
+    fn look(&self, time: NaiveDateTime) -> Result<LookAngles, PropagationError> {
+        self.observer.look_angles(self.propagator, time, &self.eop)
+    }
+
+    fn visibility(
+        &self,
+        aos: NaiveDateTime,
+        los: NaiveDateTime,
+    ) -> Result<PassVisibility, PropagationError> {
+        const SAMPLES: i64 = 24;
+        let span_us = (los - aos).num_microseconds().unwrap_or(0);
+
+        let mut observer_dark = false;
+        for i in 0..=SAMPLES {
+            let time = aos + Duration::microseconds(span_us * i / SAMPLES);
+            let sun = sun_position_km(time);
+            let sun_look = self
+                .observer
+                .look_angles_to(&TemeState::new(sun, [0.0; 3], time).to_itrf(&self.eop));
+            if sun_look.elevation_deg >= self.twilight_deg {
+                continue;
+            }
+            observer_dark = true;
+            let satellite = self.propagator.propagate_datetime(time)?;
+            if is_sunlit(satellite.position_km, sun) {
+                return Ok(PassVisibility::Visible);
+            }
+        }
+
+        Ok(if observer_dark {
+            PassVisibility::Eclipsed
+        } else {
+            PassVisibility::Daylight
+        })
+    }
+}
+
+fn seconds_between(start: NaiveDateTime, end: NaiveDateTime) -> f64 {
+    (end - start).num_microseconds().unwrap_or(i64::MAX) as f64 / 1e6
+}
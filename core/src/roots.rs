@@ -0,0 +1,125 @@
+//! Scalar root finding and maximisation used by event searches.
+//!
+//! The objective functions propagate orbits and can therefore fail, so every
+//! routine accepts a fallible closure and forwards the first error.
+
+/// Find a root of `f` in `[a, b]` with Brent's method.
+///
+/// `fa` and `fb` are the already-evaluated endpoints and must bracket a root
+/// (opposite signs, or one of them zero). Converges to within `tol`.
+pub(crate) fn brent_root<E>(
+    mut f: impl FnMut(f64) -> Result<f64, E>,
+    mut a: f64,
+    mut b: f64,
+    mut fa: f64,
+    mut fb: f64,
+    tol: f64,
+) -> Result<f64, E> {
+    if fa == 0.0 {
+        return Ok(a);
+    }
+    if fb == 0.0 {
+        return Ok(b);
+    }
+
+    let mut c = a;
+    let mut fc = fa;
+    let mut d = b - a;
+    let mut e = d;
+
+    for _ in 0..100 {
+        if fb.signum() == fc.signum() {
+            c = a;
+            fc = fa;
+            d = b - a;
+            e = d;
+        }
+        if fc.abs() < fb.abs() {
+            a = b;
+            b = c;
+            c = a;
+            fa = fb;
+            fb = fc;
+            fc = fa;
+        }
+
+        let tol1 = 2.0 * f64::EPSILON * b.abs() + 0.5 * tol;
+        let xm = 0.5 * (c - b);
+        if xm.abs() <= tol1 || fb == 0.0 {
+            return Ok(b);
+        }
+
+        if e.abs() >= tol1 && fa.abs() > fb.abs() {
+            // Attempt inverse quadratic interpolation (secant when a == c).
+            let s = fb / fa;
+            let (mut p, mut q) = if a == c {
+                (2.0 * xm * s, 1.0 - s)
+            } else {
+                let q = fa / fc;
+                let r = fb / fc;
+                (
+                    s * (2.0 * xm * q * (q - r) - (b - a) * (r - 1.0)),
+                    (q - 1.0) * (r - 1.0) * (s - 1.0),
+                )
+            };
+            if p > 0.0 {
+                q = -q;
+            }
+            p = p.abs();
+            let min1 = 3.0 * xm * q - (tol1 * q).abs();
+            let min2 = (e * q).abs();
+            if 2.0 * p < min1.min(min2) {
+                e = d;
+                d = p / q;
+            } else {
+                d = xm;
+                e = d;
+            }
+        } else {
+            d = xm;
+            e = d;
+        }
+
+        a = b;
+        fa = fb;
+        b += if d.abs() > tol1 { d } else { tol1.copysign(xm) };
+        fb = f(b)?;
+    }
+
+    Ok(b)
+}
+
+/// Locate the maximum of a unimodal `f` on `[a, b]` by golden-section search.
+///
+/// Returns the abscissa and value of the maximum to within `tol`.
+pub(crate) fn golden_max<E>(
+    mut f: impl FnMut(f64) -> Result<f64, E>,
+    mut a: f64,
+    mut b: f64,
+    tol: f64,
+) -> Result<(f64, f64), E> {
+    const INV_PHI: f64 = 0.618_033_988_749_894_8;
+
+    let mut x1 = b - INV_PHI * (b - a);
+    let mut x2 = a + INV_PHI * (b - a);
+    let mut f1 = f(x1)?;
+    let mut f2 = f(x2)?;
+
+    while (b - a).abs() > tol {
+        if f1 < f2 {
+            a = x1;
+            x1 = x2;
+            f1 = f2;
+            x2 = a + INV_PHI * (b - a);
+            f2 = f(x2)?;
+        } else {
+            b = x2;
+            x2 = x1;
+            f2 = f1;
+            x1 = b - INV_PHI * (b - a);
+            f1 = f(x1)?;
+        }
+    }
+
+    Ok(if f1 > f2 { (x1, f1) } else { (x2, f2) })
+}
@@ -0,0 +1,60 @@
+//! Low-precision Sun ephemeris and Earth shadow test.
+use sgp4::chrono::NaiveDateTime;
+
+use crate::geodetic::WGS84_A_KM;
+use crate::math::{dot, sub, Vec3};
+use crate::time::{julian_date, J2000_JD};
+
+/// Astronomical unit in km.
+pub const AU_KM: f64 = 149_597_870.7;
+
+/// Geocentric Sun position in km.
+///
+/// Implements Vallado's `sun` algorithm (accurate to about 0.01°), which
+/// returns mean-of-date coordinates. The offset from TEME is far below that
+/// accuracy, so the result can be used directly alongside SGP4 output.
+pub fn sun_position_km(utc: NaiveDateTime) -> [f64; 3] {
+    let t = (julian_date(utc) - J2000_JD) / 36_525.0;
+
+    let mean_longitude = (280.460 + 36_000.771 * t).rem_euclid(360.0);
+    let mean_anomaly = (357.529_109_2 + 35_999.050_34 * t)
+        .rem_euclid(360.0)
+        .to_radians();
+    let ecliptic_longitude = (mean_longitude
+        + 1.914_666_471 * mean_anomaly.sin()
+        + 0.019_994_643 * (2.0 * mean_anomaly).sin())
+    .to_radians();
+    let obliquity = (23.439_291 - 0.013_004_2 * t).to_radians();
+    let distance_au = 1.000_140_612
+        - 0.016_708_617 * mean_anomaly.cos()
+        - 0.000_139_589 * (2.0 * mean_anomaly).cos();
+
+    let r = distance_au * AU_KM;
+    [
+        r * ecliptic_longitude.cos(),
+        r * obliquity.cos() * ecliptic_longitude.sin(),
+        r * obliquity.sin() * ecliptic_longitude.sin(),
+    ]
+}
+
+/// Whether a satellite at `position_km` is lit by the Sun at `sun_km`.
+///
+/// Uses a cylindrical shadow model with the WGS-84 equatorial radius, which
+/// is the usual choice for visual pass predictions.
+pub fn is_sunlit(position_km: Vec3, sun_km: Vec3) -> bool {
+    let sun_distance = dot(sun_km, sun_km).sqrt();
+    let sun_dir = [
+        sun_km[0] / sun_distance,
+        sun_km[1] / sun_distance,
+        sun_km[2] / sun_distance,
+    ];
+    let along = dot(position_km, sun_dir);
+    if along >= 0.0 {
+        return true;
+    }
+    let perpendicular = sub(
+        position_km,
+        [sun_dir[0] * along, sun_dir[1] * along, sun_dir[2] * along],
+    );
+    dot(perpendicular, perpendicular).sqrt() > WGS84_A_KM
+}
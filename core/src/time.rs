@@ -0,0 +1,85 @@
+//! Time scale helpers used by the frame and geometry code.
+//!
+//! All datetimes in the public API are naive UTC, matching the TLE epoch
+//! returned by [`crate::Sgp4Propagator::epoch`].
+use sgp4::chrono::{NaiveDate, NaiveDateTime};
+
+/// Julian date of the J2000.0 epoch (2000-01-01 12:00 TT).
+pub const J2000_JD: f64 = 2_451_545.0;
+
+/// Seconds per day.
+pub const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Offset between Terrestrial Time and TAI in seconds.
+const TT_MINUS_TAI_S: f64 = 32.184;
+
+/// UTC dates on which TAI − UTC changed, with the new offset in seconds.
+const LEAP_SECONDS: [(i32, u32, f64); 28] = [
+    (1972, 1, 10.0),
+    (1972, 7, 11.0),
+    (1973, 1, 12.0),
+    (1974, 1, 13.0),
+    (1975, 1, 14.0),
+    (1976, 1, 15.0),
+    (1977, 1, 16.0),
+    (1978, 1, 17.0),
+    (1979, 1, 18.0),
+    (1980, 1, 19.0),
+    (1981, 7, 20.0),
+    (1982, 7, 21.0),
+    (1983, 7, 22.0),
+    (1985, 7, 23.0),
+    (1988, 1, 24.0),
+    (1990, 1, 25.0),
+    (1991, 1, 26.0),
+    (1992, 7, 27.0),
+    (1993, 7, 28.0),
+    (1994, 7, 29.0),
+    (1996, 1, 30.0),
+    (1997, 7, 31.0),
+    (1999, 1, 32.0),
+    (2006, 1, 33.0),
+    (2009, 1, 34.0),
+    (2012, 7, 35.0),
+    (2015, 7, 36.0),
+    (2017, 1, 37.0),
+];
+
+/// Julian date of a naive datetime, interpreted in whatever scale it carries.
+pub fn julian_date(datetime: NaiveDateTime) -> f64 {
+    let j2000 = NaiveDate::from_ymd_opt(2000, 1, 1)
+        .and_then(|date| date.and_hms_opt(12, 0, 0))
+        .expect("J2000 epoch is a valid datetime");
+    let micros = (datetime - j2000)
+        .num_microseconds()
+        .expect("datetime within ±292k years of J2000");
+    J2000_JD + micros as f64 / (SECONDS_PER_DAY * 1e6)
+}
+
+/// TAI − UTC in seconds at the given UTC instant.
+///
+/// Dates before 1972 use the initial 10 s offset; dates after the last
+/// table entry use the most recent value.
+pub fn tai_minus_utc(utc: NaiveDateTime) -> f64 {
+    let date = utc.date();
+    LEAP_SECONDS
+        .iter()
+        .rev()
+        .find(|(year, month, _)| {
+            NaiveDate::from_ymd_opt(*year, *month, 1).is_some_and(|start| date >= start)
+        })
+        .map(|(_, _, offset)| *offset)
+        .unwrap_or(LEAP_SECONDS[0].2)
+}
+
+/// Julian centuries of Terrestrial Time since J2000.0 for a UTC instant.
+pub fn tt_centuries(utc: NaiveDateTime) -> f64 {
+    let jd_utc = julian_date(utc);
+    let jd_tt = jd_utc + (tai_minus_utc(utc) + TT_MINUS_TAI_S) / SECONDS_PER_DAY;
+    (jd_tt - J2000_JD) / 36_525.0
+}
+
+/// Julian date in UT1 given UTC and the UT1 − UTC offset in seconds.
+pub fn julian_date_ut1(utc: NaiveDateTime, dut1_s: f64) -> f64 {
+    julian_date(utc) + dut1_s / SECONDS_PER_DAY
+}
@@ -0,0 +1,591 @@
+//! Two- and three-line element sets: strict validation and formatting.
+//!
+//! The [`sgp4`] parser, and with it [`crate::Sgp4Propagator::from_tle`],
+//! stops at the first problem. [`TleValidator`] checks every record of a
+//! catalog against the fixed-column format of Space Track Report #3
+//! (separators, field syntax, mod-10 checksums, matching catalog numbers)
+//! and against plausible element ranges, and reports every problem with the
+//! line and column it was found at. [`format_elements`] goes the other way
+//! and writes mean elements back out as a pair of lines.
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use sgp4::chrono::{Datelike, Duration, NaiveDateTime, Timelike};
+use thiserror::Error;
+
+use crate::{NoradId, NoradIdError};
+
+/// Width of both element lines, including the checksum.
+pub const LINE_LENGTH: usize = 69;
+
+/// What a [`Diagnostic`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticKind {
+    /// A record ended before both element lines.
+    MissingLine,
+    /// An element line does not start with its line number.
+    LineNumber,
+    /// An element line is not 69 characters long.
+    LineLength,
+    /// A column that must be blank is not.
+    Separator,
+    /// A field does not match its fixed-column syntax or valid range.
+    Field,
+    /// The mod-10 checksum in column 69 does not match.
+    Checksum,
+    /// The two lines carry different catalog numbers.
+    CatalogMismatch,
+    /// The epoch year is outside the validator's window.
+    EpochOutOfWindow,
+    EccentricityOutOfRange,
+    MeanMotionOutOfRange,
+}
+
+impl DiagnosticKind {
+    /// Stable kebab-case identifier for machine-readable reports.
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::MissingLine => "missing-line",
+            Self::LineNumber => "line-number",
+            Self::LineLength => "line-length",
+            Self::Separator => "separator",
+            Self::Field => "field",
+            Self::Checksum => "checksum",
+            Self::CatalogMismatch => "catalog-mismatch",
+            Self::EpochOutOfWindow => "epoch-out-of-window",
+            Self::EccentricityOutOfRange => "eccentricity-out-of-range",
+            Self::MeanMotionOutOfRange => "mean-motion-out-of-range",
+        }
+    }
+}
+
+/// One problem found in a catalog.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// 1-based line in the input, counting blank lines.
+    pub line: usize,
+    /// 1-based column of the offending field.
+    pub column: usize,
+    pub kind: DiagnosticKind,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+/// Result of validating a whole catalog.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    /// Number of records found, valid or not.
+    pub objects: usize,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+}
+
+/// Checks element sets against the TLE format and plausible ranges.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TleValidator {
+    /// Accepted epoch years. Two-digit years map to 1957–2056, which the
+    /// default accepts in full; narrow it to catch stale or future epochs.
+    pub epoch_years: RangeInclusive<i32>,
+    pub eccentricity: RangeInclusive<f64>,
+    /// Accepted mean motion in revolutions per day.
+    pub mean_motion_rev_per_day: RangeInclusive<f64>,
+}
+
+impl Default for TleValidator {
+    fn default() -> Self {
+        Self {
+            epoch_years: 1957..=2056,
+            eccentricity: 0.0..=0.99,
+            // From beyond the Moon's distance to below 150 km altitude.
+            mean_motion_rev_per_day: 0.05..=18.0,
+        }
+    }
+}
+
+/// Line 1 columns that must be blank.
+const LINE1_SEPARATORS: [usize; 8] = [2, 9, 18, 33, 44, 53, 62, 64];
+/// Line 2 columns that must be blank.
+const LINE2_SEPARATORS: [usize; 7] = [2, 8, 17, 26, 34, 43, 52];
+
+/// One element line being checked, with its position in the input.
+struct Line<'a> {
+    text: &'a str,
+    number: usize,
+    diagnostics: &'a mut Vec<Diagnostic>,
+}
+
+impl Line<'_> {
+    fn report(&mut self, column: usize, kind: DiagnosticKind, message: String) {
+        self.diagnostics.push(Diagnostic {
+            line: self.number,
+            column,
+            kind,
+            message,
+        });
+    }
+
+    /// The 1-based, inclusive column range, or `None` if the line is too
+    /// short, which has already been reported as a length problem.
+    fn field(&self, columns: RangeInclusive<usize>) -> Option<&str> {
+        self.text.get(columns.start() - 1..*columns.end())
+    }
+
+    fn number<T: std::str::FromStr>(
+        &mut self,
+        columns: RangeInclusive<usize>,
+        name: &str,
+    ) -> Option<T> {
+        let start = *columns.start();
+        let text = self.field(columns)?;
+        match text.trim().parse() {
+            Ok(value) if !text.trim().is_empty() => Some(value),
+            _ => {
+                let message = format!("{name} `{text}` is not a number");
+                self.report(start, DiagnosticKind::Field, message);
+                None
+            }
+        }
+    }
+
+    fn angle(&mut self, columns: RangeInclusive<usize>, name: &str, max: f64) {
+        let start = *columns.start();
+        if let Some(value) = self.number::<f64>(columns, name) {
+            if !(0.0..=max).contains(&value) {
+                let message = format!("{name} {value} outside [0, {max}] degrees");
+                self.report(start, DiagnosticKind::Field, message);
+            }
+        }
+    }
+
+    /// Check the layout common to both lines: line number, length,
+    /// separators and checksum.
+    fn check_layout(&mut self, line_number: char, separators: &[usize]) {
+        if !self.text.starts_with(line_number) {
+            let message = format!("expected line {line_number}");
+            self.report(1, DiagnosticKind::LineNumber, message);
+        }
+        let length = self.text.chars().count();
+        if length != LINE_LENGTH || !self.text.is_ascii() {
+            let message = format!("line is {length} characters long, expected {LINE_LENGTH}");
+            self.report(
+                length.min(LINE_LENGTH) + 1,
+                DiagnosticKind::LineLength,
+                message,
+            );
+        }
+        for &column in separators {
+            if let Some(text) = self.field(column..=column) {
+                if text != " " {
+                    let message = format!("expected a blank, found `{text}`");
+                    self.report(column, DiagnosticKind::Separator, message);
+                }
+            }
+        }
+        if let (Some(body), Some(digit)) = (self.field(1..=68), self.field(69..=69)) {
+            let expected = checksum(body);
+            if digit != expected.to_string() {
+                let message = format!("checksum `{digit}` should be {expected}");
+                self.report(69, DiagnosticKind::Checksum, message);
+            }
+        }
+    }
+
+    fn catalog_number(&mut self) -> Option<NoradId> {
+        let text = self.field(3..=7)?;
+        match NoradId::from_alpha5(text) {
+            Ok(id) => Some(id),
+            Err(error) => {
+                self.report(3, DiagnosticKind::Field, error.to_string());
+                None
+            }
+        }
+    }
+
+    /// An exponent field such as ` 12345-4` or `-31515-4`: a sign, five
+    /// mantissa digits with an implied leading decimal point, an exponent
+    /// sign and one exponent digit.
+    fn exponent(&mut self, columns: RangeInclusive<usize>, name: &str) {
+        let start = *columns.start();
+        let Some(text) = self.field(columns) else {
+            return;
+        };
+        let bytes = text.as_bytes();
+        let well_formed = matches!(bytes[0], b' ' | b'+' | b'-')
+            && bytes[1..6].iter().all(|&b| b.is_ascii_digit() || b == b' ')
+            && matches!(bytes[6], b'+' | b'-')
+            && bytes[7].is_ascii_digit();
+        if !well_formed {
+            let message = format!("{name} `{text}` is not in exponent form");
+            self.report(start, DiagnosticKind::Field, message);
+        }
+    }
+
+    fn digits(&mut self, columns: RangeInclusive<usize>, name: &str) {
+        let start = *columns.start();
+        let Some(text) = self.field(columns) else {
+            return;
+        };
+        if !text.trim_start().bytes().all(|b| b.is_ascii_digit()) {
+            let message = format!("{name} `{text}` is not a whole number");
+            self.report(start, DiagnosticKind::Field, message);
+        }
+    }
+}
+
+impl TleValidator {
+    /// Validate one pair of element lines. `first_line` is the 1-based
+    /// position of `line1` in its input and is used for reporting only.
+    pub fn validate_pair(&self, line1: &str, line2: &str, first_line: usize) -> Vec<Diagnostic> {
+        self.check_pair((first_line, line1), (first_line + 1, line2))
+    }
+
+    /// Validate a 2- or 3-line catalog. A record starts with an element
+    /// line 1 or, in 3-line catalogs, with a name line; blank lines are
+    /// skipped.
+    pub fn validate_catalog(&self, text: &str) -> ValidationReport {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim_end()))
+            .filter(|(_, line)| !line.is_empty());
+        let mut report = ValidationReport::default();
+
+        while let Some((number, line)) = lines.next() {
+            report.objects += 1;
+            let line1 = if line.starts_with("1 ") {
+                (number, line)
+            } else {
+                match lines.next() {
+                    Some(line1) => line1,
+                    None => {
+                        report.diagnostics.push(missing_line(number + 1, 1));
+                        break;
+                    }
+                }
+            };
+            let Some(line2) = lines.next() else {
+                report.diagnostics.push(missing_line(line1.0 + 1, 2));
+                break;
+            };
+            report.diagnostics.extend(self.check_pair(line1, line2));
+        }
+        report
+    }
+
+    fn check_pair(&self, line1: (usize, &str), line2: (usize, &str)) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let id1 = self.check_line1(&mut Line {
+            number: line1.0,
+            text: line1.1,
+            diagnostics: &mut diagnostics,
+        });
+        let mut line2 = Line {
+            number: line2.0,
+            text: line2.1,
+            diagnostics: &mut diagnostics,
+        };
+        let id2 = self.check_line2(&mut line2);
+        if let (Some(id1), Some(id2)) = (id1, id2) {
+            if id1 != id2 {
+                let message = format!("catalog number {id2} does not match {id1} on line 1");
+                line2.report(3, DiagnosticKind::CatalogMismatch, message);
+            }
+        }
+        diagnostics
+    }
+
+    fn check_line1(&self, line: &mut Line<'_>) -> Option<NoradId> {
+        line.check_layout('1', &LINE1_SEPARATORS);
+        let id = line.catalog_number();
+        if let Some(class) = line.field(8..=8) {
+            if !matches!(class, "U" | "C" | "S") {
+                let message = format!("classification `{class}` is not U, C or S");
+                line.report(8, DiagnosticKind::Field, message);
+            }
+        }
+        if let Some(designator) = line.field(10..=17) {
+            let bytes = designator.as_bytes();
+            let well_formed = designator.trim().is_empty()
+                || (bytes[..5].iter().all(u8::is_ascii_digit)
+                    && bytes[5..]
+                        .iter()
+                        .all(|&b| b.is_ascii_uppercase() || b == b' '));
+            if !well_formed {
+                let message = format!("international designator `{designator}` is malformed");
+                line.report(10, DiagnosticKind::Field, message);
+            }
+        }
+        self.check_epoch(line);
+        line.number::<f64>(34..=43, "first derivative of mean motion");
+        line.exponent(45..=52, "second derivative of mean motion");
+        line.exponent(54..=61, "drag term");
+        line.digits(63..=63, "ephemeris type");
+        line.digits(65..=68, "element set number");
+        id
+    }
+
+    fn check_line2(&self, line: &mut Line<'_>) -> Option<NoradId> {
+        line.check_layout('2', &LINE2_SEPARATORS);
+        let id = line.catalog_number();
+        line.angle(9..=16, "inclination", 180.0);
+        line.angle(18..=25, "right ascension", 360.0);
+        if let Some(text) = line.field(27..=33) {
+            if text.bytes().all(|b| b.is_ascii_digit()) {
+                let eccentricity = format!("0.{text}").parse::<f64>().unwrap_or_default();
+                if !self.eccentricity.contains(&eccentricity) {
+                    let message = format!(
+                        "eccentricity {eccentricity} outside {:?}",
+                        self.eccentricity
+                    );
+                    line.report(27, DiagnosticKind::EccentricityOutOfRange, message);
+                }
+            } else {
+                let message = format!("eccentricity `{text}` must be 7 digits");
+                line.report(27, DiagnosticKind::Field, message);
+            }
+        }
+        line.angle(35..=42, "argument of perigee", 360.0);
+        line.angle(44..=51, "mean anomaly", 360.0);
+        if let Some(mean_motion) = line.number::<f64>(53..=63, "mean motion") {
+            if !self.mean_motion_rev_per_day.contains(&mean_motion) {
+                let message = format!(
+                    "mean motion {mean_motion} rev/day outside {:?}",
+                    self.mean_motion_rev_per_day
+                );
+                line.report(53, DiagnosticKind::MeanMotionOutOfRange, message);
+            }
+        }
+        line.digits(64..=68, "revolution number");
+        id
+    }
+
+    fn check_epoch(&self, line: &mut Line<'_>) {
+        let Some(two_digit_year) = line.number::<i32>(19..=20, "epoch year") else {
+            return;
+        };
+        let year = if two_digit_year < 57 {
+            2000 + two_digit_year
+        } else {
+            1900 + two_digit_year
+        };
+        if !self.epoch_years.contains(&year) {
+            let message = format!("epoch year {year} outside {:?}", self.epoch_years);
+            line.report(19, DiagnosticKind::EpochOutOfWindow, message);
+        }
+        let Some(day) = line.number::<f64>(21..=32, "epoch day") else {
+            return;
+        };
+        let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+        let days = if leap { 366.0 } else { 365.0 };
+        if !(1.0..days + 1.0).contains(&day) {
+            let message = format!("epoch day {day} outside year {year}");
+            line.report(21, DiagnosticKind::Field, message);
+        }
+    }
+}
+
+fn missing_line(line: usize, element_line: u8) -> Diagnostic {
+    Diagnostic {
+        line,
+        column: 1,
+        kind: DiagnosticKind::MissingLine,
+        message: format!("record ends before element line {element_line}"),
+    }
+}
+
+/// The mod-10 checksum of an element line's first 68 columns: the sum of
+/// its digits, counting each minus sign as 1.
+pub fn checksum(columns: &str) -> u32 {
+    columns
+        .chars()
+        .map(|c| match c {
+            '-' => 1,
+            _ => c.to_digit(10).unwrap_or(0),
+        })
+        .sum::<u32>()
+        % 10
+}
+
+/// Validate a catalog with the default [`TleValidator`].
+pub fn validate_catalog(text: &str) -> ValidationReport {
+    TleValidator::default().validate_catalog(text)
+}
+
+/// Errors raised while formatting elements as a TLE.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum TleFormatError {
+    #[error(transparent)]
+    CatalogNumber(#[from] NoradIdError),
+    #[error("{field} {value} does not fit its TLE columns")]
+    FieldOverflow { field: &'static str, value: String },
+    #[error("epoch {0} is outside the TLE years 1957-2056")]
+    EpochOutOfRange(NaiveDateTime),
+}
+
+/// Format mean elements as TLE lines 1 and 2, with Alpha-5 catalog numbers
+/// and checksums.
+///
+/// Values are rounded to the precision of their columns, so a round trip
+/// through text reproduces elements read from a TLE exactly.
+pub fn format_elements(elements: &sgp4::Elements) -> Result<(String, String), TleFormatError> {
+    let catalog_number = NoradId::new(elements.norad_id)?.to_alpha5()?;
+    let classification = match elements.classification {
+        sgp4::Classification::Unclassified => 'U',
+        sgp4::Classification::Classified => 'C',
+        sgp4::Classification::Secret => 'S',
+    };
+    let overflow = |field, value: f64| TleFormatError::FieldOverflow {
+        field,
+        value: value.to_string(),
+    };
+
+    let line1 = format!(
+        "1 {catalog_number}{classification} {:<8} {} {} {} {} {} {:>4}",
+        designator(elements.international_designator.as_deref())?,
+        epoch_field(elements.datetime)?,
+        fraction_field(elements.mean_motion_dot)
+            .ok_or_else(|| overflow("first derivative of mean motion", elements.mean_motion_dot))?,
+        // The feeds write a zero second derivative as ` 00000-0` and a zero
+        // drag term as ` 00000+0`.
+        exponent_field(elements.mean_motion_ddot, '-').ok_or_else(|| overflow(
+            "second derivative of mean motion",
+            elements.mean_motion_ddot
+        ))?,
+        exponent_field(elements.drag_term, '+')
+            .ok_or_else(|| overflow("drag term", elements.drag_term))?,
+        elements.ephemeris_type % 10,
+        elements.element_set_number % 10_000,
+    );
+
+    let eccentricity = (elements.eccentricity * 1e7).round();
+    if !(0.0..1e7).contains(&eccentricity) {
+        return Err(overflow("eccentricity", elements.eccentricity));
+    }
+    let angle = |field, value: f64| {
+        if (0.0..=360.0).contains(&value) {
+            Ok(format!("{value:8.4}"))
+        } else {
+            Err(overflow(field, value))
+        }
+    };
+    let mean_motion = format!("{:11.8}", elements.mean_motion);
+    if mean_motion.len() != 11 || elements.mean_motion < 0.0 {
+        return Err(overflow("mean motion", elements.mean_motion));
+    }
+    let line2 = format!(
+        "2 {catalog_number} {} {} {:07} {} {} {mean_motion}{:>5}",
+        angle("inclination", elements.inclination)?,
+        angle("right ascension", elements.right_ascension)?,
+        eccentricity as u32,
+        angle("argument of perigee", elements.argument_of_perigee)?,
+        angle("mean anomaly", elements.mean_anomaly)?,
+        elements.revolution_number % 100_000,
+    );
+
+    Ok((with_checksum(line1), with_checksum(line2)))
+}
+
+fn with_checksum(mut line: String) -> String {
+    let sum = checksum(&line);
+    line.push(char::from_digit(sum, 10).expect("checksum is a digit"));
+    line
+}
+
+/// `1998-067A` as `98067A`. Anything else is kept as given, if it fits the
+/// eight columns.
+fn designator(designator: Option<&str>) -> Result<String, TleFormatError> {
+    let field = match designator.and_then(|designator| designator.split_once('-')) {
+        Some((year, piece)) if year.len() == 4 => format!("{}{piece}", &year[2..]),
+        _ => designator.unwrap_or_default().to_string(),
+    };
+    if field.chars().count() > 8 {
+        return Err(TleFormatError::FieldOverflow {
+            field: "international designator",
+            value: designator.unwrap_or_default().to_string(),
+        });
+    }
+    Ok(field)
+}
+
+/// Epoch as `YYDDD.DDDDDDDD`, rounded to 1e-8 day.
+fn epoch_field(epoch: NaiveDateTime) -> Result<String, TleFormatError> {
+    // 1e-8 day in nanoseconds.
+    const UNIT_NS: i64 = 864_000;
+    let nanos = i64::from(epoch.num_seconds_from_midnight()) * 1_000_000_000
+        + i64::from(epoch.nanosecond());
+    let units = (nanos + UNIT_NS / 2) / UNIT_NS;
+    let rounded = epoch.date().and_hms_opt(0, 0, 0).expect("midnight exists")
+        + Duration::nanoseconds(units * UNIT_NS);
+    let year = rounded.year();
+    if !(1957..=2056).contains(&year) {
+        return Err(TleFormatError::EpochOutOfRange(epoch));
+    }
+    let fraction = (rounded
+        - rounded
+            .date()
+            .and_hms_opt(0, 0, 0)
+            .expect("midnight exists"))
+    .num_nanoseconds()
+    .expect("a day fits in nanoseconds")
+        / UNIT_NS;
+    Ok(format!(
+        "{:02}{:03}.{fraction:08}",
+        year % 100,
+        rounded.ordinal()
+    ))
+}
+
+/// A value below 1 in magnitude as ` .12345678` or `-.12345678`.
+fn fraction_field(value: f64) -> Option<String> {
+    let digits = (value.abs() * 1e8).round();
+    if digits >= 1e8 {
+        return None;
+    }
+    let sign = if value < 0.0 && digits > 0.0 {
+        '-'
+    } else {
+        ' '
+    };
+    Some(format!("{sign}.{:08}", digits as u32))
+}
+
+/// A value in the implied-decimal exponent form ` 12345-4`, meaning
+/// 0.12345e-4. Zero is written with `zero_sign` as its exponent sign.
+fn exponent_field(value: f64, zero_sign: char) -> Option<String> {
+    let zero = format!(" 00000{zero_sign}0");
+    if value == 0.0 {
+        return Some(zero);
+    }
+    let mut exponent = value.abs().log10().floor() as i32 + 1;
+    let mut mantissa = (value.abs() / 10f64.powi(exponent) * 1e5).round() as u32;
+    if mantissa >= 100_000 {
+        mantissa /= 10;
+        exponent += 1;
+    }
+    if exponent < -9 {
+        return Some(zero);
+    }
+    if exponent > 9 {
+        return None;
+    }
+    let sign = if value < 0.0 { '-' } else { ' ' };
+    let exponent_sign = if exponent > 0 { '+' } else { '-' };
+    Some(format!(
+        "{sign}{mantissa:05}{exponent_sign}{}",
+        exponent.abs()
+    ))
+}
@@ -0,0 +1,98 @@
+use approx::assert_relative_eq;
+use chrono::Duration;
+use openastroviz_core::{
+    fp32, DispatchTimes, ElementSet, GpuBackendError, ObjectStatus, PackedElements, Sgp4Propagator,
+    StateBuffer,
+};
+
+const LINE1: &str = "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753";
+const LINE2: &str = "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
+
+fn vanguard() -> Sgp4Propagator {
+    Sgp4Propagator::from_tle(None, LINE1, LINE2).unwrap()
+}
+
+#[test]
+fn packs_initialized_constants_in_sgp4_units() {
+    let set = ElementSet::new(vec![vanguard()]);
+    let packed = set.packed()[0];
+
+    // 3 u32 flags, 36 scalar constants, two 13-term third-body tables and
+    // 10 resonance terms, all 4 bytes wide.
+    assert_eq!(std::mem::size_of::<PackedElements>(), 76 * 4);
+    assert_eq!(packed.status(), ObjectStatus::Ok);
+    assert_eq!(packed.method, fp32::METHOD_NEAR_EARTH);
+    assert_relative_eq!(packed.inclination, 34.2682_f32.to_radians(), epsilon = 1e-6);
+    assert_relative_eq!(packed.eccentricity, 0.1859667, epsilon = 1e-7);
+    // Brouwer mean motion is within a fraction of a percent of the Kozai one.
+    assert_relative_eq!(
+        packed.mean_motion,
+        (10.82419157 * std::f64::consts::TAU / 1440.0) as f32,
+        max_relative = 1e-3
+    );
+    assert!(packed.c1 > 0.0, "positive B* gives positive drag");
+}
+
+#[test]
+fn uniform_times_resolve_against_each_epoch() {
+    let propagator = vanguard();
+    let set: ElementSet = [propagator.clone(), propagator.clone()]
+        .into_iter()
+        .collect();
+    let time = propagator.epoch() + Duration::minutes(360);
+
+    let minutes = DispatchTimes::Uniform(time)
+        .minutes_since_epoch(set.propagators())
+        .unwrap();
+    assert_eq!(minutes.len(), 2);
+    assert_relative_eq!(minutes[0], 360.0, epsilon = 1e-9);
+}
+
+#[test]
+fn per_object_times_must_match_the_set() {
+    let set = ElementSet::new(vec![vanguard()]);
+    let err = DispatchTimes::MinutesSinceEpoch(&[0.0, 1.0])
+        .minutes_since_epoch(set.propagators())
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        GpuBackendError::TimeCountMismatch {
+            expected: 1,
+            actual: 2
+        }
+    ));
+}
+
+#[test]
+fn status_codes_round_trip() {
+    for status in [
+        ObjectStatus::Ok,
+        ObjectStatus::EccentricityOutOfRange,
+        ObjectStatus::MeanMotionNegative,
+        ObjectStatus::PerturbedEccentricityOutOfRange,
+        ObjectStatus::SemiLatusRectumNegative,
+        ObjectStatus::SubOrbital,
+        ObjectStatus::Decayed,
+        ObjectStatus::InvalidTime,
+        ObjectStatus::InvalidElements,
+        ObjectStatus::Pending,
+    ] {
+        assert_eq!(ObjectStatus::from_code(status.code()), Some(status));
+    }
+    assert_eq!(ObjectStatus::from_code(42), None);
+}
+
+#[test]
+fn state_buffer_tracks_failures_per_slot() {
+    let state = vanguard().propagate_minutes(0.0).unwrap();
+    let mut buffer = StateBuffer::with_len(2);
+    assert_eq!(buffer.status, vec![ObjectStatus::Pending; 2]);
+
+    buffer.set(0, Ok(state));
+    buffer.set(1, Err(ObjectStatus::Decayed));
+
+    assert_eq!(buffer.state(0), Some(state));
+    assert_eq!(buffer.state(1), None);
+    assert!(buffer.x_km[1].is_nan());
+    assert_eq!(buffer.ok_count(), 1);
+}
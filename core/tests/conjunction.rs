@@ -0,0 +1,168 @@
+use approx::assert_relative_eq;
+use chrono::{Duration, NaiveDateTime};
+use openastroviz_core::conjunction::{ApproachSolver, ClosestApproach};
+use openastroviz_core::Sgp4Propagator;
+
+const ISS_LINE1: &str = "1 25544U 98067A   20194.88612269 -.00002218  00000-0 -31515-4 0  9992";
+const ISS_LINE2: &str = "2 25544  51.6461 221.2784 0001413  89.1723 280.4612 15.49507896236008";
+
+/// Revolutions per day of the synthetic orbits, about 400 km up.
+const MEAN_MOTION: f64 = 15.5;
+
+/// A drag-free circular orbit with its ascending node on the TEME x axis,
+/// reaching the node `lag_s` seconds after the epoch.
+fn circular(norad_id: u64, inclination: f64, lag_s: f64) -> Sgp4Propagator {
+    let mut elements =
+        sgp4::Elements::from_tle(None, ISS_LINE1.as_bytes(), ISS_LINE2.as_bytes()).unwrap();
+    elements.norad_id = norad_id;
+    elements.inclination = inclination;
+    elements.right_ascension = 0.0;
+    elements.eccentricity = 0.0;
+    elements.argument_of_perigee = 0.0;
+    elements.mean_anomaly = (-MEAN_MOTION * 360.0 / 86_400.0 * lag_s).rem_euclid(360.0);
+    elements.mean_motion = MEAN_MOTION;
+    elements.mean_motion_dot = 0.0;
+    elements.mean_motion_ddot = 0.0;
+    elements.drag_term = 0.0;
+    Sgp4Propagator::from_elements(elements).unwrap()
+}
+
+/// Two orbits inclined at 45° and 135° cross the node at right angles with
+/// the same speed, and their short-period perturbations match, so the
+/// geometry of the crossing is known in closed form.
+fn crossing(lag_s: f64) -> (Sgp4Propagator, Sgp4Propagator) {
+    (circular(90_001, 45.0, 0.0), circular(90_002, 135.0, lag_s))
+}
+
+fn norm(v: [f64; 3]) -> f64 {
+    v.iter().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+fn range_km(primary: &Sgp4Propagator, secondary: &Sgp4Propagator, time: NaiveDateTime) -> f64 {
+    let a = primary.propagate_datetime(time).unwrap().position_km;
+    let b = secondary.propagate_datetime(time).unwrap().position_km;
+    norm([b[0] - a[0], b[1] - a[1], b[2] - a[2]])
+}
+
+/// The closest approach within ten minutes of the epoch.
+fn solve(lag_s: f64) -> ClosestApproach {
+    let (primary, secondary) = crossing(lag_s);
+    let epoch = primary.epoch();
+    ApproachSolver::new(&primary, &secondary)
+        .closest_approach(epoch - Duration::minutes(10), epoch + Duration::minutes(10))
+        .unwrap()
+        .unwrap()
+}
+
+#[test]
+fn a_simultaneous_crossing_is_a_collision() {
+    let collision = solve(0.0);
+    assert!(collision.miss_distance_km < 1e-3, "{collision:?}");
+    // Short-period terms leave both objects a little short of the node at
+    // the epoch.
+    let after_epoch = collision.tca - circular(1, 45.0, 0.0).epoch();
+    assert!(after_epoch > Duration::zero() && after_epoch < Duration::seconds(5));
+}
+
+#[test]
+fn solves_a_right_angle_crossing() {
+    // The secondary reaches the node 2 s after the primary. Moving in
+    // straight lines at speed v they are closest 1 s after the primary
+    // passes the node, v·√2 km apart, with the miss split evenly between
+    // the primary's transverse and normal axes.
+    let node = solve(0.0).tca;
+    let approach = solve(2.0);
+
+    let offset = (approach.tca - node).num_microseconds().unwrap();
+    assert!(
+        (offset - 1_000_000).abs() < 5_000,
+        "TCA {offset} µs after the node"
+    );
+    let speed = norm(approach.primary.velocity_km_s);
+    let miss = approach.miss_distance_km;
+    assert_relative_eq!(miss, speed * 2_f64.sqrt(), max_relative = 5e-3);
+    assert_relative_eq!(
+        approach.relative_speed_km_s(),
+        speed * 2_f64.sqrt(),
+        max_relative = 5e-3
+    );
+
+    let [radial, transverse, normal] = approach.miss_rtn_km;
+    assert!(radial.abs() < 1e-2 * miss, "radial miss {radial} km");
+    assert_relative_eq!(transverse, -miss / 2_f64.sqrt(), max_relative = 1e-2);
+    assert_relative_eq!(normal, -miss / 2_f64.sqrt(), max_relative = 1e-2);
+    assert_relative_eq!(norm(approach.miss_rtn_km), miss, max_relative = 1e-12);
+
+    // At TCA the miss vector is perpendicular to the relative velocity.
+    let along = (0..3)
+        .map(|i| {
+            (approach.secondary.position_km[i] - approach.primary.position_km[i])
+                * approach.relative_velocity_km_s[i]
+        })
+        .sum::<f64>();
+    assert!(along.abs() < 1e-6 * miss * approach.relative_speed_km_s());
+    assert_eq!(approach.primary.epoch, approach.tca);
+}
+
+#[test]
+fn approaches_match_brute_force_sampling() {
+    let (primary, secondary) = crossing(30.0);
+    let start = primary.epoch() - Duration::minutes(5);
+    let seconds = 3 * 3600;
+    let end = start + Duration::seconds(seconds);
+
+    let approaches = ApproachSolver::new(&primary, &secondary)
+        .closest_approaches(start, end)
+        .unwrap();
+
+    let ranges: Vec<f64> = (0..=seconds)
+        .map(|s| range_km(&primary, &secondary, start + Duration::seconds(s)))
+        .collect();
+    let minima: Vec<i64> = (1..seconds as usize)
+        .filter(|&s| ranges[s] < ranges[s - 1] && ranges[s] <= ranges[s + 1])
+        .map(|s| s as i64)
+        .collect();
+
+    // The orbits meet at both nodes, twice per revolution.
+    assert!(minima.len() >= 4, "{minima:?}");
+    assert_eq!(approaches.len(), minima.len());
+    for (approach, second) in approaches.iter().zip(minima) {
+        let sampled = start + Duration::seconds(second);
+        assert!((approach.tca - sampled).num_milliseconds().abs() <= 1_000);
+        assert!(approach.miss_distance_km <= ranges[second as usize] + 1e-9);
+    }
+}
+
+#[test]
+fn windows_clip_the_approach() {
+    let (primary, secondary) = crossing(2.0);
+    let epoch = primary.epoch();
+    let solver = ApproachSolver::new(&primary, &secondary);
+
+    // After the crossing the pair only separates.
+    let start = epoch + Duration::seconds(10);
+    let end = epoch + Duration::seconds(20);
+    assert!(solver.closest_approaches(start, end).unwrap().is_empty());
+    let clipped = solver.closest_approach(start, end).unwrap().unwrap();
+    assert_eq!(clipped.tca, start);
+    assert_relative_eq!(
+        clipped.miss_distance_km,
+        range_km(&primary, &secondary, start),
+        max_relative = 1e-9
+    );
+
+    assert!(solver.closest_approach(end, start).unwrap().is_none());
+    assert!(solver.closest_approaches(end, end).unwrap().is_empty());
+}
+
+#[test]
+fn sub_millisecond_steps_still_advance() {
+    let (primary, secondary) = crossing(2.0);
+    let tca = solve(2.0).tca;
+    let approaches = ApproachSolver::new(&primary, &secondary)
+        .with_step(Duration::microseconds(500))
+        .closest_approaches(tca - Duration::seconds(1), tca + Duration::seconds(1))
+        .unwrap();
+    assert_eq!(approaches.len(), 1);
+    assert!((approaches[0].tca - tca).num_microseconds().unwrap().abs() < 10);
+}
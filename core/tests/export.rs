@@ -0,0 +1,180 @@
+use openastroviz_core::omm::{self, OmmFormat};
+use openastroviz_core::tle::{self, TleFormatError};
+use openastroviz_core::{NoradId, NoradIdError, Sgp4Propagator};
+
+const ISS_LINE1: &str = "1 25544U 98067A   20194.88612269 -.00002218  00000-0 -31515-4 0  9992";
+const ISS_LINE2: &str = "2 25544  51.6461 221.2784 0001413  89.1723 280.4612 15.49507896236008";
+
+fn iss() -> sgp4::Elements {
+    sgp4::Elements::from_tle(
+        Some("ISS (ZARYA)".to_string()),
+        ISS_LINE1.as_bytes(),
+        ISS_LINE2.as_bytes(),
+    )
+    .unwrap()
+}
+
+fn assert_same_elements(actual: &sgp4::Elements, expected: &sgp4::Elements) {
+    assert_eq!(actual.object_name, expected.object_name);
+    assert_eq!(
+        actual.international_designator,
+        expected.international_designator
+    );
+    assert_eq!(actual.norad_id, expected.norad_id);
+    assert_eq!(actual.classification, expected.classification);
+    let epoch_offset = (actual.datetime - expected.datetime)
+        .num_nanoseconds()
+        .unwrap();
+    assert!(epoch_offset.abs() < 1_000, "{epoch_offset} ns");
+    assert_eq!(actual.mean_motion_dot, expected.mean_motion_dot);
+    assert_eq!(actual.mean_motion_ddot, expected.mean_motion_ddot);
+    assert_eq!(actual.drag_term, expected.drag_term);
+    assert_eq!(actual.element_set_number, expected.element_set_number);
+    assert_eq!(actual.inclination, expected.inclination);
+    assert_eq!(actual.right_ascension, expected.right_ascension);
+    assert_eq!(actual.eccentricity, expected.eccentricity);
+    assert_eq!(actual.argument_of_perigee, expected.argument_of_perigee);
+    assert_eq!(actual.mean_anomaly, expected.mean_anomaly);
+    assert_eq!(actual.mean_motion, expected.mean_motion);
+    assert_eq!(actual.revolution_number, expected.revolution_number);
+    assert_eq!(actual.ephemeris_type, expected.ephemeris_type);
+}
+
+#[test]
+fn rewrites_tles_column_for_column() {
+    let propagator = Sgp4Propagator::from_tle(None, ISS_LINE1, ISS_LINE2).unwrap();
+    let (line1, line2) = propagator.to_tle().unwrap();
+    assert_eq!(line1, ISS_LINE1);
+    assert_eq!(line2, ISS_LINE2);
+}
+
+#[test]
+fn rewrites_the_verification_catalog() {
+    let lines: Vec<&str> = include_str!("data/SGP4-VER.TLE")
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| &line[..line.len().min(69)])
+        .collect();
+    for pair in lines.chunks_exact(2) {
+        let elements =
+            sgp4::Elements::from_tle(None, pair[0].as_bytes(), pair[1].as_bytes()).unwrap();
+        let (line1, line2) = tle::format_elements(&elements).unwrap();
+        assert_eq!(line2, pair[1]);
+        // The catalog writes a zero drag term as both ` 00000+0` and
+        // ` 00000-0`, which changes the checksum; everything else matches.
+        assert_eq!(line1[..53], pair[0][..53]);
+        assert_eq!(line1[61..68], pair[0][61..68]);
+        let reread = sgp4::Elements::from_tle(None, line1.as_bytes(), line2.as_bytes()).unwrap();
+        assert_same_elements(&reread, &elements);
+    }
+}
+
+#[test]
+fn writes_alpha5_catalog_numbers() {
+    let mut elements = iss();
+    elements.norad_id = 105_544;
+    let (line1, line2) = tle::format_elements(&elements).unwrap();
+    assert!(line1.starts_with("1 A5544U "), "{line1}");
+    assert!(line2.starts_with("2 A5544 "), "{line2}");
+    let reread = Sgp4Propagator::from_tle(None, &line1, &line2).unwrap();
+    assert_eq!(reread.norad_id(), NoradId::new(105_544).unwrap());
+
+    elements.norad_id = 340_000;
+    let err = tle::format_elements(&elements).unwrap_err();
+    assert_eq!(
+        err,
+        TleFormatError::CatalogNumber(NoradIdError::NotAlpha5(NoradId::new(340_000).unwrap()))
+    );
+}
+
+#[test]
+fn writes_exponent_and_fraction_fields() {
+    let mut elements = iss();
+    elements.drag_term = 0.0;
+    elements.mean_motion_ddot = 1.2345e-10;
+    elements.mean_motion_dot = 0.0;
+    let (line1, _) = tle::format_elements(&elements).unwrap();
+    assert_eq!(&line1[33..43], " .00000000");
+    assert_eq!(&line1[44..52], " 12345-9");
+    assert_eq!(&line1[53..61], " 00000+0");
+
+    elements.drag_term = 0.999996;
+    let (line1, _) = tle::format_elements(&elements).unwrap();
+    assert_eq!(&line1[53..61], " 10000+1");
+
+    elements.mean_motion_dot = 1.5;
+    let err = tle::format_elements(&elements).unwrap_err();
+    assert!(matches!(
+        err,
+        TleFormatError::FieldOverflow {
+            field: "first derivative of mean motion",
+            ..
+        }
+    ));
+}
+
+#[test]
+fn rejects_designators_wider_than_their_columns() {
+    let mut elements = iss();
+    elements.international_designator = Some("UNKNOWN".to_string());
+    let (line1, _) = tle::format_elements(&elements).unwrap();
+    assert_eq!(&line1[9..17], "UNKNOWN ");
+
+    elements.international_designator = Some("ANALYST OBJECT".to_string());
+    let err = tle::format_elements(&elements).unwrap_err();
+    assert_eq!(
+        err,
+        TleFormatError::FieldOverflow {
+            field: "international designator",
+            value: "ANALYST OBJECT".to_string(),
+        }
+    );
+}
+
+#[test]
+fn rounds_the_epoch_to_the_tle_resolution() {
+    let mut elements = iss();
+    // 23:59:59.9999 rounds up into the next year.
+    elements.datetime = sgp4::chrono::NaiveDate::from_ymd_opt(2020, 12, 31)
+        .unwrap()
+        .and_hms_micro_opt(23, 59, 59, 999_900)
+        .unwrap();
+    let (line1, _) = tle::format_elements(&elements).unwrap();
+    assert_eq!(&line1[18..32], "21001.00000000");
+}
+
+#[test]
+fn omm_round_trips_in_every_encoding() {
+    let elements = iss();
+    for format in [OmmFormat::Xml, OmmFormat::Kvn, OmmFormat::Json] {
+        let text = omm::write([&elements, &elements], format);
+        assert_eq!(OmmFormat::detect(&text), Some(format));
+        let reread = omm::parse(&text, format).unwrap();
+        assert_eq!(reread.len(), 2, "{format:?}");
+        for copy in &reread {
+            assert_same_elements(copy, &elements);
+        }
+    }
+}
+
+#[test]
+fn tle_to_omm_to_tle_is_lossless() {
+    let propagator = Sgp4Propagator::from_tle(None, ISS_LINE1, ISS_LINE2).unwrap();
+    for format in [OmmFormat::Xml, OmmFormat::Kvn, OmmFormat::Json] {
+        let elements = omm::parse(&propagator.to_omm(format), format)
+            .unwrap()
+            .remove(0);
+        let (line1, line2) = tle::format_elements(&elements).unwrap();
+        assert_eq!((line1.as_str(), line2.as_str()), (ISS_LINE1, ISS_LINE2));
+    }
+}
+
+#[test]
+fn escapes_names_in_xml_and_json() {
+    let mut elements = iss();
+    elements.object_name = Some("R&D <\"TEST\">".to_string());
+    for format in [OmmFormat::Xml, OmmFormat::Json] {
+        let reread = omm::parse(&omm::write([&elements], format), format).unwrap();
+        assert_eq!(reread[0].object_name, elements.object_name, "{format:?}");
+    }
+}
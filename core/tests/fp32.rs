@@ -0,0 +1,117 @@
+use openastroviz_core::{fp32, ElementSet, ObjectStatus, PackedElements, Sgp4Propagator};
+
+/// Verification TLEs covering each branch of the kernel, as
+/// `(line 1, line 2, method, resonance)`.
+const REGIMES: [(&str, &str, u32, u32); 6] = [
+    (
+        "1 06251U 62025E   06176.82412014  .00008885  00000-0  12808-3 0  3985",
+        "2 06251  58.0579  54.0425 0030035 139.1568 221.1854 15.56387291  6774",
+        fp32::METHOD_NEAR_EARTH,
+        fp32::RESONANCE_NONE,
+    ),
+    (
+        "1 28350U 04020A   06167.21788666  .16154492  76267-5  18678-3 0  8894",
+        "2 28350  64.9977 345.6130 0024870 260.7578  99.9590 16.47856722116490",
+        fp32::METHOD_NEAR_EARTH_LOW_PERIGEE,
+        fp32::RESONANCE_NONE,
+    ),
+    (
+        "1 11801U          80230.29629788  .01431103  00000-0  14311-1 0    13",
+        "2 11801  46.7916 230.4354 7318036  47.4722  10.4117  2.28537848    13",
+        fp32::METHOD_DEEP_SPACE,
+        fp32::RESONANCE_NONE,
+    ),
+    (
+        "1 23599U 95029B   06171.76535463  .00085586  12891-6  12956-2 0  2905",
+        "2 23599   6.9327   0.2849 5782022 274.4436  25.2425  4.47796565123555",
+        fp32::METHOD_DEEP_SPACE,
+        fp32::RESONANCE_NONE,
+    ),
+    (
+        "1 28626U 05008A   06176.46683397 -.00000205  00000-0  10000-3 0  2190",
+        "2 28626   0.0019 286.9433 0000335  13.7918  55.6504  1.00270176  4891",
+        fp32::METHOD_DEEP_SPACE,
+        fp32::RESONANCE_ONE_DAY,
+    ),
+    (
+        "1 09880U 77021A   06176.56157475  .00000421  00000-0  10000-3 0  9814",
+        "2 09880  64.5968 349.3786 7069051 270.0229  16.3320  2.00813614112380",
+        fp32::METHOD_DEEP_SPACE,
+        fp32::RESONANCE_HALF_DAY,
+    ),
+];
+
+fn pack(line1: &str, line2: &str) -> (Sgp4Propagator, PackedElements) {
+    let propagator = Sgp4Propagator::from_tle(None, line1, line2).unwrap();
+    let packed = ElementSet::new(vec![propagator.clone()]).packed()[0];
+    (propagator, packed)
+}
+
+fn distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+    (0..3).map(|i| (a[i] - b[i]).powi(2)).sum::<f64>().sqrt()
+}
+
+#[test]
+fn classifies_each_regime_like_sgp4() {
+    for (line1, line2, method, resonance) in REGIMES {
+        let (_, packed) = pack(line1, line2);
+        assert_eq!(packed.status(), ObjectStatus::Ok);
+        assert_eq!(packed.method, method, "{line1}");
+        assert_eq!(packed.resonance, resonance, "{line1}");
+    }
+}
+
+#[test]
+fn tracks_the_f64_reference_for_one_day() {
+    for (line1, line2, _, _) in REGIMES {
+        let (propagator, packed) = pack(line1, line2);
+        for step in 0..=24 {
+            let minutes = step as f64 * 60.0;
+            let Ok(expected) = propagator.propagate_minutes(minutes) else {
+                break;
+            };
+            let actual = fp32::propagate(&packed, minutes as f32).unwrap();
+            let dr = distance(actual.position_km, expected.position_km);
+            let dv = distance(actual.velocity_km_s, expected.velocity_km_s);
+            assert!(dr < 1.0, "{line1} at {minutes} min: {dr} km");
+            assert!(dv < 1e-3, "{line1} at {minutes} min: {dv} km/s");
+        }
+    }
+}
+
+#[test]
+fn reports_the_same_failure_as_the_f64_reference() {
+    // Low-perigee object whose mean eccentricity goes negative as it decays.
+    let (line1, line2, _, _) = REGIMES[1];
+    let (propagator, packed) = pack(line1, line2);
+
+    let error = propagator.propagate_minutes(1500.0).unwrap_err();
+    assert_eq!(
+        fp32::propagate(&packed, 1500.0).unwrap_err(),
+        ObjectStatus::from(&error)
+    );
+}
+
+#[test]
+fn rejects_non_finite_times() {
+    let (line1, line2, _, _) = REGIMES[4];
+    let (_, packed) = pack(line1, line2);
+    for minutes in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+        assert_eq!(
+            fp32::propagate(&packed, minutes).unwrap_err(),
+            ObjectStatus::InvalidTime
+        );
+    }
+}
+
+#[test]
+fn initialization_failures_are_sticky() {
+    let packed = PackedElements {
+        status: ObjectStatus::MeanMotionNegative.code(),
+        ..PackedElements::default()
+    };
+    assert_eq!(
+        fp32::propagate(&packed, 0.0).unwrap_err(),
+        ObjectStatus::MeanMotionNegative
+    );
+}
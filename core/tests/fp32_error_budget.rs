@@ -0,0 +1,31 @@
+use openastroviz_core::error_budget::ErrorBudget;
+use openastroviz_core::Sgp4Propagator;
+
+const SEVEN_DAYS_MIN: f64 = 7.0 * 1440.0;
+
+/// Every object of the Vallado verification catalog.
+fn verification_catalog() -> Vec<Sgp4Propagator> {
+    let text = include_str!("data/SGP4-VER.TLE");
+    let lines: Vec<&str> = text.lines().filter(|line| !line.starts_with('#')).collect();
+    lines
+        .chunks_exact(2)
+        .map(|pair| {
+            // Columns past 69 hold the verification run's start, stop and step.
+            Sgp4Propagator::from_tle(None, pair[0], &pair[1][..69]).unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn fp32_error_budget_over_seven_days() {
+    let catalog = verification_catalog();
+    assert_eq!(catalog.len(), 33);
+
+    let budget = ErrorBudget::measure(&catalog, SEVEN_DAYS_MIN, 10.0);
+    // Printed so `cargo test -- --nocapture` doubles as the report.
+    println!("{budget}");
+
+    assert_eq!(budget.divergences().count(), 0, "{budget}");
+    assert!(budget.max_position_km() < 2.0, "{budget}");
+    assert!(budget.max_velocity_km_s() < 2e-3, "{budget}");
+}
@@ -0,0 +1,107 @@
+use approx::assert_relative_eq;
+use openastroviz_core::frames::{gmst_iau82, TemeState};
+use openastroviz_core::{EarthOrientation, Sgp4Propagator};
+
+/// Example from Vallado et al. (AIAA 2006-6753), section "TEME to ITRF".
+fn vallado_example() -> (TemeState, EarthOrientation) {
+    let epoch =
+        chrono::NaiveDateTime::parse_from_str("2004-04-06T07:51:28.386009", "%Y-%m-%dT%H:%M:%S%.f")
+            .unwrap();
+    let teme = TemeState::new(
+        [5094.18016210, 6127.64465950, 6380.34453270],
+        [-4.746131487, 0.785818041, 5.531931288],
+        epoch,
+    );
+    let eop = EarthOrientation {
+        dut1_s: -0.4399619,
+        lod_s: 0.0015563,
+        xp_arcsec: -0.140682,
+        yp_arcsec: 0.333309,
+        ..EarthOrientation::default()
+    };
+    (teme, eop)
+}
+
+#[test]
+fn teme_to_itrf_matches_vallado_example() {
+    let (teme, eop) = vallado_example();
+    let itrf = teme.to_itrf(&eop);
+
+    let expected_r = [-1033.4793830, 7901.2952754, 6380.3565958];
+    let expected_v = [-3.225636520, -2.872451450, 5.531924446];
+    for i in 0..3 {
+        assert_relative_eq!(itrf.position_km[i], expected_r[i], epsilon = 1e-4);
+        assert_relative_eq!(itrf.velocity_km_s[i], expected_v[i], epsilon = 1e-7);
+    }
+    assert_eq!(itrf.frame_name(), "ITRF");
+    assert_eq!(itrf.epoch, teme.epoch);
+}
+
+#[test]
+fn teme_to_gcrf_matches_vallado_example() {
+    let (teme, eop) = vallado_example();
+    let gcrf = teme.to_gcrf(&eop);
+
+    let expected_r = [5102.5089579, 6123.0114007, 6378.1369282];
+    let expected_v = [-4.743220157, 0.790536497, 5.533755727];
+    for i in 0..3 {
+        assert_relative_eq!(gcrf.position_km[i], expected_r[i], epsilon = 2e-3);
+        assert_relative_eq!(gcrf.velocity_km_s[i], expected_v[i], epsilon = 2e-6);
+    }
+}
+
+#[test]
+fn frame_round_trips_are_lossless() {
+    let (teme, eop) = vallado_example();
+
+    let via_itrf = teme.to_itrf(&eop).to_teme(&eop);
+    let via_gcrf = teme.to_gcrf(&eop).to_teme(&eop);
+    let gcrf_itrf = teme.to_gcrf(&eop).to_itrf(&eop);
+    let direct_itrf = teme.to_itrf(&eop);
+
+    for i in 0..3 {
+        assert_relative_eq!(via_itrf.position_km[i], teme.position_km[i], epsilon = 1e-8);
+        assert_relative_eq!(
+            via_itrf.velocity_km_s[i],
+            teme.velocity_km_s[i],
+            epsilon = 1e-11
+        );
+        assert_relative_eq!(via_gcrf.position_km[i], teme.position_km[i], epsilon = 1e-8);
+        assert_relative_eq!(
+            via_gcrf.velocity_km_s[i],
+            teme.velocity_km_s[i],
+            epsilon = 1e-11
+        );
+        assert_relative_eq!(
+            gcrf_itrf.position_km[i],
+            direct_itrf.position_km[i],
+            epsilon = 1e-8
+        );
+    }
+}
+
+#[test]
+fn gmst_matches_vallado_example_3_5() {
+    // Vallado, Fundamentals of Astrodynamics, Example 3-5: 1992-08-20 12:14 UT1.
+    let jd_ut1 = 2_448_855.009_722_222;
+    let gmst_deg = gmst_iau82(jd_ut1).to_degrees();
+    assert_relative_eq!(gmst_deg, 152.578_787_810, epsilon = 1e-6);
+}
+
+#[test]
+fn propagated_state_converts_to_earth_fixed_frame() {
+    let line1 = "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753";
+    let line2 = "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
+    let propagator = Sgp4Propagator::from_tle(None, line1, line2).unwrap();
+    let epoch = propagator.epoch();
+    let teme = propagator.propagate_minutes(0.0).unwrap().into_teme(epoch);
+    let itrf = teme.to_itrf(&EarthOrientation::default());
+
+    let radius = |r: [f64; 3]| (r[0] * r[0] + r[1] * r[1] + r[2] * r[2]).sqrt();
+    assert_relative_eq!(
+        radius(itrf.position_km),
+        radius(teme.position_km),
+        epsilon = 1e-8
+    );
+    assert_relative_eq!(itrf.position_km[2], teme.position_km[2], epsilon = 1e-8);
+}
@@ -0,0 +1,143 @@
+use approx::assert_relative_eq;
+use chrono::Duration;
+use openastroviz_core::ground_track::{ground_track, GroundTrackError};
+use openastroviz_core::{EarthOrientation, Geodetic, Sgp4Propagator};
+
+const ISS_LINE1: &str = "1 25544U 98067A   20194.88612269 -.00002218  00000-0 -31515-4 0  9992";
+const ISS_LINE2: &str = "2 25544  51.6461 221.2784 0001413  89.1723 280.4612 15.49507896236008";
+
+fn iss() -> Sgp4Propagator {
+    Sgp4Propagator::from_tle(Some("ISS (ZARYA)".to_owned()), ISS_LINE1, ISS_LINE2).unwrap()
+}
+
+#[test]
+fn geodetic_matches_vallado_example_3_3() {
+    let geodetic = Geodetic::from_ecef([6524.834, 6862.875, 6448.296]);
+    assert_relative_eq!(geodetic.latitude_deg, 34.352496, epsilon = 1e-5);
+    assert_relative_eq!(geodetic.longitude_deg, 46.4464, epsilon = 1e-4);
+    assert_relative_eq!(geodetic.height_km, 5085.22, epsilon = 1e-2);
+}
+
+#[test]
+fn geodetic_round_trips_through_ecef() {
+    let cases = [
+        (0.0, 0.0, 0.0),
+        (51.6, -122.3, 420.0),
+        (-33.9, 151.2, 0.05),
+        (89.9, 10.0, 35_786.0),
+        (-89.9, -179.9, 800.0),
+    ];
+    for (latitude_deg, longitude_deg, height_km) in cases {
+        let point = Geodetic {
+            latitude_deg,
+            longitude_deg,
+            height_km,
+        };
+        let back = Geodetic::from_ecef(point.to_ecef());
+        assert_relative_eq!(back.latitude_deg, latitude_deg, epsilon = 1e-9);
+        assert_relative_eq!(back.longitude_deg, longitude_deg, epsilon = 1e-9);
+        assert_relative_eq!(back.height_km, height_km, epsilon = 1e-6);
+    }
+}
+
+#[test]
+fn geodetic_handles_poles() {
+    let north = Geodetic::from_ecef([0.0, 0.0, 6356.752314245 + 100.0]);
+    assert_eq!(north.latitude_deg, 90.0);
+    assert_relative_eq!(north.height_km, 100.0, epsilon = 1e-6);
+}
+
+#[test]
+fn ground_track_splits_at_antimeridian() {
+    let propagator = iss();
+    let start = propagator.epoch();
+    let end = start + Duration::hours(6);
+    let track = ground_track(
+        &propagator,
+        start,
+        end,
+        Duration::seconds(60),
+        &EarthOrientation::default(),
+    )
+    .expect("ground track should propagate");
+
+    // Four orbits cross the antimeridian at least three times.
+    assert!(
+        track.segments.len() >= 4,
+        "{} segments",
+        track.segments.len()
+    );
+
+    for segment in &track.segments {
+        for pair in segment.windows(2) {
+            let jump = (pair[1].geodetic.longitude_deg - pair[0].geodetic.longitude_deg).abs();
+            assert!(jump < 180.0, "segment jumps {jump} degrees");
+        }
+        for point in segment {
+            assert!((-180.0..=180.0).contains(&point.geodetic.longitude_deg));
+            assert!(point.geodetic.latitude_deg.abs() <= 51.7 + 0.3);
+            assert!((350.0..450.0).contains(&point.geodetic.height_km));
+        }
+    }
+
+    for pair in track.segments.windows(2) {
+        let leaving = pair[0].last().unwrap();
+        let entering = pair[1].first().unwrap();
+        assert_eq!(leaving.time, entering.time);
+        assert_eq!(
+            leaving.geodetic.latitude_deg,
+            entering.geodetic.latitude_deg
+        );
+        assert_eq!(leaving.geodetic.longitude_deg.abs(), 180.0);
+        assert_eq!(
+            leaving.geodetic.longitude_deg,
+            -entering.geodetic.longitude_deg
+        );
+    }
+
+    let first = track.points().next().unwrap();
+    let last = track.points().last().unwrap();
+    assert_eq!(first.time, start);
+    assert_eq!(last.time, end);
+}
+
+#[test]
+fn ground_track_includes_window_end() {
+    let propagator = iss();
+    let start = propagator.epoch();
+    let end = start + Duration::seconds(150);
+    let track = ground_track(
+        &propagator,
+        start,
+        end,
+        Duration::seconds(60),
+        &EarthOrientation::default(),
+    )
+    .unwrap();
+
+    let times: Vec<_> = track
+        .points()
+        .map(|p| (p.time - start).num_seconds())
+        .collect();
+    assert_eq!(times, vec![0, 60, 120, 150]);
+}
+
+#[test]
+fn ground_track_rejects_bad_parameters() {
+    let propagator = iss();
+    let start = propagator.epoch();
+    let eop = EarthOrientation::default();
+
+    let err = ground_track(&propagator, start, start, Duration::zero(), &eop).unwrap_err();
+    assert!(matches!(err, GroundTrackError::InvalidStep(_)));
+
+    let err = ground_track(
+        &propagator,
+        start,
+        start - Duration::minutes(1),
+        Duration::seconds(10),
+        &eop,
+    )
+    .unwrap_err();
+    assert!(matches!(err, GroundTrackError::InvalidWindow { .. }));
+}
@@ -0,0 +1,92 @@
+use openastroviz_core::omm::{self, OmmError};
+use openastroviz_core::{NoradId, NoradIdError, Sgp4Propagator};
+
+fn id(value: u64) -> NoradId {
+    NoradId::new(value).unwrap()
+}
+
+#[test]
+fn decodes_alpha5() {
+    assert_eq!(NoradId::from_alpha5("25544"), Ok(id(25544)));
+    assert_eq!(NoradId::from_alpha5("    5"), Ok(id(5)));
+    assert_eq!(NoradId::from_alpha5("A0000"), Ok(id(100_000)));
+    assert_eq!(NoradId::from_alpha5("A2345"), Ok(id(102_345)));
+    // I and O are skipped so they cannot be mistaken for 1 and 0.
+    assert_eq!(NoradId::from_alpha5("J0000"), Ok(id(180_000)));
+    assert_eq!(NoradId::from_alpha5("P0000"), Ok(id(230_000)));
+    assert_eq!(NoradId::from_alpha5("T2345"), Ok(id(272_345)));
+    assert_eq!(NoradId::from_alpha5("Z9999"), Ok(NoradId::MAX_ALPHA5));
+}
+
+#[test]
+fn rejects_malformed_alpha5() {
+    for field in [
+        "", "     ", "I0000", "O1234", "a2345", "A234", "1A345", "A23456", "-1234",
+    ] {
+        assert_eq!(
+            NoradId::from_alpha5(field),
+            Err(NoradIdError::Invalid(field.to_string())),
+            "{field:?}"
+        );
+    }
+}
+
+#[test]
+fn encodes_alpha5() {
+    assert_eq!(id(5).to_alpha5().unwrap(), "00005");
+    assert_eq!(id(99_999).to_alpha5().unwrap(), "99999");
+    assert_eq!(id(105_544).to_alpha5().unwrap(), "A5544");
+    assert_eq!(id(339_999).to_alpha5().unwrap(), "Z9999");
+    assert_eq!(
+        id(340_000).to_alpha5(),
+        Err(NoradIdError::NotAlpha5(id(340_000)))
+    );
+}
+
+#[test]
+fn alpha5_round_trips() {
+    for value in (0..=339_999).step_by(997).chain([99_999, 100_000, 339_999]) {
+        let encoded = id(value).to_alpha5().unwrap();
+        assert_eq!(NoradId::from_alpha5(&encoded), Ok(id(value)), "{encoded}");
+    }
+}
+
+#[test]
+fn holds_nine_digits() {
+    assert_eq!(NoradId::MAX.get(), 999_999_999);
+    assert_eq!(
+        NoradId::new(1_000_000_000),
+        Err(NoradIdError::OutOfRange(1_000_000_000))
+    );
+    assert_eq!("270544".parse(), Ok(id(270_544)));
+    assert_eq!("A2345".parse(), Ok(id(102_345)));
+    assert_eq!(" 999999999 ".parse(), Ok(NoradId::MAX));
+    assert!("1000000000".parse::<NoradId>().is_err());
+    assert!("25544X".parse::<NoradId>().is_err());
+    assert_eq!(format!("{:>7}", id(5)), "      5");
+}
+
+#[test]
+fn propagator_reads_alpha5_tles() {
+    let propagator = Sgp4Propagator::from_tle(
+        None,
+        "1 A5544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2925",
+        "2 A5544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563535",
+    )
+    .unwrap();
+    assert_eq!(propagator.norad_id(), id(105_544));
+    assert_eq!(propagator.norad_id().to_alpha5().unwrap(), "A5544");
+}
+
+#[test]
+fn omm_rejects_catalog_numbers_beyond_nine_digits() {
+    let json = include_str!("data/ISS.omm.json").replace("25544", "1000025544");
+    let err = omm::parse_json(&json).unwrap_err();
+    assert!(matches!(
+        err,
+        OmmError::InvalidField {
+            field: "NORAD_CAT_ID",
+            ..
+        }
+    ));
+}
@@ -0,0 +1,113 @@
+use approx::assert_relative_eq;
+use chrono::Duration;
+use openastroviz_core::frames::ItrfState;
+use openastroviz_core::observer::Observer;
+use openastroviz_core::{EarthOrientation, Geodetic, Sgp4Propagator};
+
+const ISS_LINE1: &str = "1 25544U 98067A   20194.88612269 -.00002218  00000-0 -31515-4 0  9992";
+const ISS_LINE2: &str = "2 25544  51.6461 221.2784 0001413  89.1723 280.4612 15.49507896236008";
+
+fn site() -> Geodetic {
+    Geodetic {
+        latitude_deg: 39.007,
+        longitude_deg: -104.883,
+        height_km: 2.19456,
+    }
+}
+
+#[test]
+fn satellite_at_zenith_has_ninety_degree_elevation() {
+    let observer = Observer::new(site());
+    let above = Geodetic {
+        height_km: site().height_km + 500.0,
+        ..site()
+    };
+    let epoch = chrono::NaiveDate::from_ymd_opt(2024, 1, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    let state = ItrfState::new(above.to_ecef(), [0.0, 0.0, 1.0], epoch);
+
+    let look = observer.look_angles_to(&state);
+    assert_relative_eq!(look.elevation_deg, 90.0, epsilon = 1e-6);
+    assert_relative_eq!(look.range_km, 500.0, epsilon = 1e-6);
+}
+
+#[test]
+fn azimuth_follows_compass_convention() {
+    let observer = Observer::new(Geodetic {
+        latitude_deg: 0.0,
+        longitude_deg: 0.0,
+        height_km: 0.0,
+    });
+    let epoch = chrono::NaiveDate::from_ymd_opt(2024, 1, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    let origin = observer.location.to_ecef();
+
+    let north = ItrfState::new([origin[0], 0.0, 1000.0], [0.0; 3], epoch);
+    let east = ItrfState::new([origin[0], 1000.0, 0.0], [0.0; 3], epoch);
+    let west = ItrfState::new([origin[0], -1000.0, 0.0], [0.0; 3], epoch);
+
+    assert_relative_eq!(
+        observer.look_angles_to(&north).azimuth_deg,
+        0.0,
+        epsilon = 1e-9
+    );
+    assert_relative_eq!(
+        observer.look_angles_to(&east).azimuth_deg,
+        90.0,
+        epsilon = 1e-9
+    );
+    assert_relative_eq!(
+        observer.look_angles_to(&west).azimuth_deg,
+        270.0,
+        epsilon = 1e-9
+    );
+    assert_relative_eq!(
+        observer.look_angles_to(&east).elevation_deg,
+        0.0,
+        epsilon = 1e-9
+    );
+}
+
+#[test]
+fn range_rate_matches_finite_difference() {
+    let propagator = Sgp4Propagator::from_tle(None, ISS_LINE1, ISS_LINE2).unwrap();
+    let observer = Observer::new(site());
+    let eop = EarthOrientation::default();
+    let t = propagator.epoch() + Duration::minutes(30);
+    let h = Duration::milliseconds(500);
+
+    let look = observer.look_angles(&propagator, t, &eop).unwrap();
+    let before = observer.look_angles(&propagator, t - h, &eop).unwrap();
+    let after = observer.look_angles(&propagator, t + h, &eop).unwrap();
+
+    let numeric = (after.range_km - before.range_km) / 1.0;
+    assert_relative_eq!(look.range_rate_km_s, numeric, epsilon = 1e-4);
+    assert!((0.0..360.0).contains(&look.azimuth_deg));
+    assert!((-90.0..=90.0).contains(&look.elevation_deg));
+}
+
+#[test]
+fn elevation_mask_controls_visibility() {
+    let observer = Observer::new(site()).with_elevation_mask(10.0);
+    let epoch = chrono::NaiveDate::from_ymd_opt(2024, 1, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    let above = Geodetic {
+        height_km: site().height_km + 500.0,
+        ..site()
+    };
+    let look = observer.look_angles_to(&ItrfState::new(above.to_ecef(), [0.0; 3], epoch));
+    assert!(observer.is_visible(&look));
+
+    let low = openastroviz_core::observer::LookAngles {
+        elevation_deg: 5.0,
+        ..look
+    };
+    assert!(!observer.is_visible(&low));
+    assert!(Observer::new(site()).is_visible(&low));
+}
@@ -0,0 +1,160 @@
+use openastroviz_core::omm::{self, OmmError, OmmFormat};
+use openastroviz_core::Sgp4Propagator;
+
+const ISS_LINE1: &str = "1 25544U 98067A   20194.88612269 -.00002218  00000-0 -31515-4 0  9992";
+const ISS_LINE2: &str = "2 25544  51.6461 221.2784 0001413  89.1723 280.4612 15.49507896236008";
+
+/// The same ISS element set as `ISS_LINE1`/`ISS_LINE2` in each encoding.
+const ISS_OMM: [(&str, OmmFormat); 3] = [
+    (include_str!("data/ISS.omm.xml"), OmmFormat::Xml),
+    (include_str!("data/ISS.omm.kvn"), OmmFormat::Kvn),
+    (include_str!("data/ISS.omm.json"), OmmFormat::Json),
+];
+
+fn single(text: &str, format: OmmFormat) -> sgp4::Elements {
+    let mut elements = omm::parse(text, format).unwrap();
+    assert_eq!(elements.len(), 1, "{format:?}");
+    elements.remove(0)
+}
+
+#[test]
+fn detects_each_encoding() {
+    for (text, format) in ISS_OMM {
+        assert_eq!(OmmFormat::detect(text), Some(format));
+    }
+    assert_eq!(OmmFormat::detect(ISS_LINE1), None);
+    assert_eq!(
+        OmmFormat::detect("<omm id=\"CCSDS_OMM_VERS\" version=\"2.0\">"),
+        Some(OmmFormat::Xml)
+    );
+    // HTML error and captive-portal pages are not OMM.
+    for page in ["<!DOCTYPE html>\n<html>", "<html>error</html>", "<ommx/>"] {
+        assert_eq!(OmmFormat::detect(page), None, "{page}");
+    }
+}
+
+#[test]
+fn every_encoding_matches_the_tle() {
+    let tle = Sgp4Propagator::from_tle(None, ISS_LINE1, ISS_LINE2).unwrap();
+    for (text, format) in ISS_OMM {
+        let elements = single(text, format);
+        assert_eq!(elements.object_name.as_deref(), Some("ISS (ZARYA)"));
+        assert_eq!(
+            elements.international_designator.as_deref(),
+            Some("1998-067A")
+        );
+        assert_eq!(elements.norad_id, 25544);
+        assert_eq!(elements.element_set_number, 999);
+        assert_eq!(elements.revolution_number, 23600);
+        let epoch_offset = (elements.datetime - tle.epoch())
+            .num_microseconds()
+            .unwrap();
+        assert!(epoch_offset.abs() <= 1, "{format:?}: {epoch_offset} us");
+
+        let propagator = Sgp4Propagator::from_elements(elements).unwrap();
+        for minutes in [0.0, 360.0, 1440.0] {
+            let expected = tle.propagate_minutes(minutes).unwrap();
+            // The TLE epoch is a day fraction, so the two agree to the
+            // microsecond rather than exactly.
+            let actual = propagator
+                .propagate_minutes(minutes - epoch_offset as f64 / 60e6)
+                .unwrap();
+            for axis in 0..3 {
+                let dr = (actual.position_km[axis] - expected.position_km[axis]).abs();
+                assert!(dr < 1e-6, "{format:?} at {minutes} min: {dr} km");
+            }
+        }
+    }
+}
+
+#[test]
+fn reads_catalog_numbers_beyond_five_digits() {
+    let json = include_str!("data/ISS.omm.json").replace("25544", "270544");
+    let elements = single(&json, OmmFormat::Json);
+    assert_eq!(elements.norad_id, 270544);
+    assert_eq!(
+        Sgp4Propagator::from_elements(elements)
+            .unwrap()
+            .norad_id()
+            .get(),
+        270544
+    );
+}
+
+#[test]
+fn reads_numbers_quoted_as_strings() {
+    // Space-Track serves every value as a string.
+    let json = r#"{"OBJECT_NAME": "ISS (ZARYA)", "NORAD_CAT_ID": "25544",
+        "EPOCH": "2020-07-12T21:16:01.000416", "MEAN_MOTION": "15.49507896",
+        "ECCENTRICITY": "0.0001413", "INCLINATION": "51.6461",
+        "RA_OF_ASC_NODE": "221.2784", "ARG_OF_PERICENTER": "89.1723",
+        "MEAN_ANOMALY": "280.4612", "BSTAR": "-0.000031515"}"#;
+    let elements = single(json, OmmFormat::Json);
+    assert_eq!(elements.norad_id, 25544);
+    assert_eq!(elements.mean_motion, 15.49507896);
+    assert_eq!(elements.drag_term, -3.1515e-5);
+}
+
+#[test]
+fn strips_units_only_from_numbers() {
+    let kvn = include_str!("data/ISS.omm.kvn")
+        .replace("ISS (ZARYA)", "ISS [ZARYA] [MAIN]")
+        .replace("ECCENTRICITY = .0001413", "ECCENTRICITY = .0001413 [n/a]");
+    let elements = single(&kvn, OmmFormat::Kvn);
+    assert_eq!(elements.object_name.as_deref(), Some("ISS [ZARYA] [MAIN]"));
+    assert_eq!(elements.eccentricity, 0.0001413);
+    assert_eq!(elements.mean_motion, 15.49507896);
+}
+
+#[test]
+fn reads_several_messages() {
+    let kvn = include_str!("data/ISS.omm.kvn");
+    let two = format!("{kvn}\n{}", kvn.replace("25544", "25545"));
+    let ids: Vec<u64> = omm::parse_kvn(&two)
+        .unwrap()
+        .iter()
+        .map(|elements| elements.norad_id)
+        .collect();
+    assert_eq!(ids, [25544, 25545]);
+
+    let xml = include_str!("data/ISS.omm.xml");
+    let body = &xml[xml.find("<omm").unwrap()..xml.find("</ndm>").unwrap()];
+    let ndm = format!("<ndm>{body}{}</ndm>", body.replace("25544", "25545"));
+    assert_eq!(omm::parse_xml(&ndm).unwrap().len(), 2);
+}
+
+#[test]
+fn reports_missing_and_invalid_fields() {
+    let json = include_str!("data/ISS.omm.json");
+    let err = omm::parse_json(&json.replace("\"BSTAR\"", "\"B_STAR\"")).unwrap_err();
+    assert!(matches!(err, OmmError::MissingField("BSTAR")));
+
+    let err = omm::parse_json(&json.replace("2020-07-12T", "12/07/2020 ")).unwrap_err();
+    assert!(matches!(err, OmmError::InvalidField { field: "EPOCH", .. }));
+
+    let err = omm::parse_kvn("CCSDS_OMM_VERS = 2.0\nnot a keyword\n").unwrap_err();
+    assert!(matches!(err, OmmError::KvnSyntax { line: 2 }));
+
+    let kvn = include_str!("data/ISS.omm.kvn").replace("THEORY = SGP4", "THEORY = DSST");
+    let err = omm::parse_kvn(&kvn).unwrap_err();
+    assert!(matches!(err, OmmError::UnsupportedTheory(theory) if theory == "DSST"));
+}
+
+#[test]
+fn keeps_each_message_outcome_apart() {
+    let kvn = include_str!("data/ISS.omm.kvn");
+    let broken = kvn.replace("25544", "25545").replace("BSTAR =", "BSTAR");
+    let text = format!("{kvn}\n{broken}\n{}", kvn.replace("25544", "25546"));
+    let outcomes = omm::parse_each(&text, OmmFormat::Kvn).unwrap();
+    assert_eq!(outcomes.len(), 3);
+    assert_eq!(outcomes[0].as_ref().unwrap().norad_id, 25544);
+    assert!(matches!(outcomes[1], Err(OmmError::KvnSyntax { .. })));
+    assert_eq!(outcomes[2].as_ref().unwrap().norad_id, 25546);
+    assert!(omm::parse_kvn(&text).is_err());
+
+    // A document that cannot be read at all still fails outright.
+    assert!(matches!(
+        omm::parse_each("<omm>", OmmFormat::Xml),
+        Err(OmmError::Xml(_))
+    ));
+}
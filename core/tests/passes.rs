@@ -0,0 +1,187 @@
+use approx::assert_relative_eq;
+use chrono::{Duration, NaiveDateTime};
+use openastroviz_core::observer::Observer;
+use openastroviz_core::passes::{PassPredictor, PassVisibility};
+use openastroviz_core::sun::{is_sunlit, sun_position_km};
+use openastroviz_core::{EarthOrientation, Geodetic, Sgp4Propagator};
+
+const ISS_LINE1: &str = "1 25544U 98067A   20194.88612269 -.00002218  00000-0 -31515-4 0  9992";
+const ISS_LINE2: &str = "2 25544  51.6461 221.2784 0001413  89.1723 280.4612 15.49507896236008";
+
+fn iss() -> Sgp4Propagator {
+    Sgp4Propagator::from_tle(Some("ISS (ZARYA)".to_owned()), ISS_LINE1, ISS_LINE2).unwrap()
+}
+
+fn boulder() -> Observer {
+    Observer::new(Geodetic {
+        latitude_deg: 40.015,
+        longitude_deg: -105.27,
+        height_km: 1.655,
+    })
+}
+
+/// Above-mask intervals found by brute-force one-second sampling.
+fn brute_force_windows(
+    propagator: &Sgp4Propagator,
+    observer: &Observer,
+    start: NaiveDateTime,
+    seconds: i64,
+) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+    let eop = EarthOrientation::default();
+    let mut windows = Vec::new();
+    let mut rise = None;
+    for s in 0..=seconds {
+        let t = start + Duration::seconds(s);
+        let up = observer.is_visible(&observer.look_angles(propagator, t, &eop).unwrap());
+        match (up, rise) {
+            (true, None) => rise = Some(t),
+            (false, Some(r)) => {
+                windows.push((r, t));
+                rise = None;
+            }
+            _ => {}
+        }
+    }
+    windows
+}
+
+#[test]
+fn passes_match_brute_force_sampling() {
+    let propagator = iss();
+    let observer = boulder();
+    let start = propagator.epoch();
+    let end = start + Duration::hours(24);
+
+    let passes = PassPredictor::new(&propagator, observer)
+        .passes(start, end)
+        .unwrap();
+    let expected = brute_force_windows(&propagator, &observer, start, 24 * 3600);
+
+    assert!(!passes.is_empty());
+    assert_eq!(passes.len(), expected.len());
+    for (pass, (rise, set)) in passes.iter().zip(expected) {
+        assert!((pass.aos.time - rise).num_milliseconds().abs() <= 1_000);
+        assert!((pass.los.time - set).num_milliseconds().abs() <= 1_000);
+    }
+}
+
+#[test]
+fn pass_events_are_refined() {
+    let propagator = iss();
+    let observer = boulder();
+    let start = propagator.epoch();
+    let passes = PassPredictor::new(&propagator, observer)
+        .passes(start, start + Duration::hours(24))
+        .unwrap();
+
+    for pass in &passes {
+        assert!(pass.aos.time < pass.culmination.time);
+        assert!(pass.culmination.time < pass.los.time);
+        assert_relative_eq!(pass.aos.look.elevation_deg, 0.0, epsilon = 1e-3);
+        assert_relative_eq!(pass.los.look.elevation_deg, 0.0, epsilon = 1e-3);
+        assert!(pass.aos.look.range_rate_km_s < 0.0);
+        assert!(pass.los.look.range_rate_km_s > 0.0);
+
+        // No sample within the pass rises above the refined culmination.
+        let mut t = pass.aos.time;
+        while t < pass.los.time {
+            let look = observer
+                .look_angles(&propagator, t, &EarthOrientation::default())
+                .unwrap();
+            assert!(look.elevation_deg <= pass.max_elevation_deg() + 1e-6);
+            t += Duration::seconds(5);
+        }
+    }
+}
+
+#[test]
+fn elevation_mask_shortens_and_filters_passes() {
+    let propagator = iss();
+    let start = propagator.epoch();
+    let end = start + Duration::hours(24);
+
+    let horizon = PassPredictor::new(&propagator, boulder())
+        .passes(start, end)
+        .unwrap();
+    let masked = PassPredictor::new(&propagator, boulder().with_elevation_mask(20.0))
+        .passes(start, end)
+        .unwrap();
+
+    assert!(masked.len() <= horizon.len());
+    for pass in &masked {
+        assert!(pass.max_elevation_deg() >= 20.0);
+        assert_relative_eq!(pass.aos.look.elevation_deg, 20.0, epsilon = 1e-3);
+    }
+    let total = |passes: &[openastroviz_core::passes::Pass]| {
+        passes.iter().map(|p| p.duration()).sum::<Duration>()
+    };
+    assert!(total(&masked) < total(&horizon));
+}
+
+#[test]
+fn pass_in_progress_is_clipped_to_window() {
+    let propagator = iss();
+    let observer = boulder();
+    let start = propagator.epoch();
+    let passes = PassPredictor::new(&propagator, observer)
+        .passes(start, start + Duration::hours(24))
+        .unwrap();
+    let first = passes[0];
+
+    let clipped = PassPredictor::new(&propagator, observer)
+        .passes(
+            first.culmination.time,
+            first.los.time + Duration::minutes(1),
+        )
+        .unwrap();
+    assert_eq!(clipped.len(), 1);
+    assert_eq!(clipped[0].aos.time, first.culmination.time);
+    assert!(
+        (clipped[0].los.time - first.los.time)
+            .num_milliseconds()
+            .abs()
+            <= 10
+    );
+}
+
+#[test]
+fn visibility_depends_on_observer_darkness() {
+    let propagator = iss();
+    let start = propagator.epoch();
+    let passes = PassPredictor::new(&propagator, boulder())
+        .passes(start, start + Duration::hours(48))
+        .unwrap();
+
+    // Daylight in Boulder for every pass that culminates around local noon.
+    for pass in &passes {
+        let local_hour = (pass.culmination.time - Duration::hours(7)).time();
+        if (chrono::NaiveTime::from_hms_opt(10, 0, 0).unwrap()
+            ..chrono::NaiveTime::from_hms_opt(15, 0, 0).unwrap())
+            .contains(&local_hour)
+        {
+            assert_eq!(pass.visibility, PassVisibility::Daylight);
+        }
+    }
+    assert!(passes
+        .iter()
+        .any(|p| p.visibility != PassVisibility::Daylight));
+}
+
+#[test]
+fn sun_declination_at_june_solstice() {
+    let solstice =
+        NaiveDateTime::parse_from_str("2024-06-20T20:51:00", "%Y-%m-%dT%H:%M:%S").unwrap();
+    let sun = sun_position_km(solstice);
+    let distance = (sun[0] * sun[0] + sun[1] * sun[1] + sun[2] * sun[2]).sqrt();
+    let declination = (sun[2] / distance).asin().to_degrees();
+    assert_relative_eq!(declination, 23.44, epsilon = 0.05);
+    assert_relative_eq!(distance / 149_597_870.7, 1.016, epsilon = 0.002);
+}
+
+#[test]
+fn earth_shadow_blocks_sunlight() {
+    let sun = [149_597_870.7, 0.0, 0.0];
+    assert!(is_sunlit([7000.0, 0.0, 0.0], sun));
+    assert!(!is_sunlit([-7000.0, 0.0, 0.0], sun));
+    assert!(is_sunlit([-7000.0, 0.0, 7000.0], sun));
+}
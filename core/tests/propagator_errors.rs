@@ -1,4 +1,7 @@
-use openastroviz_core::{PropagationError, Sgp4Propagator};
+use openastroviz_core::{
+    FailureKind, NoradId, NoradIdError, ObjectStatus, PropagationError, PropagationFailure,
+    Sgp4Propagator,
+};
 
 const LINE1: &str = "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753";
 const LINE2: &str = "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
@@ -8,6 +11,13 @@ fn rejects_invalid_tle() {
     let err = Sgp4Propagator::from_tle(Some("bad".to_string()), "invalid", LINE2)
         .expect_err("invalid TLE should fail");
     assert!(matches!(err, PropagationError::Tle(_)));
+    assert_eq!(ObjectStatus::from(&err), ObjectStatus::InvalidElements);
+}
+
+#[test]
+fn bad_catalog_numbers_are_invalid_elements() {
+    let err = PropagationError::from(NoradIdError::OutOfRange(1_000_000_000));
+    assert_eq!(ObjectStatus::from(&err), ObjectStatus::InvalidElements);
 }
 
 #[test]
@@ -15,10 +25,82 @@ fn rejects_non_finite_minutes() {
     let propagator =
         Sgp4Propagator::from_tle(Some("Vanguard 1".to_string()), LINE1, LINE2).unwrap();
 
-    let err = propagator
-        .propagate_minutes(f64::NAN)
-        .expect_err("non-finite propagation input should fail");
-    assert!(matches!(err, PropagationError::Propagation(_)));
+    for minutes in [f64::NAN, f64::INFINITY] {
+        let err = propagator
+            .propagate_minutes(minutes)
+            .expect_err("non-finite propagation input should fail");
+        assert!(matches!(
+            err,
+            PropagationError::Propagation(PropagationFailure {
+                norad_id,
+                kind: FailureKind::NonFiniteInput,
+                ..
+            }) if norad_id.get() == 5
+        ));
+    }
+}
+
+#[test]
+fn reports_decay_with_object_and_time() {
+    // Sub-orbital verification object that re-enters within an hour.
+    let propagator = Sgp4Propagator::from_tle(
+        None,
+        "1 28872U 05037B   05333.02012661  .25992681  00000-0  24476-3 0  1534",
+        "2 28872  96.4736 157.9986 0303955 244.0492 110.6523 16.46015938 10708",
+    )
+    .unwrap();
+    assert!(propagator.propagate_minutes(50.0).is_ok());
+
+    let err = propagator.propagate_minutes(60.0).unwrap_err();
+    let PropagationError::Propagation(failure) = err else {
+        panic!("expected a propagation failure, got {err:?}");
+    };
+    assert_eq!(
+        failure,
+        PropagationFailure {
+            norad_id: NoradId::new(28872).unwrap(),
+            minutes_since_epoch: 60.0,
+            kind: FailureKind::Decayed,
+        }
+    );
+    assert_eq!(
+        failure.to_string(),
+        "orbit has decayed for object 28872 at 60 min since epoch"
+    );
+}
+
+#[test]
+fn classifies_runtime_traps() {
+    // Low-perigee object whose drag drives the mean eccentricity negative.
+    let propagator = Sgp4Propagator::from_tle(
+        None,
+        "1 28350U 04020A   06167.21788666  .16154492  76267-5  18678-3 0  8894",
+        "2 28350  64.9977 345.6130 0024870 260.7578  99.9590 16.47856722116490",
+    )
+    .unwrap();
+    let err = propagator.propagate_minutes(1500.0).unwrap_err();
+    assert!(matches!(
+        err,
+        PropagationError::Propagation(PropagationFailure {
+            kind: FailureKind::EccentricityOutOfRange,
+            minutes_since_epoch: 1500.0,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn reports_invalid_epoch_elements_at_minute_zero() {
+    let line2 = "2 00005  34.2682 348.7242 1859667 331.7664  19.3264  0.00000000413669";
+    let err = Sgp4Propagator::from_tle(None, LINE1, line2).expect_err("zero mean motion");
+    assert!(matches!(
+        err,
+        PropagationError::Propagation(PropagationFailure {
+            norad_id,
+            minutes_since_epoch: 0.0,
+            kind: FailureKind::MeanMotionNegative,
+        }) if norad_id.get() == 5
+    ));
 }
 
 #[test]
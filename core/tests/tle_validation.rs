@@ -0,0 +1,174 @@
+use openastroviz_core::tle::{self, DiagnosticKind, TleValidator};
+
+const ISS_LINE1: &str = "1 25544U 98067A   20194.88612269 -.00002218  00000-0 -31515-4 0  9992";
+const ISS_LINE2: &str = "2 25544  51.6461 221.2784 0001413  89.1723 280.4612 15.49507896236008";
+
+/// Replace `with.len()` characters starting at 1-based `column`.
+fn patch(line: &str, column: usize, with: &str) -> String {
+    let mut line = line.to_string();
+    line.replace_range(column - 1..column - 1 + with.len(), with);
+    line
+}
+
+/// Patch a line and repair its checksum, so only the patched field is bad.
+fn patch_and_sum(line: &str, column: usize, with: &str) -> String {
+    let line = patch(line, column, with);
+    let sum = tle::checksum(&line[..68]);
+    patch(&line, 69, &sum.to_string())
+}
+
+fn kinds(line1: &str, line2: &str) -> Vec<(usize, usize, DiagnosticKind)> {
+    TleValidator::default()
+        .validate_pair(line1, line2, 1)
+        .into_iter()
+        .map(|diagnostic| (diagnostic.line, diagnostic.column, diagnostic.kind))
+        .collect()
+}
+
+#[test]
+fn accepts_well_formed_records() {
+    assert_eq!(kinds(ISS_LINE1, ISS_LINE2), []);
+    let alpha5 = kinds(
+        "1 A5544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2925",
+        "2 A5544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563535",
+    );
+    assert_eq!(alpha5, []);
+}
+
+#[test]
+fn verification_catalog_only_trips_its_deliberate_outliers() {
+    // Strip the run times appended past column 69.
+    let catalog: String = include_str!("data/SGP4-VER.TLE")
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| format!("{}\n", &line[..line.len().min(69)]))
+        .collect();
+    let report = tle::validate_catalog(&catalog);
+    assert_eq!(report.objects, 33);
+    let flagged: Vec<(&str, DiagnosticKind)> = report
+        .diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let line = catalog.lines().nth(diagnostic.line - 1).unwrap();
+            (&line[2..7], diagnostic.kind)
+        })
+        .collect();
+    assert_eq!(
+        flagged,
+        [
+            ("33333", DiagnosticKind::EccentricityOutOfRange),
+            ("33334", DiagnosticKind::MeanMotionOutOfRange),
+        ]
+    );
+}
+
+#[test]
+fn reports_checksum_with_the_expected_digit() {
+    let line1 = patch(ISS_LINE1, 69, "3");
+    let diagnostics = TleValidator::default().validate_pair(&line1, ISS_LINE2, 7);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "line 7, column 69: checksum `3` should be 2"
+    );
+    assert_eq!(diagnostics[0].kind, DiagnosticKind::Checksum);
+}
+
+#[test]
+fn reports_layout_problems() {
+    let truncated = &ISS_LINE2[..60];
+    assert_eq!(
+        kinds(ISS_LINE1, truncated),
+        [(2, 61, DiagnosticKind::LineLength)]
+    );
+
+    let shifted = patch_and_sum(ISS_LINE1, 33, "0");
+    assert_eq!(
+        kinds(&shifted, ISS_LINE2),
+        [(1, 33, DiagnosticKind::Separator)]
+    );
+
+    let swapped = kinds(ISS_LINE2, ISS_LINE1);
+    assert!(swapped.contains(&(1, 1, DiagnosticKind::LineNumber)));
+    assert!(swapped.contains(&(2, 1, DiagnosticKind::LineNumber)));
+}
+
+#[test]
+fn reports_mismatched_catalog_numbers() {
+    let line2 = patch_and_sum(ISS_LINE2, 3, "25545");
+    let diagnostics = TleValidator::default().validate_pair(ISS_LINE1, &line2, 1);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].kind, DiagnosticKind::CatalogMismatch);
+    assert_eq!(
+        diagnostics[0].message,
+        "catalog number 25545 does not match 25544 on line 1"
+    );
+}
+
+#[test]
+fn reports_malformed_fields() {
+    let cases = [
+        (patch_and_sum(ISS_LINE1, 8, "X"), 8),
+        (patch_and_sum(ISS_LINE1, 3, "I5544"), 3),
+        (patch_and_sum(ISS_LINE1, 21, "367"), 21),
+        (patch_and_sum(ISS_LINE1, 45, " 00000 0"), 45),
+        (patch_and_sum(ISS_LINE1, 35, "x"), 34),
+    ];
+    for (line1, column) in cases {
+        let diagnostics = kinds(&line1, &patch_and_sum(ISS_LINE2, 3, &line1[2..7]));
+        assert!(
+            diagnostics.contains(&(1, column, DiagnosticKind::Field)),
+            "{line1}: {diagnostics:?}"
+        );
+    }
+
+    let inclination = patch_and_sum(ISS_LINE2, 9, "181.0000");
+    assert_eq!(
+        kinds(ISS_LINE1, &inclination),
+        [(2, 9, DiagnosticKind::Field)]
+    );
+}
+
+#[test]
+fn applies_the_validator_ranges() {
+    let validator = TleValidator {
+        epoch_years: 2021..=2030,
+        ..TleValidator::default()
+    };
+    let diagnostics = validator.validate_pair(ISS_LINE1, ISS_LINE2, 1);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].kind, DiagnosticKind::EpochOutOfWindow);
+    assert_eq!(diagnostics[0].column, 19);
+
+    let decaying = patch_and_sum(ISS_LINE2, 53, "18.50000000");
+    assert_eq!(
+        kinds(ISS_LINE1, &decaying),
+        [(2, 53, DiagnosticKind::MeanMotionOutOfRange)]
+    );
+}
+
+#[test]
+fn reports_every_problem_in_a_catalog() {
+    let bad_checksum = patch(ISS_LINE1, 69, "0");
+    let catalog = format!(
+        "ISS (ZARYA)\n{ISS_LINE1}\n{ISS_LINE2}\n\nBROKEN\n{bad_checksum}\n\n{}\n{ISS_LINE1}\n{ISS_LINE2}\nTRUNCATED\n{ISS_LINE1}\n",
+        patch(ISS_LINE2, 3, "25545"),
+    );
+    let report = tle::validate_catalog(&catalog);
+    assert_eq!(report.objects, 4);
+    assert!(!report.is_valid());
+    let found: Vec<(usize, usize, &str)> = report
+        .diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.line, diagnostic.column, diagnostic.kind.code()))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (6, 69, "checksum"),
+            (8, 69, "checksum"),
+            (8, 3, "catalog-mismatch"),
+            (13, 1, "missing-line"),
+        ]
+    );
+}
@@ -1,5 +1,5 @@
 use approx::assert_relative_eq;
-use openastroviz_core::Sgp4Propagator;
+use openastroviz_core::{fp32, ElementSet, Sgp4Propagator};
 
 const LINE1: &str = "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753";
 const LINE2: &str = "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
@@ -15,30 +15,32 @@ fn assert_state_close(
     }
 }
 
+/// Vanguard 1 states from the Vallado verification output, as
+/// `(minutes since epoch, position km, velocity km/s)`.
+const VANGUARD_CASES: [(f64, [f64; 3], [f64; 3]); 3] = [
+    (
+        0.0,
+        [7022.46529266, -1400.08296755, 0.03995155],
+        [1.893841015, 6.405893759, 4.53480725],
+    ),
+    (
+        360.0,
+        [-7154.03120202, -3783.17682504, -3536.19412294],
+        [4.741887409, -4.151817765, -2.093935425],
+    ),
+    (
+        1440.0,
+        [-938.55923943, -6268.18748831, -4294.02924751],
+        [7.536105209, -0.427127707, 0.98987808],
+    ),
+];
+
 #[test]
 fn matches_vallado_reference_minutes() {
     let propagator = Sgp4Propagator::from_tle(Some("Vanguard 1".to_string()), LINE1, LINE2)
         .expect("failed to build propagator");
 
-    let cases = [
-        (
-            0.0,
-            [7022.46529266, -1400.08296755, 0.03995155],
-            [1.893841015, 6.405893759, 4.53480725],
-        ),
-        (
-            360.0,
-            [-7154.03120202, -3783.17682504, -3536.19412294],
-            [4.741887409, -4.151817765, -2.093935425],
-        ),
-        (
-            1440.0,
-            [-938.55923943, -6268.18748831, -4294.02924751],
-            [7.536105209, -0.427127707, 0.98987808],
-        ),
-    ];
-
-    for (minutes, expected_r, expected_v) in cases {
+    for (minutes, expected_r, expected_v) in VANGUARD_CASES {
         let state = propagator
             .propagate_minutes(minutes)
             .expect("propagation should succeed");
@@ -63,3 +65,20 @@ fn matches_vallado_reference_datetime() {
     let expected_v = [-4.209106476, 5.159719888, 2.74485298];
     assert_state_close(&state, expected_r, expected_v);
 }
+
+#[test]
+fn fp32_reference_matches_vallado_reference_minutes() {
+    let propagator =
+        Sgp4Propagator::from_tle(None, LINE1, LINE2).expect("failed to build propagator");
+    let packed = ElementSet::new(vec![propagator]).packed()[0];
+
+    // f32 carries about seven significant digits, so the budget is metres
+    // rather than the centimetres the f64 reference achieves.
+    for (minutes, expected_r, expected_v) in VANGUARD_CASES {
+        let state = fp32::propagate(&packed, minutes as f32).expect("propagation should succeed");
+        for i in 0..3 {
+            assert_relative_eq!(state.position_km[i], expected_r[i], epsilon = 0.1);
+            assert_relative_eq!(state.velocity_km_s[i], expected_v[i], epsilon = 1e-4);
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! Data-driven run of the full SGP4 verification catalog (AIAA 2006-6753).
+//!
+//! `data/SGP4-VER.TLE` holds the objects and run times, `data/SGP4-VER.e` the
+//! reference output in Vallado's `tcppver.out` layout. Every reference state
+//! and every error trap is checked against `Sgp4Propagator` in AFSPC mode,
+//! the mode the reference was generated in.
+use openastroviz_core::fp32::{self, Fp32Propagator};
+use openastroviz_core::{ObjectStatus, Sgp4Propagator, StateVector};
+
+/// Equatorial radius used by the reference run's decay check, in km.
+const EARTH_RADIUS_KM: f64 = 6378.135;
+
+/// Agreement required with the reference output, which is printed to 8
+/// decimals for positions and 9 for velocities.
+const POSITION_TOLERANCE_KM: f64 = 1e-6;
+const VELOCITY_TOLERANCE_KM_S: f64 = 1e-9;
+
+/// Minutes since epoch and the expected TEME state.
+type ReferenceState = (f64, StateVector);
+/// Minutes since epoch and Vallado error code at which a run stops.
+type ErrorTrap = (f64, u32);
+
+struct Case {
+    line1: String,
+    line2: String,
+    start: f64,
+    stop: f64,
+    step: f64,
+    satnum: u64,
+    states: Vec<ReferenceState>,
+    error: Option<ErrorTrap>,
+}
+
+impl Case {
+    fn propagator(&self) -> Sgp4Propagator {
+        Sgp4Propagator::from_tle_afspc(None, &self.line1, &self.line2)
+            .unwrap_or_else(|error| panic!("{}: {error}", self.satnum))
+    }
+}
+
+fn numbers(text: &str) -> Vec<f64> {
+    text.split_whitespace()
+        .map(|field| field.parse().unwrap())
+        .collect()
+}
+
+/// Pair every TLE with its block of reference output, in file order.
+fn catalog() -> Vec<Case> {
+    let tles: Vec<&str> = include_str!("data/SGP4-VER.TLE")
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect();
+
+    let mut blocks: Vec<(u64, Vec<ReferenceState>, Option<ErrorTrap>)> = Vec::new();
+    for line in include_str!("data/SGP4-VER.e").lines() {
+        if let Some(satnum) = line.strip_suffix(" xx") {
+            blocks.push((satnum.trim().parse().unwrap(), Vec::new(), None));
+        } else if let Some(trap) = line.strip_prefix("# *** error: t:= ") {
+            let (time, code) = trap.split_once(" *** code = ").unwrap();
+            let block = blocks.last_mut().unwrap();
+            block.2 = Some((time.parse().unwrap(), code.trim().parse().unwrap()));
+        } else if !line.starts_with('#') {
+            let fields = numbers(line);
+            let state = StateVector {
+                position_km: [fields[1], fields[2], fields[3]],
+                velocity_km_s: [fields[4], fields[5], fields[6]],
+            };
+            blocks.last_mut().unwrap().1.push((fields[0], state));
+        }
+    }
+
+    assert_eq!(tles.len(), 2 * blocks.len());
+    tles.chunks_exact(2)
+        .zip(blocks)
+        .map(|(pair, (satnum, states, error))| {
+            // Columns past 69 hold the run's start, stop and step.
+            let (line2, run) = pair[1].split_at(69);
+            let run = numbers(run);
+            Case {
+                line1: pair[0].to_string(),
+                line2: line2.to_string(),
+                start: run[0],
+                stop: run[1],
+                step: run[2],
+                satnum,
+                states,
+                error,
+            }
+        })
+        .collect()
+}
+
+fn radius(state: &StateVector) -> f64 {
+    state.position_km.iter().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+/// Differences from the reference that exceed the tolerances.
+fn mismatches(case: &Case) -> Vec<String> {
+    let propagator = case.propagator();
+    let mut mismatches = Vec::new();
+    for (minutes, expected) in &case.states {
+        let actual = match propagator.propagate_minutes(*minutes) {
+            Ok(state) => state,
+            Err(error) => {
+                mismatches.push(format!("{} at {minutes} min: {error}", case.satnum));
+                continue;
+            }
+        };
+        for axis in 0..3 {
+            let dr = (actual.position_km[axis] - expected.position_km[axis]).abs();
+            let dv = (actual.velocity_km_s[axis] - expected.velocity_km_s[axis]).abs();
+            if dr > POSITION_TOLERANCE_KM || dv > VELOCITY_TOLERANCE_KM_S {
+                mismatches.push(format!(
+                    "{} at {minutes} min, axis {axis}: {dr:.3e} km, {dv:.3e} km/s",
+                    case.satnum
+                ));
+            }
+        }
+    }
+    mismatches
+}
+
+#[test]
+fn catalog_is_complete() {
+    let catalog = catalog();
+    assert_eq!(catalog.len(), 33);
+    for case in &catalog {
+        let propagator = case.propagator();
+        assert_eq!(u64::from(propagator.norad_id()), case.satnum);
+        assert!(case.step > 0.0, "{}", case.satnum);
+        // The output always opens with the epoch state, then walks the run.
+        let mut times = case.states.iter().map(|(minutes, _)| *minutes);
+        if case.error != Some((0.0, 3)) {
+            assert_eq!(times.next(), Some(0.0), "{}", case.satnum);
+        }
+        for minutes in times {
+            let (low, high) = (case.start.min(case.stop), case.start.max(case.stop));
+            assert!(
+                (low - 1e-6..=high + 1e-6).contains(&minutes),
+                "{} at {minutes} min outside its run",
+                case.satnum
+            );
+        }
+    }
+}
+
+#[test]
+fn every_object_matches_the_reference_output() {
+    let mismatches: Vec<String> = catalog().iter().flat_map(mismatches).collect();
+    assert!(mismatches.is_empty(), "{}", mismatches.join("\n"));
+}
+
+#[test]
+fn error_traps_match_the_reference_output() {
+    let catalog = catalog();
+    let traps: Vec<&Case> = catalog.iter().filter(|case| case.error.is_some()).collect();
+    assert_eq!(
+        traps.iter().map(|case| case.satnum).collect::<Vec<_>>(),
+        [22312, 28872, 29141, 33333, 33334]
+    );
+
+    for case in traps {
+        let (minutes, code) = case.error.unwrap();
+        let expected = ObjectStatus::from_code(code).unwrap();
+        let error = case
+            .propagator()
+            .propagate_minutes(minutes)
+            .expect_err(&case.satnum.to_string());
+        assert_eq!(ObjectStatus::from(&error), expected, "{}", case.satnum);
+    }
+}
+
+#[test]
+fn decayed_objects_are_above_ground_until_the_trap() {
+    for case in catalog() {
+        if !matches!(case.error, Some((_, 6))) {
+            continue;
+        }
+        let propagator = case.propagator();
+        for (minutes, _) in &case.states {
+            let state = propagator.propagate_minutes(*minutes).unwrap();
+            assert!(
+                radius(&state) >= EARTH_RADIUS_KM,
+                "{} at {minutes}",
+                case.satnum
+            );
+        }
+    }
+}
+
+#[test]
+fn low_perigee_drag_trips_the_eccentricity_trap() {
+    // The verification run for 28350 stops at 1440 minutes; the catalog
+    // documents that propagation beyond about 1460 minutes drives the mean
+    // eccentricity negative.
+    let case = catalog()
+        .into_iter()
+        .find(|case| case.satnum == 28350)
+        .unwrap();
+    let propagator = case.propagator();
+    assert!(propagator.propagate_minutes(1460.0).is_ok());
+    let error = propagator.propagate_minutes(1500.0).unwrap_err();
+    assert_eq!(
+        ObjectStatus::from(&error),
+        ObjectStatus::EccentricityOutOfRange
+    );
+}
+
+#[test]
+fn resonance_cases_match_the_reference_output() {
+    // Classify with the packed kernel constants, which record the resonance
+    // branch SGP4 initialization selected.
+    let catalog = catalog();
+    let classified: Vec<(u32, &Case)> = catalog
+        .iter()
+        .filter(|case| case.error.is_none())
+        .map(|case| {
+            let packed = *Fp32Propagator::from(&case.propagator()).packed();
+            (packed.resonance, case)
+        })
+        .collect();
+    let satnums = |resonance| -> Vec<u64> {
+        classified
+            .iter()
+            .filter(|(branch, _)| *branch == resonance)
+            .map(|(_, case)| case.satnum)
+            .collect()
+    };
+
+    assert_eq!(
+        satnums(fp32::RESONANCE_ONE_DAY),
+        [9998, 14128, 24208, 25954, 26900, 28626, 33335]
+    );
+    assert_eq!(
+        satnums(fp32::RESONANCE_HALF_DAY),
+        [8195, 9880, 21897, 22674, 26975]
+    );
+    for (_, case) in classified
+        .iter()
+        .filter(|(branch, _)| *branch != fp32::RESONANCE_NONE)
+    {
+        let mismatches = mismatches(case);
+        assert!(mismatches.is_empty(), "{}", mismatches.join("\n"));
+    }
+}
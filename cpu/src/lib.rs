@@ -0,0 +1,150 @@
+//! Multi-core CPU reference backend for OpenAstroViz.
+//!
+//! Propagates a whole catalog of [`Sgp4Propagator`]s per frame with rayon and
+//! writes the results into a structure-of-arrays [`StateBuffer`], the same
+//! layout the GPU backends read back. Because it runs the f64 Vallado
+//! reference on any machine, CI uses this backend as the source of truth.
+use chrono::NaiveDateTime;
+use openastroviz_core::backend::BackendFuture;
+use openastroviz_core::{
+    DispatchTimes, ElementSet, GpuBackend, GpuBackendError, ObjectStatus, Sgp4Propagator,
+    StateBuffer, StateVector,
+};
+use rayon::prelude::*;
+
+/// Batch SGP4 propagation across all available cores.
+#[derive(Debug, Clone, Default)]
+pub struct CpuBackend {
+    propagators: Vec<Sgp4Propagator>,
+}
+
+impl CpuBackend {
+    pub fn new(propagators: Vec<Sgp4Propagator>) -> Self {
+        Self { propagators }
+    }
+
+    /// Replace the loaded catalog.
+    pub fn load(&mut self, propagators: Vec<Sgp4Propagator>) {
+        self.propagators = propagators;
+    }
+
+    pub fn propagators(&self) -> &[Sgp4Propagator] {
+        &self.propagators
+    }
+
+    pub fn len(&self) -> usize {
+        self.propagators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.propagators.is_empty()
+    }
+
+    /// Propagate every object to the same UTC instant.
+    ///
+    /// `out` is resized to the catalog length. Returns the number of objects
+    /// that failed to propagate.
+    pub fn propagate_datetime(&self, time: NaiveDateTime, out: &mut StateBuffer) -> usize {
+        propagate_into(&self.propagators, out, |_, propagator| {
+            propagator
+                .propagate_datetime(time)
+                .map_err(|e| ObjectStatus::from(&e))
+        })
+    }
+
+    /// Propagate object `i` to `minutes_since_epoch[i]` minutes past its own
+    /// epoch.
+    ///
+    /// Returns the number of objects that failed to propagate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `minutes_since_epoch` does not have one entry per object.
+    pub fn propagate_minutes(&self, minutes_since_epoch: &[f64], out: &mut StateBuffer) -> usize {
+        assert_eq!(
+            minutes_since_epoch.len(),
+            self.propagators.len(),
+            "one time offset is required per object"
+        );
+        propagate_into(&self.propagators, out, |index, propagator| {
+            let minutes = minutes_since_epoch[index];
+            if !minutes.is_finite() {
+                return Err(ObjectStatus::InvalidTime);
+            }
+            propagator
+                .propagate_minutes(minutes)
+                .map_err(|e| ObjectStatus::from(&e))
+        })
+    }
+}
+
+impl GpuBackend for CpuBackend {
+    fn name(&self) -> &'static str {
+        "cpu"
+    }
+
+    fn is_ready(&self) -> bool {
+        true
+    }
+
+    fn upload_elements(&mut self, elements: &ElementSet) -> Result<(), GpuBackendError> {
+        self.propagators = elements.propagators().to_vec();
+        Ok(())
+    }
+
+    /// Runs the whole batch on the rayon pool when first polled, so awaiting
+    /// it blocks the calling task until every object is done.
+    fn propagate<'a>(
+        &'a self,
+        times: DispatchTimes<'a>,
+        states: &'a mut StateBuffer,
+    ) -> BackendFuture<'a> {
+        Box::pin(async move {
+            let minutes = times.minutes_since_epoch(&self.propagators)?;
+            self.propagate_minutes(&minutes, states);
+            Ok(())
+        })
+    }
+}
+
+/// Run `propagate` for every object in parallel and scatter the results into
+/// `out`. Returns the number of failures.
+fn propagate_into<F>(propagators: &[Sgp4Propagator], out: &mut StateBuffer, propagate: F) -> usize
+where
+    F: Fn(usize, &Sgp4Propagator) -> Result<StateVector, ObjectStatus> + Sync,
+{
+    out.resize(propagators.len());
+    let StateBuffer {
+        x_km,
+        y_km,
+        z_km,
+        vx_km_s,
+        vy_km_s,
+        vz_km_s,
+        status,
+    } = out;
+
+    (
+        propagators.par_iter(),
+        x_km.par_iter_mut(),
+        y_km.par_iter_mut(),
+        z_km.par_iter_mut(),
+        vx_km_s.par_iter_mut(),
+        vy_km_s.par_iter_mut(),
+        vz_km_s.par_iter_mut(),
+        status.par_iter_mut(),
+    )
+        .into_par_iter()
+        .enumerate()
+        .map(|(index, (propagator, x, y, z, vx, vy, vz, status))| {
+            let (position, velocity, outcome) = match propagate(index, propagator) {
+                Ok(state) => (state.position_km, state.velocity_km_s, ObjectStatus::Ok),
+                Err(failure) => ([f64::NAN; 3], [f64::NAN; 3], failure),
+            };
+            [*x, *y, *z] = position;
+            [*vx, *vy, *vz] = velocity;
+            *status = outcome;
+            usize::from(!outcome.is_ok())
+        })
+        .sum()
+}
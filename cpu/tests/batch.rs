@@ -0,0 +1,136 @@
+use approx::assert_relative_eq;
+use chrono::Duration;
+use openastroviz_core::{
+    DispatchTimes, ElementSet, GpuBackend, GpuBackendError, ObjectStatus, Sgp4Propagator,
+    StateBuffer,
+};
+use openastroviz_cpu::CpuBackend;
+
+const ISS_LINE1: &str = "1 25544U 98067A   20194.88612269 -.00002218  00000-0 -31515-4 0  9992";
+const ISS_LINE2: &str = "2 25544  51.6461 221.2784 0001413  89.1723 280.4612 15.49507896236008";
+const VANGUARD_LINE1: &str =
+    "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753";
+const VANGUARD_LINE2: &str =
+    "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
+
+fn catalog(copies: usize) -> Vec<Sgp4Propagator> {
+    let iss = Sgp4Propagator::from_tle(None, ISS_LINE1, ISS_LINE2).unwrap();
+    let vanguard = Sgp4Propagator::from_tle(None, VANGUARD_LINE1, VANGUARD_LINE2).unwrap();
+    (0..copies)
+        .map(|i| {
+            if i % 2 == 0 {
+                iss.clone()
+            } else {
+                vanguard.clone()
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn batch_matches_scalar_propagation() {
+    let propagators = catalog(1_000);
+    let backend = CpuBackend::new(propagators.clone());
+    let time = propagators[0].epoch() + Duration::hours(6);
+
+    let mut out = StateBuffer::default();
+    let failed = backend.propagate_datetime(time, &mut out);
+
+    assert_eq!(failed, 0);
+    assert_eq!(out.len(), propagators.len());
+    assert_eq!(out.ok_count(), propagators.len());
+    for (index, propagator) in propagators.iter().enumerate() {
+        let expected = propagator.propagate_datetime(time).unwrap();
+        let actual = out.state(index).unwrap();
+        for axis in 0..3 {
+            assert_relative_eq!(actual.position_km[axis], expected.position_km[axis]);
+            assert_relative_eq!(actual.velocity_km_s[axis], expected.velocity_km_s[axis]);
+        }
+    }
+}
+
+#[test]
+fn per_object_offsets_are_relative_to_each_epoch() {
+    let backend = CpuBackend::new(catalog(2));
+    let mut out = StateBuffer::default();
+    backend.propagate_minutes(&[0.0, 360.0], &mut out);
+
+    // Vallado reference for Vanguard 1 at 360 minutes.
+    assert_relative_eq!(out.x_km[1], -7154.03120202, epsilon = 2e-2);
+    assert_relative_eq!(out.y_km[1], -3783.17682504, epsilon = 2e-2);
+    assert_relative_eq!(out.z_km[1], -3536.19412294, epsilon = 2e-2);
+}
+
+#[test]
+fn failed_objects_are_flagged_without_failing_the_batch() {
+    let backend = CpuBackend::new(catalog(2));
+    let mut out = StateBuffer::default();
+    let failed = backend.propagate_minutes(&[f64::NAN, 0.0], &mut out);
+
+    assert_eq!(failed, 1);
+    assert_eq!(out.status[0], ObjectStatus::InvalidTime);
+    assert!(out.state(0).is_none());
+    assert!(out.x_km[0].is_nan());
+    assert!(out.state(1).is_some());
+}
+
+#[test]
+fn decayed_objects_are_flagged_without_failing_the_batch() {
+    // Sub-orbital verification object, below the surface an hour after epoch.
+    let decaying = Sgp4Propagator::from_tle(
+        None,
+        "1 28872U 05037B   05333.02012661  .25992681  00000-0  24476-3 0  1534",
+        "2 28872  96.4736 157.9986 0303955 244.0492 110.6523 16.46015938 10708",
+    )
+    .unwrap();
+    let mut propagators = catalog(2);
+    propagators.insert(1, decaying);
+    let backend = CpuBackend::new(propagators);
+    let mut out = StateBuffer::default();
+    let failed = backend.propagate_minutes(&[0.0, 60.0, 0.0], &mut out);
+
+    assert_eq!(failed, 1);
+    assert_eq!(
+        out.status,
+        [ObjectStatus::Ok, ObjectStatus::Decayed, ObjectStatus::Ok]
+    );
+    assert!(out.state(1).is_none());
+}
+
+#[test]
+fn buffer_is_resized_to_the_catalog() {
+    let backend = CpuBackend::new(catalog(3));
+    let mut out = StateBuffer::with_len(10);
+    backend.propagate_minutes(&[0.0; 3], &mut out);
+    assert_eq!(out.len(), 3);
+    assert_eq!(out.vz_km_s.len(), 3);
+}
+
+#[test]
+fn implements_backend_contract() {
+    let elements: ElementSet = catalog(4).into_iter().collect();
+    let mut backend = CpuBackend::default();
+    assert_eq!(backend.name(), "cpu");
+    assert!(backend.is_ready());
+    backend.upload_elements(&elements).unwrap();
+    assert_eq!(backend.len(), 4);
+
+    let time = elements.propagators()[0].epoch() + Duration::minutes(90);
+    let mut states = StateBuffer::default();
+    pollster::block_on(backend.propagate(DispatchTimes::Uniform(time), &mut states)).unwrap();
+    assert_eq!(states.ok_count(), 4);
+    let expected = elements.propagators()[0].propagate_datetime(time).unwrap();
+    assert_eq!(states.state(0), Some(expected));
+
+    let err = pollster::block_on(
+        backend.propagate(DispatchTimes::MinutesSinceEpoch(&[0.0; 3]), &mut states),
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        GpuBackendError::TimeCountMismatch {
+            expected: 4,
+            actual: 3
+        }
+    ));
+}
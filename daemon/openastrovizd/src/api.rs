@@ -0,0 +1,442 @@
+//! The HTTP API under `/api`, answering one-off questions about the live
+//! catalog. Objects and snapshots use the same JSON shapes as the orbital
+//! stream; failures come back as `{"error": "..."}`.
+use std::sync::Arc;
+
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{Path, Query, State};
+use axum::http::header::ACCESS_CONTROL_ALLOW_ORIGIN;
+use axum::http::{HeaderValue, StatusCode};
+use axum::middleware;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use openastroviz_core::omm::OmmFormat;
+use openastroviz_core::NoradId;
+use serde::{Deserialize, Serialize};
+
+use crate::orbits::{Framer, ObjectInfo, OrbitalObject, OrbitalSnapshot};
+use crate::server::AppState;
+use crate::tle::OrbitalRecord;
+
+/// Search results returned when the request does not ask for a number.
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+/// The most search results one request can ask for.
+const MAX_SEARCH_LIMIT: usize = 500;
+
+/// The `/api` routes. Any origin may call them, since the web client is
+/// usually served from another port.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/orbits/snapshot", get(snapshot))
+        .route("/api/objects", get(search))
+        .route("/api/objects/{id}", get(object))
+        .route("/api/catalog", get(catalog))
+        .layer(middleware::map_response(allow_any_origin))
+}
+
+async fn allow_any_origin(mut response: Response) -> Response {
+    response
+        .headers_mut()
+        .insert(ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+    response
+}
+
+/// A failed request, answered with `status` and `{"error": message}`.
+#[derive(Debug)]
+struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, rejection.body_text())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        #[derive(Serialize)]
+        struct Body {
+            error: String,
+        }
+        (
+            self.status,
+            Json(Body {
+                error: self.message,
+            }),
+        )
+            .into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+#[derive(Debug, Deserialize)]
+struct TimeQuery {
+    /// RFC 3339 time to propagate to; now when absent.
+    time: Option<DateTime<Utc>>,
+}
+
+/// `GET /api/orbits/snapshot[?time=]`: the whole catalog at `time`, or the
+/// latest streamed frame when no time is given.
+async fn snapshot(
+    State(state): State<AppState>,
+    query: Result<Query<TimeQuery>, QueryRejection>,
+) -> ApiResult<OrbitalSnapshot> {
+    let Query(TimeQuery { time }) = query?;
+    let Some(time) = time else {
+        let frame = Arc::clone(&state.frames.borrow());
+        return Ok(Json(frame.snapshot.clone()));
+    };
+    let mut framer = Framer::new(state.screener);
+    framer.load(&*state.catalog.read().await);
+    let frame = tokio::task::spawn_blocking(move || framer.frame(time))
+        .await
+        .map_err(|err| {
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("propagation failed: {err}"),
+            )
+        })?;
+    Ok(Json(frame.snapshot))
+}
+
+/// One object with the elements it was propagated from.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ObjectDetail {
+    #[serde(flatten)]
+    object: OrbitalObject,
+    international_designator: Option<String>,
+    /// The element set as a CCSDS OMM JSON object.
+    elements: serde_json::Value,
+}
+
+/// `GET /api/objects/{id}[?time=]`: one object by NORAD ID, decimal or
+/// Alpha-5.
+async fn object(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    query: Result<Query<TimeQuery>, QueryRejection>,
+) -> ApiResult<ObjectDetail> {
+    let Query(TimeQuery { time }) = query?;
+    let time = time.unwrap_or_else(Utc::now);
+    let norad_id: NoradId = id.parse().map_err(|err| {
+        ApiError::new(
+            StatusCode::BAD_REQUEST,
+            format!("`{id}` is not a NORAD ID: {err}"),
+        )
+    })?;
+    let record = state
+        .catalog
+        .read()
+        .await
+        .records
+        .iter()
+        .find(|record| record.norad_id == norad_id)
+        .cloned()
+        .ok_or_else(|| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("object {} is not in the catalog", norad_id.get()),
+            )
+        })?;
+    let object = propagate(&record, time)
+        .map_err(|message| ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, message))?;
+    let elements =
+        serde_json::from_str::<serde_json::Value>(&record.propagator.to_omm(OmmFormat::Json))
+            .ok()
+            .and_then(|mut messages| messages.get_mut(0).map(serde_json::Value::take))
+            .unwrap_or_default();
+    Ok(Json(ObjectDetail {
+        object,
+        international_designator: record
+            .propagator
+            .elements()
+            .international_designator
+            .clone(),
+        elements,
+    }))
+}
+
+fn propagate(record: &OrbitalRecord, time: DateTime<Utc>) -> Result<OrbitalObject, String> {
+    let state = record
+        .propagator
+        .propagate_datetime(time.naive_utc())
+        .map_err(|err| err.to_string())?;
+    Ok(ObjectInfo::new(record).object(state.position_km, state.velocity_km_s, time))
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchQuery {
+    q: String,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct SearchResults {
+    /// How many objects matched, including any beyond the limit.
+    matches: usize,
+    objects: Vec<OrbitalObject>,
+}
+
+/// `GET /api/objects?q=&limit=`: objects whose name contains `q`, ignoring
+/// case, or whose international designator starts with it. Matches are
+/// ordered by NORAD ID and propagated to now; ones that no longer propagate
+/// are counted but not listed.
+async fn search(
+    State(state): State<AppState>,
+    query: Result<Query<SearchQuery>, QueryRejection>,
+) -> ApiResult<SearchResults> {
+    let Query(SearchQuery { q, limit }) = query?;
+    let q = q.trim();
+    if q.is_empty() {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "the search text `q` is empty",
+        ));
+    }
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT).min(MAX_SEARCH_LIMIT);
+    let name = q.to_lowercase();
+    let designator = normalize_designator(q);
+
+    let mut found: Vec<OrbitalRecord> = state
+        .catalog
+        .read()
+        .await
+        .records
+        .iter()
+        .filter(|record| {
+            record.name.to_lowercase().contains(&name)
+                || record
+                    .propagator
+                    .elements()
+                    .international_designator
+                    .as_deref()
+                    .is_some_and(|id| designator_matches(id, &designator))
+        })
+        .cloned()
+        .collect();
+    found.sort_by_key(|record| record.norad_id.get());
+
+    let now = Utc::now();
+    let objects = found
+        .iter()
+        .filter_map(|record| propagate(record, now).ok())
+        .take(limit)
+        .collect();
+    Ok(Json(SearchResults {
+        matches: found.len(),
+        objects,
+    }))
+}
+
+/// A designator without dashes, in upper case: "1998-067A" becomes
+/// "1998067A".
+fn normalize_designator(text: &str) -> String {
+    text.chars()
+        .filter(|&c| c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Whether `designator` starts with the normalized `prefix`, written with a
+/// four-digit year or, as in TLEs, a two-digit one.
+fn designator_matches(designator: &str, prefix: &str) -> bool {
+    let full = normalize_designator(designator);
+    full.starts_with(prefix) || full.get(2..).is_some_and(|short| short.starts_with(prefix))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CatalogInfo {
+    objects: usize,
+    /// When the oldest contributing source was fetched.
+    fetched_at: Option<DateTime<Utc>>,
+    sources: Vec<SourceInfo>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SourceInfo {
+    source: String,
+    fetched_at: DateTime<Utc>,
+    objects: usize,
+}
+
+/// `GET /api/catalog`: what the live catalog holds and where it came from.
+async fn catalog(State(state): State<AppState>) -> Json<CatalogInfo> {
+    let live = state.catalog.read().await;
+    Json(CatalogInfo {
+        objects: live.records.len(),
+        fetched_at: live.fetched_at,
+        sources: live
+            .sources
+            .iter()
+            .map(|meta| SourceInfo {
+                source: meta.source.clone(),
+                fetched_at: meta.fetched_at,
+                objects: meta.objects,
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheMeta;
+    use crate::conjunction::Screener;
+    use crate::server::{frame_channel, serve};
+    use crate::source::LiveCatalog;
+    use crate::test_util::{GEO, VANGUARD};
+    use crate::tle::{parse_catalog_with, ParseMode};
+    use tokio::net::TcpListener;
+    use tokio::sync::RwLock;
+
+    /// Serve the API over a catalog of VANGUARD 1 and an SL-12 R/B, and
+    /// return its base URL.
+    async fn start() -> String {
+        let fetched_at = "2006-06-26T00:00:00Z".parse().unwrap();
+        let catalog = LiveCatalog {
+            records: parse_catalog_with(&format!("{VANGUARD}{GEO}"), ParseMode::Lenient)
+                .unwrap()
+                .records,
+            fetched_at: Some(fetched_at),
+            revision: 1,
+            sources: vec![CacheMeta {
+                source: "file:catalog.tle".to_string(),
+                fetched_at,
+                etag: None,
+                last_modified: None,
+                objects: 2,
+            }],
+        };
+        let (_frames, receiver) = frame_channel();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(serve(
+            listener,
+            AppState {
+                frames: receiver,
+                catalog: Arc::new(RwLock::new(catalog)),
+                screener: Screener::default(),
+            },
+        ));
+        url
+    }
+
+    async fn get(url: String) -> (StatusCode, serde_json::Value) {
+        let response = reqwest::get(url).await.unwrap();
+        let status = StatusCode::from_u16(response.status().as_u16()).unwrap();
+        assert_eq!(
+            response.headers()["access-control-allow-origin"],
+            "*",
+            "missing CORS header"
+        );
+        (
+            status,
+            serde_json::from_str(&response.text().await.unwrap()).unwrap(),
+        )
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn snapshots_at_a_given_time() {
+        let base = start().await;
+        let (status, body) = get(format!(
+            "{base}/api/orbits/snapshot?time=2006-06-26T12:00:00Z"
+        ))
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["epoch"], "2006-06-26T12:00:00.000Z");
+        assert_eq!(body["stats"]["tracked"], 2);
+        assert_eq!(body["objects"][1]["noradId"], 28626);
+        assert!(body["closeApproaches"].is_array());
+
+        let (status, body) = get(format!("{base}/api/orbits/snapshot?time=noon")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("time"), "{body}");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn looks_up_objects_by_norad_id() {
+        let base = start().await;
+        let (status, body) = get(format!(
+            "{base}/api/objects/28626?time=2006-06-26T12:00:00Z"
+        ))
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["id"], "28626");
+        assert_eq!(body["name"], "SL-12 R/B");
+        assert_eq!(body["kind"], "rocket_body");
+        assert_eq!(body["health"]["status"], "nominal");
+        assert_eq!(body["internationalDesignator"], "2005-008A");
+        assert_eq!(body["elements"]["NORAD_CAT_ID"], 28626);
+        let radius = body["position"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|x| x.as_f64().unwrap().powi(2))
+            .sum::<f64>()
+            .sqrt();
+        assert!((radius - 42_164.0).abs() < 100.0, "radius {radius} km");
+
+        let (status, body) = get(format!("{base}/api/objects/25544")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "object 25544 is not in the catalog");
+        let (status, _) = get(format!("{base}/api/objects/ISS")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn searches_names_and_designators() {
+        let base = start().await;
+        let ids = |body: &serde_json::Value| -> Vec<u64> {
+            body["objects"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|object| object["noradId"].as_u64().unwrap())
+                .collect()
+        };
+        let (status, body) = get(format!("{base}/api/objects?q=vanguard")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["matches"], 1);
+        assert_eq!(ids(&body), [5]);
+
+        let (_, body) = get(format!("{base}/api/objects?q=2005-008")).await;
+        assert_eq!(ids(&body), [28626]);
+        let (_, body) = get(format!("{base}/api/objects?q=58002b")).await;
+        assert_eq!(ids(&body), [5]);
+
+        let (_, body) = get(format!("{base}/api/objects?q=1&limit=1")).await;
+        assert_eq!(body["matches"], 2);
+        assert_eq!(ids(&body), [5]);
+
+        let (status, _) = get(format!("{base}/api/objects?q=")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn describes_the_catalog() {
+        let base = start().await;
+        let (status, body) = get(format!("{base}/api/catalog")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["objects"], 2);
+        assert_eq!(body["fetchedAt"], "2006-06-26T00:00:00Z");
+        assert_eq!(body["sources"][0]["source"], "file:catalog.tle");
+        assert_eq!(body["sources"][0]["objects"], 2);
+    }
+}
@@ -1,8 +1,11 @@
 use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-/// Supported backends for benchmarking.
-#[derive(Clone, Copy, Debug, ValueEnum)]
+/// Supported compute backends, for benchmarking and for the service's
+/// `[compute]` config section.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Backend {
     /// CUDA backend
     Cuda,
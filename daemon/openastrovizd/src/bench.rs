@@ -3,7 +3,28 @@ use std::hint::black_box;
 use std::thread;
 use std::time::{Duration, Instant};
 
+use openastroviz_core::{Sgp4Propagator, StateBuffer};
+use openastroviz_cpu::CpuBackend;
+
 use crate::backend::Backend;
+use crate::conjunction::GridConfig;
+
+/// Representative LEO, MEO/HEO and GEO element sets used to build the
+/// synthetic CPU benchmark catalog.
+const BENCH_TLES: [(&str, &str); 3] = [
+    (
+        "1 25544U 98067A   20194.88612269 -.00002218  00000-0 -31515-4 0  9992",
+        "2 25544  51.6461 221.2784 0001413  89.1723 280.4612 15.49507896236008",
+    ),
+    (
+        "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753",
+        "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667",
+    ),
+    (
+        "1 28626U 05008A   06176.46683397 -.00000205  00000-0  10000-3 0  2190",
+        "2 28626   0.0019 286.9433 0000335  13.7918  55.6504  1.00270176  4891",
+    ),
+];
 
 /// Errors that can occur while benchmarking.
 #[derive(Debug)]
@@ -33,7 +54,7 @@ struct CpuRunner;
 impl BenchRunner for CpuRunner {
     fn run(&self) -> Result<BenchOutcome, BenchError> {
         let start = Instant::now();
-        let work_units = simulate_cpu_propagation(50_000)?;
+        let work_units = propagate_cpu_catalog(2_048, 8)?;
         Ok(BenchOutcome {
             duration: start.elapsed(),
             work_units,
@@ -54,10 +75,11 @@ impl BenchRunner for CudaRunner {
     }
 }
 
-/// Runs a stub benchmark for the given backend.
+/// Runs a benchmark for the given backend.
 ///
-/// This currently performs a dummy computation to provide an example
-/// of how benchmarking might be implemented.
+/// The CPU benchmark propagates a synthetic catalog with the real
+/// `openastroviz-cpu` backend; the CUDA benchmark is still a stand-in that
+/// models the cell-grid conjunction pipeline.
 pub fn bench_backend(backend: Backend) -> Result<BenchOutcome, BenchError> {
     match backend {
         Backend::Cuda => CudaRunner.run(),
@@ -65,66 +87,32 @@ pub fn bench_backend(backend: Backend) -> Result<BenchOutcome, BenchError> {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-struct Cartesian {
-    x: f64,
-    y: f64,
-    z: f64,
-}
-
-#[derive(Clone, Copy, Debug)]
-struct GridConfig {
-    min: f64,
-    max: f64,
-    voxel_size: f64,
-    dim: u32,
-}
-
-impl GridConfig {
-    fn leo_to_geo() -> Self {
-        // ±45,000 km cube comfortably covers LEO through GEO with guard cells.
-        let min = -45_000.0;
-        let max = 45_000.0;
-        let voxel_size = 250.0;
-        let dim = ((max - min) / voxel_size) as u32;
-
-        Self {
-            min,
-            max,
-            voxel_size,
-            dim,
-        }
-    }
-
-    fn coords_to_linear_hash(&self, point: Cartesian) -> u32 {
-        let nx = self.quantize_axis(point.x);
-        let ny = self.quantize_axis(point.y);
-        let nz = self.quantize_axis(point.z);
-
-        nx + self.dim * (ny + self.dim * nz)
-    }
-
-    fn quantize_axis(&self, axis: f64) -> u32 {
-        let clamped = axis.clamp(self.min, self.max - f64::EPSILON);
-        let normalized = (clamped - self.min) / self.voxel_size;
-        normalized.floor() as u32
-    }
-}
-
-fn simulate_cpu_propagation(steps: u64) -> Result<u64, BenchError> {
-    // Stand-in for CPU propagator: iteratively update a pseudo state vector.
-    let mut state = [1.0_f64, 0.5, -0.25];
-    let mut total = 0.0_f64;
-
-    for tick in 0..steps {
-        state[0] += state[1] * 0.001;
-        state[1] += state[2] * 0.001;
-        state[2] = -(state[0] * 0.0001).sin();
-        total += state[0].abs() + state[1].abs() + state[2].abs() + tick as f64 * 1e-6;
+fn propagate_cpu_catalog(object_count: usize, frames: u32) -> Result<u64, BenchError> {
+    let templates = BENCH_TLES
+        .iter()
+        .map(|(line1, line2)| Sgp4Propagator::from_tle(None, line1, line2))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| BenchError::Failed)?;
+    let backend = CpuBackend::new(
+        templates
+            .iter()
+            .cycle()
+            .take(object_count)
+            .cloned()
+            .collect(),
+    );
+
+    // Spread the objects along their orbits and advance one minute per frame.
+    let mut minutes: Vec<f64> = (0..object_count).map(|i| (i % 1_440) as f64).collect();
+    let mut states = StateBuffer::with_len(object_count);
+    let mut work_units = 0_u64;
+    for _ in 0..frames {
+        let failed = backend.propagate_minutes(&minutes, &mut states);
+        work_units += (object_count - failed) as u64;
+        minutes.iter_mut().for_each(|m| *m += 1.0);
     }
+    black_box(&states);
 
-    let work_units = (total * 10_000.0) as u64;
-    black_box(&state);
     if work_units == 0 {
         return Err(BenchError::Failed);
     }
@@ -136,16 +124,17 @@ fn simulate_cuda_cell_grid_conjunctions(object_count: u32) -> Result<u64, BenchE
     // Stand-in for CUDA launch overhead.
     thread::sleep(Duration::from_millis(5));
 
-    let grid = GridConfig::leo_to_geo();
-    let mut keyed: Vec<(u32, Cartesian)> = (0..object_count)
+    // ±45,000 km cube comfortably covers LEO through GEO with guard cells.
+    let grid = GridConfig::new(250.0);
+    let mut keyed: Vec<(u64, [f64; 3])> = (0..object_count)
         .map(|id| {
             // Deterministic pseudo-orbit position generation in km.
             let t = id as f64 * 0.013;
-            let point = Cartesian {
-                x: (t.sin() * 37_000.0) + ((id % 97) as f64 - 48.0) * 7.5,
-                y: (t.cos() * 39_000.0) + ((id % 53) as f64 - 26.0) * 8.0,
-                z: ((t * 0.7).sin() * 20_000.0) + ((id % 41) as f64 - 20.0) * 6.0,
-            };
+            let point = [
+                (t.sin() * 37_000.0) + ((id % 97) as f64 - 48.0) * 7.5,
+                (t.cos() * 39_000.0) + ((id % 53) as f64 - 26.0) * 8.0,
+                ((t * 0.7).sin() * 20_000.0) + ((id % 41) as f64 - 20.0) * 6.0,
+            ];
             (grid.coords_to_linear_hash(point), point)
         })
         .collect();
@@ -154,7 +143,7 @@ fn simulate_cuda_cell_grid_conjunctions(object_count: u32) -> Result<u64, BenchE
     keyed.sort_unstable_by_key(|(hash, _)| *hash);
 
     // Build compact voxel ranges over the sorted list.
-    let mut voxel_ranges: Vec<(u32, usize, usize)> = Vec::new();
+    let mut voxel_ranges: Vec<(u64, usize, usize)> = Vec::new();
     let mut i = 0;
     while i < keyed.len() {
         let hash = keyed[i].0;
@@ -166,18 +155,18 @@ fn simulate_cuda_cell_grid_conjunctions(object_count: u32) -> Result<u64, BenchE
     }
 
     // Sliding queue keeps only local 3x3x3 neighbors in z-major linear hash space.
-    let plane_span = (grid.dim * grid.dim) as u64;
-    let mut active: VecDeque<(u32, usize, usize)> = VecDeque::new();
+    let plane_span = grid.dim * grid.dim;
+    let mut active: VecDeque<(u64, usize, usize)> = VecDeque::new();
     let mut range_index = 0usize;
     let mut candidate_pairs: u64 = 0;
 
     while range_index < voxel_ranges.len() {
         let (hash, start, end) = voxel_ranges[range_index];
-        let current = hash as u64;
+        let current = hash;
 
         while let Some((queued_hash, _, _)) = active.front().copied() {
-            let queued = queued_hash as u64;
-            if current.saturating_sub(queued) > plane_span + grid.dim as u64 + 1 {
+            let queued = queued_hash;
+            if current.saturating_sub(queued) > plane_span + grid.dim + 1 {
                 active.pop_front();
             } else {
                 break;
@@ -211,7 +200,7 @@ fn simulate_cuda_cell_grid_conjunctions(object_count: u32) -> Result<u64, BenchE
     Ok(work_units)
 }
 
-fn are_neighbor_voxels(a: u32, b: u32, dim: u32) -> bool {
+fn are_neighbor_voxels(a: u64, b: u64, dim: u64) -> bool {
     let (ax, ay, az) = unflatten_hash(a, dim);
     let (bx, by, bz) = unflatten_hash(b, dim);
 
@@ -220,7 +209,7 @@ fn are_neighbor_voxels(a: u32, b: u32, dim: u32) -> bool {
         && (az as i64 - bz as i64).abs() <= 1
 }
 
-fn unflatten_hash(hash: u32, dim: u32) -> (u32, u32, u32) {
+fn unflatten_hash(hash: u64, dim: u64) -> (u64, u64, u64) {
     let plane = dim * dim;
     let z = hash / plane;
     let rem = hash % plane;
@@ -233,6 +222,7 @@ fn unflatten_hash(hash: u32, dim: u32) -> (u32, u32, u32) {
 mod tests {
     use super::*;
     use crate::backend::Backend;
+    use crate::conjunction::GridConfig;
 
     #[test]
     fn bench_returns_non_zero_work() {
@@ -256,7 +246,7 @@ mod tests {
 
     #[test]
     fn voxel_neighbor_predicate_matches_3x3x3_expectation() {
-        let dim = GridConfig::leo_to_geo().dim;
+        let dim = GridConfig::new(250.0).dim;
         let center = dim + dim * (dim + dim * dim);
 
         assert!(are_neighbor_voxels(center, center, dim));
@@ -264,19 +254,4 @@ mod tests {
         assert!(are_neighbor_voxels(center, center + dim + 1, dim));
         assert!(!are_neighbor_voxels(center, center + 2, dim));
     }
-
-    #[test]
-    fn hash_round_trip_is_stable() {
-        let grid = GridConfig::leo_to_geo();
-        let point = Cartesian {
-            x: 42.0,
-            y: -1_337.0,
-            z: 24_200.0,
-        };
-
-        let hash = grid.coords_to_linear_hash(point);
-        let (x, y, z) = unflatten_hash(hash, grid.dim);
-
-        assert!(x < grid.dim && y < grid.dim && z < grid.dim);
-    }
 }
@@ -0,0 +1,325 @@
+//! On-disk copies of the last good catalog from each source, so the service
+//! starts with data even when its sources are unreachable.
+//!
+//! Each source gets one JSON file, named after a hash of the source, holding
+//! the fetch metadata and the records as OMM JSON. Files are replaced by
+//! rename so a crash never leaves a torn cache behind.
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use openastroviz_core::omm::{self, OmmFormat};
+use serde::{Deserialize, Serialize};
+
+use crate::tle::{parse_omm_catalog, OrbitalRecord, ParseMode};
+
+/// The cache section of the daemon config file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    /// Directory the cached catalogs are kept in.
+    pub dir: PathBuf,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            dir: default_dir(
+                env::var_os("XDG_CACHE_HOME").map(PathBuf::from),
+                env::var_os("HOME").map(PathBuf::from),
+            ),
+        }
+    }
+}
+
+/// `$XDG_CACHE_HOME/openastrovizd`, else `~/.cache/openastrovizd`, so the
+/// cache survives the reboots that clear the temp dir. The temp dir is the
+/// last resort when there is no home directory.
+fn default_dir(xdg_cache_home: Option<PathBuf>, home: Option<PathBuf>) -> PathBuf {
+    if let Some(dir) = xdg_cache_home.filter(|dir| dir.is_absolute()) {
+        return dir.join("openastrovizd");
+    }
+    match home.filter(|dir| dir.is_absolute()) {
+        Some(home) => home.join(".cache").join("openastrovizd"),
+        None => env::temp_dir().join("openastrovizd-cache"),
+    }
+}
+
+/// Where and when a cached catalog was fetched.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheMeta {
+    /// The source the catalog came from, as a URL or `file:`/`directory:`
+    /// path.
+    pub source: String,
+    /// When the catalog was downloaded, or last modified for local sources.
+    pub fetched_at: DateTime<Utc>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub objects: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    meta: CacheMeta,
+    omm: serde_json::Value,
+}
+
+/// A directory of cached catalogs, one per source.
+#[derive(Debug, Clone)]
+pub struct CatalogCache {
+    dir: PathBuf,
+}
+
+impl CatalogCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Replace the cached catalog for `meta.source`.
+    pub fn store(&self, meta: &CacheMeta, records: &[OrbitalRecord]) -> io::Result<()> {
+        let omm = omm::write(
+            records.iter().map(|record| record.propagator.elements()),
+            OmmFormat::Json,
+        );
+        let file = CacheFile {
+            meta: meta.clone(),
+            omm: serde_json::from_str(&omm).map_err(io::Error::other)?,
+        };
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(&meta.source);
+        let partial = path.with_extension("json.partial");
+        fs::write(
+            &partial,
+            serde_json::to_vec(&file).map_err(io::Error::other)?,
+        )?;
+        fs::rename(partial, path)
+    }
+
+    /// The cached catalog for `source`, if there is a readable one.
+    pub fn load(&self, source: &str) -> io::Result<Option<(CacheMeta, Vec<OrbitalRecord>)>> {
+        let file = match read_cache_file(&self.path(source)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let parsed = parse_omm_catalog(&file.omm.to_string(), OmmFormat::Json, ParseMode::Lenient)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(Some((file.meta, parsed.records)))
+    }
+
+    /// Metadata of every cached catalog, oldest fetch first.
+    pub fn entries(&self) -> io::Result<Vec<CacheMeta>> {
+        let mut entries = Vec::new();
+        let listing = match fs::read_dir(&self.dir) {
+            Ok(listing) => listing,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(entries),
+            Err(e) => return Err(e),
+        };
+        for entry in listing {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                // A foreign or damaged file is not a reason to fail.
+                if let Ok(file) = read_cache_file(&path) {
+                    entries.push(file.meta);
+                }
+            }
+        }
+        entries.sort_by_key(|meta| meta.fetched_at);
+        Ok(entries)
+    }
+
+    /// A one-line description of the cache for `daemon status`, or `None`
+    /// when nothing is cached yet.
+    pub fn summary(&self, now: DateTime<Utc>) -> io::Result<Option<String>> {
+        let entries = self.entries()?;
+        let Some(oldest) = entries.first() else {
+            return Ok(None);
+        };
+        let objects: usize = entries.iter().map(|meta| meta.objects).sum();
+        Ok(Some(format!(
+            "Cached catalog: {objects} objects from {} source(s), oldest fetched {} ago",
+            entries.len(),
+            format_age(now - oldest.fetched_at)
+        )))
+    }
+
+    fn path(&self, source: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}.json", fnv1a(source.as_bytes())))
+    }
+}
+
+fn read_cache_file(path: &Path) -> io::Result<CacheFile> {
+    let text = fs::read_to_string(path)?;
+    serde_json::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Render an age to the minute, e.g. `2d 3h` or `5h 12m`.
+pub fn format_age(age: chrono::Duration) -> String {
+    let minutes = age.num_minutes().max(0);
+    let (days, hours) = (minutes / (24 * 60), minutes / 60 % 24);
+    match (days, hours) {
+        (0, 0) => format!("{minutes}m"),
+        (0, _) => format!("{hours}h {}m", minutes % 60),
+        _ => format!("{days}d {hours}h"),
+    }
+}
+
+/// 64-bit FNV-1a, a hash that stays stable across builds for file names.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, ISS, VANGUARD};
+    use crate::tle::parse_catalog_with;
+
+    /// The ISS, and VANGUARD 1 without its name line.
+    fn catalog() -> String {
+        format!("{ISS}{}", VANGUARD.split_once('\n').unwrap().1)
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        test_util::scratch_dir("cache", name)
+    }
+
+    fn meta(source: &str, fetched_at: &str) -> CacheMeta {
+        CacheMeta {
+            source: source.to_string(),
+            fetched_at: fetched_at.parse().unwrap(),
+            etag: Some("\"abc123\"".to_string()),
+            last_modified: None,
+            objects: 2,
+        }
+    }
+
+    #[test]
+    fn defaults_to_the_user_cache_dir() {
+        let home = Some(PathBuf::from("/home/astro"));
+        assert_eq!(
+            default_dir(Some(PathBuf::from("/var/cache/astro")), home.clone()),
+            PathBuf::from("/var/cache/astro/openastrovizd")
+        );
+        // XDG paths must be absolute; relative ones are ignored.
+        assert_eq!(
+            default_dir(Some(PathBuf::from("cache")), home),
+            PathBuf::from("/home/astro/.cache/openastrovizd")
+        );
+        assert_eq!(
+            default_dir(None, None),
+            env::temp_dir().join("openastrovizd-cache")
+        );
+    }
+
+    #[test]
+    fn round_trips_records_and_metadata() {
+        let dir = scratch_dir("round-trip");
+        let cache = CatalogCache::new(&dir);
+        let records = parse_catalog_with(&catalog(), ParseMode::Lenient)
+            .unwrap()
+            .records;
+        let meta = meta("https://mirror.example/gp.json", "2026-10-18T06:00:00Z");
+        assert!(cache.load(&meta.source).unwrap().is_none());
+
+        cache.store(&meta, &records).unwrap();
+        let (cached_meta, cached) = cache.load(&meta.source).unwrap().unwrap();
+        assert_eq!(cached_meta, meta);
+        let names: Vec<(u32, &str)> = cached
+            .iter()
+            .map(|record| (record.norad_id.get(), record.name.as_str()))
+            .collect();
+        assert_eq!(names, [(25544, "ISS (ZARYA)"), (5, "5")]);
+        for (cached, original) in cached.iter().zip(&records) {
+            assert_eq!(
+                cached.epoch_state.position_km,
+                original.epoch_state.position_km
+            );
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_one_entry_per_source() {
+        let dir = scratch_dir("entries");
+        let cache = CatalogCache::new(&dir);
+        let records = parse_catalog_with(&catalog(), ParseMode::Lenient)
+            .unwrap()
+            .records;
+        cache
+            .store(&meta("file:/srv/a.tle", "2026-10-18T06:00:00Z"), &records)
+            .unwrap();
+        cache
+            .store(&meta("https://b.example", "2026-10-17T06:00:00Z"), &records)
+            .unwrap();
+        cache
+            .store(&meta("file:/srv/a.tle", "2026-10-18T07:00:00Z"), &records)
+            .unwrap();
+        fs::write(dir.join("notes.json"), "not a cache file").unwrap();
+
+        let sources: Vec<(String, String)> = cache
+            .entries()
+            .unwrap()
+            .into_iter()
+            .map(|meta| (meta.source, meta.fetched_at.to_rfc3339()))
+            .collect();
+        assert_eq!(
+            sources,
+            [
+                (
+                    "https://b.example".into(),
+                    "2026-10-17T06:00:00+00:00".into()
+                ),
+                ("file:/srv/a.tle".into(), "2026-10-18T07:00:00+00:00".into()),
+            ]
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn summarises_the_oldest_entry() {
+        let dir = scratch_dir("summary");
+        let cache = CatalogCache::new(&dir);
+        let now = "2026-10-18T12:00:00Z".parse().unwrap();
+        assert_eq!(cache.summary(now).unwrap(), None);
+        let records = parse_catalog_with(&catalog(), ParseMode::Lenient)
+            .unwrap()
+            .records;
+        for (source, fetched_at) in [
+            ("https://a.example", "2026-10-18T06:48:00Z"),
+            ("https://b.example", "2026-10-18T11:00:00Z"),
+        ] {
+            cache.store(&meta(source, fetched_at), &records).unwrap();
+        }
+        assert_eq!(
+            cache.summary(now).unwrap().unwrap(),
+            "Cached catalog: 4 objects from 2 source(s), oldest fetched 5h 12m ago"
+        );
+        assert_eq!(
+            format_age(chrono::Duration::minutes(3 * 24 * 60 + 70)),
+            "3d 1h"
+        );
+        assert_eq!(format_age(chrono::Duration::seconds(59)), "0m");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_damaged_files() {
+        let dir = scratch_dir("damaged");
+        let cache = CatalogCache::new(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(cache.path("https://a.example"), "{\"meta\": ").unwrap();
+        let err = cache.load("https://a.example").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(CatalogCache::new(dir.join("missing"))
+            .entries()
+            .unwrap()
+            .is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
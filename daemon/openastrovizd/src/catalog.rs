@@ -0,0 +1,168 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use clap::ValueEnum;
+use openastroviz_core::omm::{self, OmmFormat};
+use openastroviz_core::tle::{TleValidator, ValidationReport};
+use openastroviz_core::NoradId;
+use serde::Serialize;
+
+use crate::tle::{parse_catalog, OrbitalRecord};
+
+/// Encodings `catalog convert` can write.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// 3-line TLE text
+    Tle,
+    /// OMM XML
+    Xml,
+    /// OMM KVN
+    Kvn,
+    /// OMM JSON in the CelesTrak layout
+    Json,
+}
+
+/// Machine-readable result of `catalog validate`.
+#[derive(Debug, Serialize)]
+struct Report<'a> {
+    file: String,
+    objects: usize,
+    valid: bool,
+    problems: Vec<Problem<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Problem<'a> {
+    line: usize,
+    column: usize,
+    code: &'static str,
+    message: &'a str,
+}
+
+/// Validate a TLE catalog file and render the report as JSON.
+///
+/// Returns the report and whether the catalog passed; only unreadable files
+/// are errors.
+pub fn validate_file(path: &Path) -> Result<(String, bool), String> {
+    let body = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    let report = TleValidator::default().validate_catalog(&body);
+    Ok((render(path, &report), report.is_valid()))
+}
+
+fn render(path: &Path, report: &ValidationReport) -> String {
+    let report = Report {
+        file: path.display().to_string(),
+        objects: report.objects,
+        valid: report.is_valid(),
+        problems: report
+            .diagnostics
+            .iter()
+            .map(|diagnostic| Problem {
+                line: diagnostic.line,
+                column: diagnostic.column,
+                code: diagnostic.kind.code(),
+                message: &diagnostic.message,
+            })
+            .collect(),
+    };
+    serde_json::to_string_pretty(&report).expect("report serializes")
+}
+
+/// Read a catalog in any supported format and write the objects in
+/// `norad_ids` (or all of them, if empty) in `format`.
+pub fn convert_file(
+    path: &Path,
+    format: ExportFormat,
+    norad_ids: &[NoradId],
+) -> Result<String, String> {
+    let body = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    let records: Vec<OrbitalRecord> = parse_catalog(&body)?
+        .records
+        .into_iter()
+        .filter(|record| norad_ids.is_empty() || norad_ids.contains(&record.norad_id))
+        .collect();
+    convert(&records, format)
+}
+
+fn convert(records: &[OrbitalRecord], format: ExportFormat) -> Result<String, String> {
+    let elements = records.iter().map(|record| record.propagator.elements());
+    let omm_format = match format {
+        ExportFormat::Tle => {
+            let mut out = String::new();
+            for record in records {
+                let (line1, line2) = record
+                    .propagator
+                    .to_tle()
+                    .map_err(|e| format!("cannot write `{}` as a TLE: {e}", record.name))?;
+                let _ = writeln!(out, "{}\n{line1}\n{line2}", record.name);
+            }
+            return Ok(out);
+        }
+        ExportFormat::Xml => OmmFormat::Xml,
+        ExportFormat::Kvn => OmmFormat::Kvn,
+        ExportFormat::Json => OmmFormat::Json,
+    };
+    Ok(omm::write(elements, omm_format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_each_problem_with_its_position() {
+        let src = "ISS (ZARYA)\n1 25544U 98067A   20194.88612269 -.00002218  00000-0 -31515-4 0  9990\n2 25545  51.6461 221.2784 0001413  89.1723 280.4612 15.49507896236009\n";
+        let report = TleValidator::default().validate_catalog(src);
+        let json: serde_json::Value =
+            serde_json::from_str(&render(Path::new("feed.tle"), &report)).unwrap();
+        assert_eq!(json["file"], "feed.tle");
+        assert_eq!(json["objects"], 1);
+        assert_eq!(json["valid"], false);
+        let problems = json["problems"].as_array().unwrap();
+        let codes: Vec<(u64, u64, &str)> = problems
+            .iter()
+            .map(|problem| {
+                (
+                    problem["line"].as_u64().unwrap(),
+                    problem["column"].as_u64().unwrap(),
+                    problem["code"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(codes, [(2, 69, "checksum"), (3, 3, "catalog-mismatch")]);
+        assert_eq!(problems[0]["message"], "checksum `0` should be 2");
+    }
+
+    fn bundled_catalog() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/iss.tle")
+    }
+
+    #[test]
+    fn converts_between_tle_and_omm() {
+        let tle = std::fs::read_to_string(bundled_catalog()).unwrap();
+        for format in [ExportFormat::Xml, ExportFormat::Kvn, ExportFormat::Json] {
+            let omm = convert_file(&bundled_catalog(), format, &[]).unwrap();
+            let records = parse_catalog(&omm).unwrap().records;
+            assert_eq!(convert(&records, ExportFormat::Tle).unwrap(), tle);
+        }
+    }
+
+    #[test]
+    fn filters_by_catalog_number() {
+        let iss = NoradId::new(25544).unwrap();
+        let json = convert_file(&bundled_catalog(), ExportFormat::Json, &[iss]).unwrap();
+        assert!(json.contains("\"NORAD_CAT_ID\": 25544"));
+        let vanguard = NoradId::new(5).unwrap();
+        let json = convert_file(&bundled_catalog(), ExportFormat::Json, &[vanguard]).unwrap();
+        assert_eq!(json, "[]\n");
+    }
+
+    #[test]
+    fn passes_the_bundled_catalog() {
+        let (json, valid) = validate_file(&bundled_catalog()).unwrap();
+        assert!(valid, "{json}");
+        assert!(json.contains("\"problems\": []"));
+    }
+}
@@ -0,0 +1,538 @@
+//! The TOML config file passed to the service with `--config`, and the
+//! `OPENASTROVIZD_<SECTION>_<KEY>` environment variables that override it.
+use std::env;
+use std::fs;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::backend::Backend;
+use crate::cache::CacheConfig;
+use crate::logging::LogConfig;
+use crate::source::CatalogConfig;
+
+/// Settings read from the service config file. Every section is optional.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServiceConfig {
+    pub server: ServerConfig,
+    pub compute: ComputeConfig,
+    pub catalog: CatalogConfig,
+    pub cache: CacheConfig,
+    pub daemon: ProcessConfig,
+    pub logging: LogConfig,
+    pub conjunction: ConjunctionConfig,
+}
+
+/// The server section: where clients reach the service.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Address the WebSocket and HTTP API listen on.
+    pub listen: SocketAddr,
+    /// How often the catalog is propagated and streamed to clients.
+    pub tick_ms: u64,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            listen: (Ipv4Addr::LOCALHOST, 8000).into(),
+            tick_ms: 1000,
+        }
+    }
+}
+
+/// The compute section: which backend propagates the catalog.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ComputeConfig {
+    pub backend: Backend,
+}
+
+impl Default for ComputeConfig {
+    fn default() -> Self {
+        Self {
+            backend: Backend::Cpu,
+        }
+    }
+}
+
+/// The daemon section: how `start`, `status` and `stop` find the service.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProcessConfig {
+    /// File holding the pid of the running service.
+    pub pid_file: PathBuf,
+}
+
+impl Default for ProcessConfig {
+    fn default() -> Self {
+        Self {
+            pid_file: env::temp_dir().join("openastrovizd.pid"),
+        }
+    }
+}
+
+/// The conjunction section: which close approaches are reported.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConjunctionConfig {
+    /// Miss distance below which an approach is reported.
+    pub threshold_km: f64,
+    /// Most approaches reported per frame, closest first.
+    pub top_n: usize,
+}
+
+impl Default for ConjunctionConfig {
+    fn default() -> Self {
+        Self {
+            threshold_km: 5.0,
+            top_n: 20,
+        }
+    }
+}
+
+/// What an overridable setting holds, to parse its environment variable.
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Text,
+    Integer,
+    Number,
+}
+
+impl Kind {
+    fn parse(self, raw: &str) -> Option<toml::Value> {
+        match self {
+            Kind::Text => Some(toml::Value::String(raw.to_string())),
+            Kind::Integer => raw.trim().parse().ok().map(toml::Value::Integer),
+            Kind::Number => raw.trim().parse().ok().map(toml::Value::Float),
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Kind::Text => "text",
+            Kind::Integer => "a whole number",
+            Kind::Number => "a number",
+        }
+    }
+}
+
+/// The settings that can be overridden from the environment, as
+/// `(section, key)`. Catalog sources are lists and can only be set in the
+/// file.
+const OVERRIDES: &[(&str, &str, Kind)] = &[
+    ("server", "listen", Kind::Text),
+    ("server", "tick_ms", Kind::Integer),
+    ("compute", "backend", Kind::Text),
+    ("catalog", "poll_interval_secs", Kind::Integer),
+    ("catalog", "http_interval_secs", Kind::Integer),
+    ("catalog", "min_fetch_spacing_secs", Kind::Integer),
+    ("catalog", "retry_initial_secs", Kind::Integer),
+    ("catalog", "retry_max_secs", Kind::Integer),
+    ("catalog", "connect_timeout_secs", Kind::Integer),
+    ("catalog", "request_timeout_secs", Kind::Integer),
+    ("cache", "dir", Kind::Text),
+    ("daemon", "pid_file", Kind::Text),
+    ("logging", "level", Kind::Text),
+    ("logging", "file", Kind::Text),
+    ("conjunction", "threshold_km", Kind::Number),
+    ("conjunction", "top_n", Kind::Integer),
+];
+
+/// The environment variable overriding `section.key`, e.g.
+/// `OPENASTROVIZD_SERVER_LISTEN`.
+pub fn override_var(section: &str, key: &str) -> String {
+    format!("OPENASTROVIZD_{section}_{key}").to_uppercase()
+}
+
+impl ServiceConfig {
+    /// Read the config file if one was given, apply environment overrides
+    /// and check the result. Missing Space-Track credentials are not an
+    /// error here, see [`require_credentials`](Self::require_credentials).
+    pub fn load(path: Option<&Path>) -> io::Result<Self> {
+        Self::load_with(path, &|name| env::var(name).ok()).map(|(config, _)| config)
+    }
+
+    /// [`load`](Self::load) with the environment read through `var`, so
+    /// tests need not touch the process environment. Also returns the
+    /// override variables that were set, in the order they were applied.
+    ///
+    /// Relative paths, in the file or in overrides, are taken from the
+    /// directory holding the file.
+    pub fn load_with(
+        path: Option<&Path>,
+        var: &impl Fn(&str) -> Option<String>,
+    ) -> io::Result<(Self, Vec<String>)> {
+        let label = match path {
+            Some(path) => format!("Config file {}", path.display()),
+            None => String::from("Default config"),
+        };
+        let invalid = |detail: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{label} is invalid: {detail}"),
+            )
+        };
+        let (text, base) = match path {
+            Some(path) => (read(path)?, path.parent().unwrap_or_else(|| Path::new(""))),
+            None => (String::new(), Path::new("")),
+        };
+        let mut config: Self = toml::from_str(&text).map_err(|e| invalid(e.to_string()))?;
+
+        // Overrides are applied to the document rather than the parsed
+        // config, so each one is checked exactly as if it were in the file.
+        let mut document: toml::Table =
+            toml::from_str(&text).map_err(|e| invalid(e.to_string()))?;
+        let mut applied = Vec::new();
+        for &(section, key, kind) in OVERRIDES {
+            let name = override_var(section, key);
+            let Some(raw) = var(&name) else { continue };
+            let value = kind
+                .parse(&raw)
+                .ok_or_else(|| invalid(format!("{name}: `{raw}` is not {}", kind.describe())))?;
+            let table = document
+                .entry(section)
+                .or_insert_with(|| toml::Table::new().into());
+            if let Some(table) = table.as_table_mut() {
+                table.insert(key.to_string(), value);
+            }
+            config = toml::Value::Table(document.clone())
+                .try_into()
+                .map_err(|e| invalid(format!("{name}: {e}")))?;
+            applied.push(name);
+        }
+
+        for source in &mut config.catalog.sources {
+            source.fill_credentials(var);
+        }
+        let mut problems = Vec::new();
+        config.check(&mut problems);
+        if !problems.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{label} is invalid:\n  {}", problems.join("\n  ")),
+            ));
+        }
+
+        config.catalog.sources = config
+            .catalog
+            .sources
+            .into_iter()
+            .map(|source| source.resolve(base))
+            .collect();
+        config.cache.dir = base.join(&config.cache.dir);
+        config.daemon.pid_file = base.join(&config.daemon.pid_file);
+        config.logging.file = config.logging.file.map(|file| base.join(file));
+        Ok((config, applied))
+    }
+
+    /// Fail unless every Space-Track source has credentials. Only the
+    /// service and `config check` need them; commands that merely find the
+    /// pid file or cache do not.
+    pub fn require_credentials(&self) -> io::Result<()> {
+        let problems: Vec<String> = self
+            .catalog
+            .sources
+            .iter()
+            .enumerate()
+            .filter_map(|(i, source)| {
+                let e = source.require_credentials().err()?;
+                Some(format!("catalog.sources[{i}]: {e}"))
+            })
+            .collect();
+        if problems.is_empty() {
+            return Ok(());
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Missing credentials:\n  {}", problems.join("\n  ")),
+        ))
+    }
+
+    /// Settings the service accepts but will not honour as written, prefixed
+    /// by their config key.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.compute.backend != Backend::Cpu {
+            warnings.push(format!(
+                "compute.backend: the {} backend cannot propagate the live catalog yet; \
+                 the service uses cpu",
+                self.compute.backend
+            ));
+        }
+        warnings
+    }
+
+    /// Record every setting the service cannot run with, prefixed by its
+    /// config key.
+    fn check(&self, problems: &mut Vec<String>) {
+        if self.server.tick_ms == 0 {
+            problems.push(String::from("server.tick_ms: must be at least 1"));
+        }
+        self.catalog.check(problems);
+        let threshold = self.conjunction.threshold_km;
+        if !(threshold.is_finite() && threshold > 0.0) {
+            problems.push(format!(
+                "conjunction.threshold_km: must be a positive distance, not {threshold}"
+            ));
+        }
+        if self.conjunction.top_n == 0 {
+            problems.push(String::from("conjunction.top_n: must be at least 1"));
+        }
+    }
+}
+
+/// The report printed by `config check`: which file and overrides were
+/// used, then the effective configuration as TOML with secrets redacted.
+pub fn check(path: Option<&Path>) -> io::Result<String> {
+    let (config, applied) = ServiceConfig::load_with(path, &|name| env::var(name).ok())?;
+    config.require_credentials()?;
+    let mut report = match path {
+        Some(path) => format!("Config file {} is valid\n", path.display()),
+        None => String::from("No config file given; using the defaults\n"),
+    };
+    if !applied.is_empty() {
+        report.push_str(&format!("Overridden by {}\n", applied.join(", ")));
+    }
+    for warning in config.warnings() {
+        report.push_str(&format!("Warning: {warning}\n"));
+    }
+    let effective = toml::to_string(&config).map_err(io::Error::other)?;
+    report.push('\n');
+    report.push_str(&effective);
+    Ok(report)
+}
+
+fn read(path: &Path) -> io::Result<String> {
+    if !path.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Config file {} was not found", path.display()),
+        ));
+    }
+    fs::read_to_string(path).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("Config file {} could not be read: {e}", path.display()),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use std::collections::HashMap;
+
+    fn write_config(name: &str, text: &str) -> PathBuf {
+        let path = test_util::scratch_dir("config", name).join("daemon.toml");
+        fs::write(&path, text).unwrap();
+        path
+    }
+
+    fn load(
+        path: Option<&Path>,
+        vars: &[(&str, &str)],
+    ) -> io::Result<(ServiceConfig, Vec<String>)> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        ServiceConfig::load_with(path, &|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn reads_every_section() {
+        let path = write_config(
+            "sections",
+            r#"
+[server]
+listen = "0.0.0.0:9000"
+
+[compute]
+backend = "cuda"
+
+[catalog]
+poll_interval_secs = 10
+
+[[catalog.sources]]
+kind = "file"
+path = "active.tle"
+
+[cache]
+dir = "cache"
+
+[daemon]
+pid_file = "run/openastrovizd.pid"
+
+[logging]
+level = "debug"
+file = "daemon.log"
+
+[conjunction]
+threshold_km = 2.5
+top_n = 5
+"#,
+        );
+        let base = path.parent().unwrap();
+        let (config, applied) = load(Some(&path), &[]).unwrap();
+        assert!(applied.is_empty());
+        assert_eq!(config.server.listen, "0.0.0.0:9000".parse().unwrap());
+        assert_eq!(config.compute.backend, Backend::Cuda);
+        assert_eq!(config.catalog.poll_interval_secs, 10);
+        assert_eq!(config.catalog.http_interval_secs, 86_400);
+        assert_eq!(config.cache.dir, base.join("cache"));
+        assert_eq!(config.daemon.pid_file, base.join("run/openastrovizd.pid"));
+        assert_eq!(config.logging.level, crate::logging::LogLevel::Debug);
+        assert_eq!(config.logging.file, Some(base.join("daemon.log")));
+        assert_eq!(config.conjunction.threshold_km, 2.5);
+        assert_eq!(config.conjunction.top_n, 5);
+        fs::remove_dir_all(base).unwrap();
+    }
+
+    #[test]
+    fn environment_overrides_the_file() {
+        let path = write_config(
+            "overrides",
+            "[server]\nlisten = \"0.0.0.0:9000\"\n\n[catalog]\nretry_max_secs = 3600\n",
+        );
+        let (config, applied) = load(
+            Some(&path),
+            &[
+                ("OPENASTROVIZD_SERVER_LISTEN", "127.0.0.1:9100"),
+                ("OPENASTROVIZD_CATALOG_RETRY_MAX_SECS", "7200"),
+                ("OPENASTROVIZD_CONJUNCTION_THRESHOLD_KM", "1.5"),
+                ("OPENASTROVIZD_LOGGING_LEVEL", "warn"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(config.server.listen, "127.0.0.1:9100".parse().unwrap());
+        assert_eq!(config.catalog.retry_max_secs, 7200);
+        assert_eq!(config.conjunction.threshold_km, 1.5);
+        assert_eq!(config.logging.level, crate::logging::LogLevel::Warn);
+        assert_eq!(
+            applied,
+            [
+                "OPENASTROVIZD_SERVER_LISTEN",
+                "OPENASTROVIZD_CATALOG_RETRY_MAX_SECS",
+                "OPENASTROVIZD_LOGGING_LEVEL",
+                "OPENASTROVIZD_CONJUNCTION_THRESHOLD_KM",
+            ]
+        );
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn environment_applies_without_a_file() {
+        let (config, _) = load(None, &[("OPENASTROVIZD_COMPUTE_BACKEND", "cuda")]).unwrap();
+        assert_eq!(config.compute.backend, Backend::Cuda);
+        assert_eq!(config.server, ServerConfig::default());
+        assert_eq!(load(None, &[]).unwrap().0, ServiceConfig::default());
+    }
+
+    #[test]
+    fn names_the_bad_override() {
+        let err = load(
+            None,
+            &[("OPENASTROVIZD_CATALOG_POLL_INTERVAL_SECS", "soon")],
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Default config is invalid: OPENASTROVIZD_CATALOG_POLL_INTERVAL_SECS: `soon` is not a \
+             whole number"
+        );
+        let err = load(None, &[("OPENASTROVIZD_COMPUTE_BACKEND", "fpga")]).unwrap_err();
+        let message = err.to_string();
+        assert!(
+            message.contains("OPENASTROVIZD_COMPUTE_BACKEND"),
+            "{message}"
+        );
+        assert!(message.contains("fpga"), "{message}");
+    }
+
+    #[test]
+    fn points_at_the_bad_line() {
+        let path = write_config("syntax", "[server]\nlisten = \"localhost\"\n");
+        let message = load(Some(&path), &[]).unwrap_err().to_string();
+        assert!(message.contains("line 2"), "{message}");
+        assert!(message.contains("socket address"), "{message}");
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn lists_every_problem_with_its_key() {
+        let path = write_config(
+            "problems",
+            r#"
+[catalog]
+poll_interval_secs = 0
+retry_initial_secs = 7200
+retry_max_secs = 3600
+
+[[catalog.sources]]
+kind = "http"
+url = "ftp://mirror.example/active.tle"
+
+[[catalog.sources]]
+kind = "merged"
+sources = []
+
+[conjunction]
+threshold_km = -1.0
+"#,
+        );
+        let err = load(Some(&path), &[]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Config file {} is invalid:\n  \
+                 catalog.sources[0].url: `ftp://mirror.example/active.tle` is not an http:// or \
+                 https:// URL\n  \
+                 catalog.sources[1].sources: a merged source needs at least one source\n  \
+                 catalog.poll_interval_secs: must be at least 1\n  \
+                 catalog.retry_initial_secs: 7200 is longer than catalog.retry_max_secs (3600)\n  \
+                 conjunction.threshold_km: must be a positive distance, not -1",
+                path.display()
+            )
+        );
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn redacts_secrets_when_printed() {
+        let path = write_config(
+            "secrets",
+            "[[catalog.sources]]\nkind = \"spacetrack\"\nidentity = \"a@example.org\"\npassword = \"hunter2\"\n",
+        );
+        let (config, _) = load(Some(&path), &[]).unwrap();
+        let printed = toml::to_string(&config).unwrap();
+        assert!(printed.contains("password = \"***\""), "{printed}");
+        assert!(!printed.contains("hunter2"));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn loads_without_credentials_until_they_are_required() {
+        let path = write_config(
+            "credentials",
+            "[[catalog.sources]]\nkind = \"spacetrack\"\nidentity = \"a@example.org\"\n",
+        );
+        let (config, _) = load(Some(&path), &[]).unwrap();
+        let err = config.require_credentials().unwrap_err();
+        assert!(err.to_string().contains("catalog.sources[0]"), "{err}");
+
+        let (config, _) = load(
+            Some(&path),
+            &[("OPENASTROVIZD_SPACETRACK_PASSWORD", "hunter2")],
+        )
+        .unwrap();
+        config.require_credentials().unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,497 @@
+//! Conjunction screening for the orbital stream.
+//!
+//! Each frame the propagated objects are hashed into a voxel grid, and only
+//! pairs in the same or touching voxels are compared. For those the
+//! closest approach is found assuming straight-line relative motion over
+//! the span of time the frame stands for, so approaches that fall between
+//! two frames are not missed. Each frame's span starts where the previous
+//! one ended, however late it ran. The closest approaches under the
+//! threshold are reported with the frame whose span holds their time of
+//! closest approach, so each encounter is reported once.
+use std::ops::Range;
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::config::ConjunctionConfig;
+use crate::orbits::{CloseApproach, OrbitalObject};
+
+/// Half the edge of the cube the grid covers, centred on the Earth. It
+/// reaches past GEO; objects further out share the boundary voxels.
+const GRID_EXTENT_KM: f64 = 45_000.0;
+/// A bound on the speed of two objects relative to each other: a highly
+/// eccentric orbit near perigee, at up to about 10.5 km/s, meeting a
+/// retrograde low orbit at 7.8 km/s head-on, with some margin.
+pub const MAX_RELATIVE_SPEED_KPS: f64 = 20.0;
+/// The smallest voxel edge, which keeps the grid size within `u64` hashes.
+const MIN_VOXEL_KM: f64 = 10.0;
+/// How far back a late frame screens for the frames it replaced. Past this
+/// straight-line motion is a poor guide, and the gap goes unscreened.
+const MAX_LOOKBACK_S: f64 = 10.0;
+
+/// A cubic voxel grid over near-Earth space, hashed z-major.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GridConfig {
+    pub min: f64,
+    pub max: f64,
+    pub voxel_size: f64,
+    /// Voxels along each axis.
+    pub dim: u64,
+}
+
+impl GridConfig {
+    /// A grid from LEO to GEO with voxels `voxel_size` km on a side. Two
+    /// points closer than that always fall in the same or touching voxels.
+    pub fn new(voxel_size: f64) -> Self {
+        let min = -GRID_EXTENT_KM;
+        let max = GRID_EXTENT_KM;
+        let dim = ((max - min) / voxel_size).ceil() as u64;
+        Self {
+            min,
+            max,
+            voxel_size,
+            dim,
+        }
+    }
+
+    pub fn coords_to_linear_hash(&self, point: [f64; 3]) -> u64 {
+        self.hash(self.voxel(point))
+    }
+
+    fn voxel(&self, point: [f64; 3]) -> [u64; 3] {
+        point.map(|axis| self.quantize_axis(axis))
+    }
+
+    fn hash(&self, [x, y, z]: [u64; 3]) -> u64 {
+        x + self.dim * (y + self.dim * z)
+    }
+
+    fn quantize_axis(&self, axis: f64) -> u64 {
+        let clamped = axis.clamp(self.min, self.max - f64::EPSILON);
+        let normalized = (clamped - self.min) / self.voxel_size;
+        (normalized.floor() as u64).min(self.dim - 1)
+    }
+
+    /// Every pair of indices into `positions` in the same or touching
+    /// voxels, each once with the lower index first.
+    pub fn candidate_pairs(&self, positions: &[[f64; 3]]) -> Vec<(usize, usize)> {
+        let mut keyed: Vec<(u64, usize)> = positions
+            .iter()
+            .enumerate()
+            .map(|(i, &point)| (self.coords_to_linear_hash(point), i))
+            .collect();
+        keyed.sort_unstable();
+
+        // Runs of objects sharing a voxel, as (hash, start, end) over `keyed`.
+        let mut voxels: Vec<(u64, usize, usize)> = Vec::new();
+        let mut i = 0;
+        while i < keyed.len() {
+            let hash = keyed[i].0;
+            let start = i;
+            while i < keyed.len() && keyed[i].0 == hash {
+                i += 1;
+            }
+            voxels.push((hash, start, i));
+        }
+
+        let ordered = |a: usize, b: usize| (a.min(b), a.max(b));
+        let mut pairs = Vec::new();
+        for &(hash, start, end) in &voxels {
+            for a in start..end {
+                for b in a + 1..end {
+                    pairs.push(ordered(keyed[a].1, keyed[b].1));
+                }
+            }
+            // Half of the 26 neighbours, so each touching pair of voxels is
+            // visited from one side only.
+            let [x, y, z] = self.voxel(positions[keyed[start].1]);
+            for (dx, dy, dz) in FORWARD_NEIGHBOURS {
+                let Some(neighbour) = self.offset([x, y, z], [dx, dy, dz]) else {
+                    continue;
+                };
+                let neighbour = self.hash(neighbour);
+                debug_assert!(neighbour > hash);
+                let Ok(found) = voxels.binary_search_by_key(&neighbour, |&(hash, _, _)| hash)
+                else {
+                    continue;
+                };
+                let (_, nstart, nend) = voxels[found];
+                for a in start..end {
+                    for b in nstart..nend {
+                        pairs.push(ordered(keyed[a].1, keyed[b].1));
+                    }
+                }
+            }
+        }
+        pairs
+    }
+
+    fn offset(&self, voxel: [u64; 3], delta: [i64; 3]) -> Option<[u64; 3]> {
+        let mut moved = [0; 3];
+        for axis in 0..3 {
+            let index = voxel[axis].checked_add_signed(delta[axis])?;
+            if index >= self.dim {
+                return None;
+            }
+            moved[axis] = index;
+        }
+        Some(moved)
+    }
+}
+
+/// The neighbours whose hash is larger than the voxel's own.
+const FORWARD_NEIGHBOURS: [(i64, i64, i64); 13] = [
+    (1, 0, 0),
+    (-1, 1, 0),
+    (0, 1, 0),
+    (1, 1, 0),
+    (-1, -1, 1),
+    (0, -1, 1),
+    (1, -1, 1),
+    (-1, 0, 1),
+    (0, 0, 1),
+    (1, 0, 1),
+    (-1, 1, 1),
+    (0, 1, 1),
+    (1, 1, 1),
+];
+
+/// Finds the closest approaches among the objects of a frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Screener {
+    threshold_km: f64,
+    top_n: usize,
+    /// How far past a frame's epoch approaches are sought: half the time
+    /// between frames.
+    half_window_s: f64,
+}
+
+impl Screener {
+    /// A screener for frames `frame_interval` apart.
+    pub fn new(config: &ConjunctionConfig, frame_interval: Duration) -> Self {
+        Self {
+            threshold_km: config.threshold_km,
+            top_n: config.top_n,
+            half_window_s: frame_interval.as_secs_f64() / 2.0,
+        }
+    }
+
+    /// The span of time the frame at `epoch` stands for: from where the
+    /// previous frame's span ended, or half an interval back for the first
+    /// frame, to half an interval ahead.
+    pub fn window(
+        &self,
+        epoch: DateTime<Utc>,
+        previous_end: Option<DateTime<Utc>>,
+    ) -> Range<DateTime<Utc>> {
+        let seconds = |s: f64| TimeDelta::microseconds((s * 1e6).round() as i64);
+        let end = epoch + seconds(self.half_window_s);
+        let earliest = epoch - seconds(self.half_window_s.max(MAX_LOOKBACK_S));
+        let start = match previous_end {
+            Some(previous_end) if previous_end < end => previous_end.max(earliest),
+            _ => epoch - seconds(self.half_window_s),
+        };
+        start..end
+    }
+
+    /// The approaches among `objects` at `epoch` closer than the threshold
+    /// with their time of closest approach in `window`, closest first and at
+    /// most `top_n` of them.
+    pub fn screen(
+        &self,
+        epoch: DateTime<Utc>,
+        window: Range<DateTime<Utc>>,
+        objects: &[OrbitalObject],
+    ) -> Vec<CloseApproach> {
+        if self.top_n == 0 || window.is_empty() {
+            return Vec::new();
+        }
+        let offset = |time: DateTime<Utc>| (time - epoch).as_seconds_f64();
+        let span = offset(window.start)..offset(window.end);
+        // Pairs that close to the threshold within the window start out at
+        // most this far apart.
+        let lookaround_s = span.start.abs().max(span.end.abs());
+        let reach = self.threshold_km + MAX_RELATIVE_SPEED_KPS * lookaround_s;
+        let positions: Vec<[f64; 3]> = objects.iter().map(|object| object.position).collect();
+        let mut approaches: Vec<CloseApproach> = GridConfig::new(reach.max(MIN_VOXEL_KM))
+            .candidate_pairs(&positions)
+            .into_iter()
+            .filter_map(|(a, b)| {
+                let (primary, secondary) = if objects[a].norad_id <= objects[b].norad_id {
+                    (&objects[a], &objects[b])
+                } else {
+                    (&objects[b], &objects[a])
+                };
+                self.approach(epoch, &span, primary, secondary)
+            })
+            .collect();
+        approaches.sort_by(|a, b| {
+            a.miss_distance_km
+                .total_cmp(&b.miss_distance_km)
+                .then_with(|| a.primary_id.cmp(&b.primary_id))
+                .then_with(|| a.secondary_id.cmp(&b.secondary_id))
+        });
+        approaches.truncate(self.top_n);
+        approaches
+    }
+
+    /// The closest approach of two objects moving in straight lines, if it
+    /// falls within `span`, in seconds from `epoch`, and is under the
+    /// threshold. Each encounter is reported by exactly one frame, and pairs
+    /// that keep their distance, such as docked vehicles, by none.
+    fn approach(
+        &self,
+        epoch: DateTime<Utc>,
+        span: &Range<f64>,
+        primary: &OrbitalObject,
+        secondary: &OrbitalObject,
+    ) -> Option<CloseApproach> {
+        let r = sub(secondary.position, primary.position);
+        let v = sub(secondary.velocity, primary.velocity);
+        let speed_squared = dot(v, v);
+        if speed_squared == 0.0 {
+            return None;
+        }
+        let t = -dot(r, v) / speed_squared;
+        if !span.contains(&t) {
+            return None;
+        }
+        let (_, miss_distance_km) = linear_approach(r, v, t.abs());
+        if miss_distance_km >= self.threshold_km {
+            return None;
+        }
+        Some(CloseApproach {
+            time: epoch + TimeDelta::microseconds((t * 1e6).round() as i64),
+            primary_id: primary.id.clone(),
+            secondary_id: secondary.id.clone(),
+            miss_distance_km,
+            relative_velocity_kps: dot(v, v).sqrt(),
+        })
+    }
+}
+
+impl Default for Screener {
+    fn default() -> Self {
+        Self::new(&ConjunctionConfig::default(), Duration::from_secs(1))
+    }
+}
+
+/// When, in seconds from now and at most `half_window_s` either way, a pair
+/// at relative position `r` (km) moving at relative velocity `v` (km/s) in a
+/// straight line is closest, and how far apart it is then.
+pub fn linear_approach(r: [f64; 3], v: [f64; 3], half_window_s: f64) -> (f64, f64) {
+    let speed_squared = dot(v, v);
+    let t = if speed_squared > 0.0 {
+        (-dot(r, v) / speed_squared).clamp(-half_window_s, half_window_s)
+    } else {
+        0.0
+    };
+    let miss = [r[0] + v[0] * t, r[1] + v[1] * t, r[2] + v[2] * t];
+    (t, dot(miss, miss).sqrt())
+}
+
+pub fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orbits::{ObjectHealth, ObjectKind};
+
+    fn object(norad_id: u32, position: [f64; 3], velocity: [f64; 3]) -> OrbitalObject {
+        let now = Utc::now();
+        OrbitalObject {
+            id: norad_id.to_string(),
+            norad_id,
+            name: format!("OBJECT {norad_id}"),
+            kind: ObjectKind::Satellite,
+            position,
+            velocity,
+            health: ObjectHealth::from_element_age(now, now),
+        }
+    }
+
+    fn brute_force(positions: &[[f64; 3]], within: f64) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        for a in 0..positions.len() {
+            for b in a + 1..positions.len() {
+                let r = sub(positions[a], positions[b]);
+                if dot(r, r).sqrt() < within {
+                    pairs.push((a, b));
+                }
+            }
+        }
+        pairs
+    }
+
+    #[test]
+    fn hash_round_trip_is_stable() {
+        let grid = GridConfig::new(250.0);
+        let voxel = grid.voxel([42.0, -1_337.0, 24_200.0]);
+        let hash = grid.hash(voxel);
+        let dim = grid.dim;
+        assert_eq!([hash % dim, hash / dim % dim, hash / (dim * dim)], voxel);
+        assert_eq!(grid.voxel([1e6, -1e6, 0.0]), [dim - 1, 0, dim / 2]);
+    }
+
+    #[test]
+    fn candidate_pairs_cover_every_close_pair_once() {
+        let grid = GridConfig::new(100.0);
+        // A deterministic cloud dense enough to fill neighbouring voxels.
+        let positions: Vec<[f64; 3]> = (0..600)
+            .map(|i| {
+                let t = i as f64 * 0.37;
+                [
+                    7_000.0 + (t * 1.3).sin() * 400.0,
+                    (t * 0.7).cos() * 400.0,
+                    (t * 2.1).sin() * 400.0,
+                ]
+            })
+            .collect();
+        let mut pairs = grid.candidate_pairs(&positions);
+        pairs.sort_unstable();
+        let unique = pairs.len();
+        pairs.dedup();
+        assert_eq!(pairs.len(), unique, "a pair was listed twice");
+        let close = brute_force(&positions, 100.0);
+        assert!(!close.is_empty());
+        for pair in close {
+            assert!(pairs.binary_search(&pair).is_ok(), "missed {pair:?}");
+        }
+        assert!(pairs.len() < positions.len() * (positions.len() - 1) / 2);
+    }
+
+    #[test]
+    fn finds_crossings_between_frames() {
+        let epoch = Utc::now();
+        let screener = Screener::new(
+            &ConjunctionConfig {
+                threshold_km: 5.0,
+                top_n: 20,
+            },
+            Duration::from_secs(2),
+        );
+        // Head-on at 15 km/s, 0.5 s from meeting with a 1 km offset: the
+        // frame itself sees them 7.5 km apart.
+        let objects = [
+            object(1, [7_000.0, -3.75, 0.0], [0.0, 7.5, 0.0]),
+            object(2, [7_000.0, 3.75, 1.0], [0.0, -7.5, 0.0]),
+            // Parallel and 4 km away, like a docked vehicle: never closer
+            // than now, so never reported.
+            object(3, [-7_000.0, 0.0, 0.0], [0.0, 7.5, 0.0]),
+            object(4, [-7_004.0, 0.0, 0.0], [0.0, 7.5, 0.0]),
+            // Too far to pass within the threshold.
+            object(5, [0.0, 7_000.0, 0.0], [7.5, 0.0, 0.0]),
+            object(6, [0.0, 7_000.0, 40.0], [-7.5, 0.0, 0.0]),
+        ];
+        let approaches = screener.screen(epoch, screener.window(epoch, None), &objects);
+        assert_eq!(approaches.len(), 1);
+
+        assert_eq!(approaches[0].primary_id, "1");
+        assert_eq!(approaches[0].secondary_id, "2");
+        assert!((approaches[0].miss_distance_km - 1.0).abs() < 1e-9);
+        assert!((approaches[0].relative_velocity_kps - 15.0).abs() < 1e-9);
+        assert_eq!(approaches[0].time, epoch + TimeDelta::milliseconds(500));
+    }
+
+    #[test]
+    fn reports_each_encounter_in_one_frame() {
+        let epoch = Utc::now();
+        let screener = Screener::new(
+            &ConjunctionConfig {
+                threshold_km: 5.0,
+                top_n: 20,
+            },
+            Duration::from_secs(2),
+        );
+        // Head-on at 15 km/s with a 1 km offset, meeting 3 s after `epoch`:
+        // on the boundary between the frames at 2 s and 4 s.
+        let mut previous_end = None;
+        let frames: Vec<Vec<CloseApproach>> = (-5..=10)
+            .map(|frame| crossing_frame(&screener, epoch, frame * 2, &mut previous_end))
+            .collect();
+        let reported: Vec<&CloseApproach> = frames.iter().flatten().collect();
+        assert_eq!(reported.len(), 1);
+        assert_eq!(reported[0].time, epoch + TimeDelta::seconds(3));
+        // The half-open window puts it in the later frame.
+        assert_eq!(frames[5 + 2].len(), 1);
+    }
+
+    #[test]
+    fn a_late_frame_screens_the_frames_it_replaced() {
+        let epoch = Utc::now();
+        let screener = Screener::new(
+            &ConjunctionConfig {
+                threshold_km: 5.0,
+                top_n: 20,
+            },
+            Duration::from_secs(2),
+        );
+        // The frames at 2 s and 4 s never run, so the one at 6 s covers the
+        // encounter at 3 s.
+        let mut previous_end = None;
+        let frames: Vec<Vec<CloseApproach>> = [0, 6, 8]
+            .into_iter()
+            .map(|second| crossing_frame(&screener, epoch, second, &mut previous_end))
+            .collect();
+        assert!(frames[0].is_empty());
+        assert_eq!(frames[1].len(), 1);
+        assert_eq!(frames[1][0].time, epoch + TimeDelta::seconds(3));
+        assert!(frames[2].is_empty());
+    }
+
+    /// The frame `second`s after `epoch` of a head-on crossing at 15 km/s
+    /// with a 1 km offset, 3 s after `epoch`.
+    fn crossing_frame(
+        screener: &Screener,
+        epoch: DateTime<Utc>,
+        second: i64,
+        previous_end: &mut Option<DateTime<Utc>>,
+    ) -> Vec<CloseApproach> {
+        let y = 7.5 * (second as f64 - 3.0);
+        let objects = [
+            object(1, [7_000.0, y, 0.0], [0.0, 7.5, 0.0]),
+            object(2, [7_000.0, -y, 1.0], [0.0, -7.5, 0.0]),
+        ];
+        let frame_epoch = epoch + TimeDelta::seconds(second);
+        let window = screener.window(frame_epoch, *previous_end);
+        *previous_end = Some(window.end);
+        screener.screen(frame_epoch, window, &objects)
+    }
+
+    #[test]
+    fn reports_only_the_closest() {
+        let screener = Screener::new(
+            &ConjunctionConfig {
+                threshold_km: 5.0,
+                top_n: 2,
+            },
+            Duration::from_secs(1),
+        );
+        let objects: Vec<OrbitalObject> = (0..5)
+            .map(|i| {
+                // Drifting apart along x, so each pair is closest now.
+                object(
+                    i + 1,
+                    [7_000.0, 0.0, i as f64 * 1.5_f64.powi(i as i32)],
+                    [i as f64 * 0.1, 0.0, 0.0],
+                )
+            })
+            .collect();
+        let now = Utc::now();
+        let misses: Vec<f64> = screener
+            .screen(now, screener.window(now, None), &objects)
+            .iter()
+            .map(|approach| approach.miss_distance_km)
+            .collect();
+        // Offsets 0, 1.5, 4.5, 10.125, ... give gaps 1.5, 3 and 4.5 first.
+        assert_eq!(misses.len(), 2);
+        assert!((misses[0] - 1.5).abs() < 1e-9);
+        assert!((misses[1] - 3.0).abs() < 1e-9);
+    }
+}
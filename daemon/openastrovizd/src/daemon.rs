@@ -2,7 +2,7 @@ use std::env;
 use std::fs;
 use std::io;
 use std::net::{TcpStream, ToSocketAddrs};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
 use std::sync::Arc;
 use std::thread;
@@ -10,7 +10,13 @@ use std::time::{Duration, Instant};
 
 use tokio::sync::RwLock;
 
-use crate::tle::{fetch_tle_catalog, parse_tle_catalog, OrbitalRecord};
+use crate::cache::{format_age, CatalogCache};
+use crate::config::ServiceConfig;
+use crate::conjunction::Screener;
+use crate::logging::{self, error, info, warn};
+use crate::server::{self, AppState};
+use crate::source::{CatalogConfig, CatalogFeed, LiveCatalog};
+use crate::tle::drop_failed;
 
 #[cfg(all(test, windows))]
 static TASKKILL_STATUS: std::sync::Mutex<Option<io::Result<std::process::ExitStatus>>> =
@@ -26,10 +32,6 @@ pub(crate) fn set_mock_taskkill_status(result: io::Result<std::process::ExitStat
     *TASKKILL_STATUS.lock().unwrap() = Some(result);
 }
 
-fn pid_file() -> PathBuf {
-    env::temp_dir().join("openastrovizd.pid")
-}
-
 fn default_binary_path() -> io::Result<String> {
     if let Ok(path) = env::var("CARGO_BIN_EXE_openastrovizd") {
         return Ok(path);
@@ -60,54 +62,124 @@ fn binary_name() -> &'static str {
 /// Entry point for the background daemon process. This function blocks
 /// indefinitely and is intended to be run by re-invoking the `openastrovizd`
 /// binary with the `--run-service` flag.
-pub fn run_service(config: Option<&PathBuf>) -> Result<(), io::Error> {
-    if let Some(path) = config {
-        if !path.exists() {
-            return Err(io::Error::new(
-                io::ErrorKind::NotFound,
-                format!("Config file {} was not found", path.display()),
-            ));
-        }
-    }
+pub fn run_service(config: Option<&Path>) -> Result<(), io::Error> {
+    let config = ServiceConfig::load(config)?;
+    config.require_credentials()?;
+    logging::init(&config.logging)?;
 
     let runtime = tokio::runtime::Builder::new_multi_thread()
         .enable_all()
         .build()
-        .map_err(|e| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                format!("tokio runtime init failed: {e}"),
-            )
-        })?;
+        .map_err(|e| io::Error::other(format!("tokio runtime init failed: {e}")))?;
 
     runtime.block_on(async move {
-        let state = Arc::new(RwLock::new(Vec::<OrbitalRecord>::new()));
-        tokio::spawn(tle_refresh_loop(Arc::clone(&state)));
-
-        loop {
-            tokio::time::sleep(Duration::from_secs(60)).await;
+        let listener = tokio::net::TcpListener::bind(config.server.listen)
+            .await
+            .map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!("cannot listen on {}: {e}", config.server.listen),
+                )
+            })?;
+        info!(
+            "Serving the orbital stream on ws://{addr}/ws/orbits and the API on http://{addr}/api",
+            addr = listener.local_addr()?
+        );
+        for warning in config.warnings() {
+            warn!("{warning}");
         }
+
+        let state = Arc::new(RwLock::new(LiveCatalog::default()));
+        let cache = CatalogCache::new(&config.cache.dir);
+        tokio::spawn(tle_refresh_loop(config.catalog, cache, Arc::clone(&state)));
+        tokio::spawn(prune_loop(Arc::clone(&state)));
+
+        let tick = Duration::from_millis(config.server.tick_ms);
+        let screener = Screener::new(&config.conjunction, tick);
+        let (frames, receiver) = server::frame_channel();
+        tokio::spawn(server::tick(Arc::clone(&state), tick, screener, frames));
+        server::serve(
+            listener,
+            AppState {
+                frames: receiver,
+                catalog: state,
+                screener,
+            },
+        )
+        .await
     })
 }
 
-async fn tle_refresh_loop(state: Arc<RwLock<Vec<OrbitalRecord>>>) {
-    let client = reqwest::Client::new();
+/// Once a minute, drop objects that no longer propagate from the live state.
+/// The catalog is checked off the async workers on a copy, and the lock is
+/// only taken to swap the survivors in.
+async fn prune_loop(state: Arc<RwLock<LiveCatalog>>) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        let (mut records, revision) = {
+            let live = state.read().await;
+            (live.records.clone(), live.revision)
+        };
+        let pruned = tokio::task::spawn_blocking(move || {
+            let dropped = drop_failed(&mut records, chrono::Utc::now().naive_utc());
+            (records, dropped)
+        })
+        .await;
+        let (records, dropped) = match pruned {
+            Ok(pruned) => pruned,
+            Err(err) => {
+                error!("Pruning the live orbital state failed: {err}");
+                continue;
+            }
+        };
+        if dropped.is_empty() {
+            continue;
+        }
+        {
+            let mut live = state.write().await;
+            // A refresh replaced the catalog meanwhile; it was pruned on load.
+            if live.revision != revision {
+                continue;
+            }
+            live.records = records;
+            live.revision += 1;
+        }
+        for failure in dropped {
+            info!("Dropped from live orbital state: {failure}");
+        }
+    }
+}
+
+async fn tle_refresh_loop(
+    config: CatalogConfig,
+    cache: CatalogCache,
+    state: Arc<RwLock<LiveCatalog>>,
+) {
+    let client = config.http_client();
+    // The first refresh serves the cached catalog before any source is
+    // read; due sources are re-read from the next poll on.
+    let mut feed = CatalogFeed::new(&config.source(), config.http_policy(), Some(cache));
 
     loop {
-        match fetch_tle_catalog(&client).await {
-            Ok(raw_catalog) => match parse_tle_catalog(&raw_catalog) {
-                Ok(next) => {
-                    let count = next.len();
-                    let mut shared = state.write().await;
-                    *shared = next;
-                    eprintln!("Loaded {count} TLE records into live orbital state");
-                }
-                Err(err) => eprintln!("TLE parse error: {err}"),
-            },
-            Err(err) => eprintln!("TLE fetch error: {err}"),
+        // Sources are only re-read when they change or fall due.
+        if let Some(mut next) = feed.refresh(&client).await {
+            let now = chrono::Utc::now();
+            for failure in drop_failed(&mut next.records, now.naive_utc()) {
+                info!("Dropped from live orbital state: {failure}");
+            }
+            let count = next.records.len();
+            if count == 0 {
+                warn!("Catalog held no usable records; keeping the live state");
+            } else {
+                let age = next.age(now).map(format_age).unwrap_or_default();
+                let mut live = state.write().await;
+                next.revision = live.revision + 1;
+                *live = next;
+                info!("Loaded {count} catalog records into live orbital state, fetched {age} ago");
+            }
         }
 
-        tokio::time::sleep(Duration::from_secs(24 * 60 * 60)).await;
+        tokio::time::sleep(Duration::from_secs(config.poll_interval_secs.max(1))).await;
     }
 }
 
@@ -126,7 +198,7 @@ enum ReadinessTarget {
 }
 
 impl DaemonConfig {
-    fn from_env() -> io::Result<Self> {
+    fn from_env(config: Option<&Path>) -> io::Result<Self> {
         let command = env::var("OPENASTROVIZD_DAEMON_CMD").or_else(|_| default_binary_path())?;
 
         let readiness_socket = env::var("OPENASTROVIZD_SOCKET").ok();
@@ -140,9 +212,9 @@ impl DaemonConfig {
             .map(|value| value.split_whitespace().map(|v| v.to_string()).collect())
             .unwrap_or_else(|_| vec![String::from("--run-service")]);
 
-        if let Some(path) = env::var("OPENASTROVIZD_CONFIG").ok() {
+        if let Some(path) = config {
             args.push(String::from("--config"));
-            args.push(path);
+            args.push(path.to_string_lossy().into_owned());
         }
 
         Ok(Self {
@@ -159,8 +231,8 @@ impl DaemonConfig {
 /// The command used can be overridden with the `OPENASTROVIZD_DAEMON_CMD`
 /// environment variable (defaults to the compiled `openastrovizd` binary).
 /// Additional arguments can be supplied via `OPENASTROVIZD_DAEMON_ARGS`, and
-/// a configuration file path may be provided with `OPENASTROVIZD_CONFIG` (the
-/// path is forwarded to the service with a `--config` flag). When spawning the
+/// the `config` file, if any, is forwarded to the service with a `--config`
+/// flag. The pid of the service is written to `pid_path`. When spawning the
 /// background service, this function waits until either a readiness socket is
 /// available (as specified by `OPENASTROVIZD_SOCKET`) or the daemon remains
 /// healthy for a short period before writing the PID file. Failures surfaced
@@ -173,11 +245,10 @@ impl DaemonConfig {
 /// Daemon started with pid 12345
 ///
 /// # Equivalent internal call path used by the CLI command handler:
-/// # daemon::start_daemon()?;
+/// # daemon::start_daemon(&config.daemon.pid_file, config_path)?;
 /// ```
-pub fn start_daemon() -> Result<String, io::Error> {
-    let pid_path = pid_file();
-    match fs::read_to_string(&pid_path) {
+pub fn start_daemon(pid_path: &Path, config: Option<&Path>) -> Result<String, io::Error> {
+    match fs::read_to_string(pid_path) {
         Ok(pid_str) => {
             if let Ok(pid) = pid_str.trim().parse::<u32>() {
                 if process_running(pid) {
@@ -187,13 +258,13 @@ pub fn start_daemon() -> Result<String, io::Error> {
                     ));
                 }
             }
-            let _ = fs::remove_file(&pid_path);
+            let _ = fs::remove_file(pid_path);
         }
         Err(e) if e.kind() == io::ErrorKind::NotFound => {}
         Err(e) => return Err(e),
     }
 
-    let config = DaemonConfig::from_env()?;
+    let config = DaemonConfig::from_env(config)?;
 
     let mut child = Command::new(&config.command)
         .args(&config.args)
@@ -205,7 +276,7 @@ pub fn start_daemon() -> Result<String, io::Error> {
         Ok(()) => {
             forward_child_stderr(&mut child);
             let pid = child.id();
-            fs::write(&pid_path, pid.to_string())?;
+            fs::write(pid_path, pid.to_string())?;
 
             Ok(format!("Daemon started with pid {pid}"))
         }
@@ -219,13 +290,7 @@ pub fn start_daemon() -> Result<String, io::Error> {
 
 #[cfg(unix)]
 fn kill_result_indicates_running(kill_result: i32, errno: i32) -> bool {
-    if kill_result == 0 {
-        true
-    } else if kill_result == -1 && errno == libc::EPERM {
-        true
-    } else {
-        false
-    }
+    kill_result == 0 || (kill_result == -1 && errno == libc::EPERM)
 }
 
 #[cfg(unix)]
@@ -266,10 +331,7 @@ fn wait_for_readiness(child: &mut Child, config: &DaemonConfig) -> io::Result<()
                 .map(|s| format!(": {s}"))
                 .unwrap_or_default();
 
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("{msg}{detail}"),
-            ));
+            return Err(io::Error::other(format!("{msg}{detail}")));
         }
 
         if let Some(target) = &readiness_target {
@@ -413,8 +475,8 @@ fn process_running(pid: u32) -> bool {
     }
 }
 
-/// Checks the status of the OpenAstroViz daemon by reading the PID file and
-/// verifying that the process is still alive.
+/// Checks the status of the OpenAstroViz daemon by reading the PID file at
+/// `pid_path` and verifying that the process is still alive.
 ///
 /// # Examples
 /// ```ignore
@@ -425,11 +487,11 @@ fn process_running(pid: u32) -> bool {
 /// Daemon is running with pid 12345
 ///
 /// # Equivalent internal call path used by `openastrovizd daemon status`:
-/// # let status = daemon::check_status()?;
+/// # let status = daemon::check_status(&config.daemon.pid_file)?;
 /// # println!("{status}");
 /// ```
-pub fn check_status() -> Result<String, io::Error> {
-    match fs::read_to_string(pid_file()) {
+pub fn check_status(pid_path: &Path) -> Result<String, io::Error> {
+    match fs::read_to_string(pid_path) {
         Ok(pid_str) => {
             if let Ok(pid) = pid_str.trim().parse::<u32>() {
                 if process_running(pid) {
@@ -443,11 +505,10 @@ pub fn check_status() -> Result<String, io::Error> {
     }
 }
 
-/// Stops the OpenAstroViz daemon by reading the PID file, sending a termination
-/// signal to the process and removing the PID file.
-pub fn stop_daemon() -> Result<String, io::Error> {
-    let pid_path = pid_file();
-    let pid_str = match fs::read_to_string(&pid_path) {
+/// Stops the OpenAstroViz daemon by reading the PID file at `pid_path`, sending
+/// a termination signal to the process and removing the PID file.
+pub fn stop_daemon(pid_path: &Path) -> Result<String, io::Error> {
+    let pid_str = match fs::read_to_string(pid_path) {
         Ok(contents) => contents,
         Err(e) if e.kind() == io::ErrorKind::NotFound => {
             return Ok(String::from("Daemon is not running"));
@@ -502,13 +563,10 @@ pub fn stop_daemon() -> Result<String, io::Error> {
         fs::remove_file(pid_path)?;
         Ok(String::from("Daemon stopped"))
     } else {
-        Err(io::Error::new(
-            io::ErrorKind::Other,
-            format!(
-                "Daemon with pid {pid} did not stop within {:?}; pid file left intact",
-                wait_timeout
-            ),
-        ))
+        Err(io::Error::other(format!(
+            "Daemon with pid {pid} did not stop within {:?}; pid file left intact",
+            wait_timeout
+        )))
     }
 }
 
@@ -524,32 +582,46 @@ mod tests {
 
     static TEST_MUTEX: Mutex<()> = Mutex::new(());
 
+    fn pid_file() -> PathBuf {
+        ServiceConfig::default().daemon.pid_file
+    }
+
+    /// Start the service the way `openastrovizd start` does, on a free port
+    /// and with a local catalog.
+    fn start(config: &Path) -> io::Result<String> {
+        start_daemon(&pid_file(), Some(config))
+    }
+
     #[test]
     fn start_and_status_success() {
         let _lock = TEST_MUTEX.lock().unwrap();
         util::cleanup();
-        let msg = start_daemon().expect("start failed");
+        let config = util::offline_config("start-status");
+        let msg = start(&config).expect("start failed");
         assert!(msg.contains("Daemon started"));
-        let status = check_status().expect("status failed");
+        let status = check_status(&pid_file()).expect("status failed");
         assert!(status.contains("running"));
         util::cleanup();
+        util::remove_config(&config);
     }
 
     #[test]
     fn start_failure() {
         let _lock = TEST_MUTEX.lock().unwrap();
         util::cleanup();
+        let config = util::offline_config("start-failure");
         env::set_var("OPENASTROVIZD_DAEMON_CMD", "/nonexistent");
-        assert!(start_daemon().is_err());
+        assert!(start(&config).is_err());
         env::remove_var("OPENASTROVIZD_DAEMON_CMD");
         util::cleanup();
+        util::remove_config(&config);
     }
 
     #[test]
     fn status_not_running() {
         let _lock = TEST_MUTEX.lock().unwrap();
         util::cleanup();
-        let status = check_status().unwrap();
+        let status = check_status(&pid_file()).unwrap();
         assert!(status.contains("not running"));
     }
 
@@ -557,7 +629,7 @@ mod tests {
     fn stop_without_pid_file_returns_not_running() {
         let _lock = TEST_MUTEX.lock().unwrap();
         util::cleanup();
-        let result = stop_daemon();
+        let result = stop_daemon(&pid_file());
         assert!(matches!(result, Ok(ref msg) if msg == "Daemon is not running"));
     }
 
@@ -580,7 +652,7 @@ mod tests {
 
         super::set_mock_taskkill_status(Ok(std::process::ExitStatus::from_raw(1)));
 
-        let err = stop_daemon().expect_err("expected taskkill failure to propagate");
+        let err = stop_daemon(&pid_file()).expect_err("expected taskkill failure to propagate");
         assert_eq!(err.kind(), io::ErrorKind::Other);
         assert!(pid_path.exists());
 
@@ -593,11 +665,12 @@ mod tests {
         util::cleanup();
 
         let pid_path = pid_file();
+        let config = util::offline_config("restart");
 
-        let first_msg = start_daemon().expect("first start failed");
+        let first_msg = start(&config).expect("first start failed");
         assert!(first_msg.contains("Daemon started"));
 
-        let second_attempt = start_daemon();
+        let second_attempt = start(&config);
         assert!(second_attempt.is_err());
         let err = second_attempt.unwrap_err();
         assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
@@ -608,13 +681,14 @@ mod tests {
         fs::write(&pid_path, &stale_pid).expect("should write stale pid file");
         assert!(pid_path.exists(), "pid file should exist before restart");
 
-        let restart_msg = start_daemon().expect("restart should succeed after stale pid");
+        let restart_msg = start(&config).expect("restart should succeed after stale pid");
         assert!(restart_msg.contains("Daemon started"));
         let new_pid_str =
             fs::read_to_string(&pid_path).expect("pid file should exist after restart");
         assert_ne!(stale_pid, new_pid_str.trim());
 
         util::cleanup();
+        util::remove_config(&config);
     }
 
     #[test]
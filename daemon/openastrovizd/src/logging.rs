@@ -0,0 +1,128 @@
+//! Leveled log lines for the long-running service, written to stderr or to
+//! the file named in the `[logging]` section of the config file.
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+/// How much the service logs; each level includes the ones before it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LogLevel::Error => "ERROR",
+            LogLevel::Warn => "WARN",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The logging section of the daemon config file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    pub level: LogLevel,
+    /// File to append to instead of stderr.
+    pub file: Option<PathBuf>,
+}
+
+struct Logger {
+    level: LogLevel,
+    file: Option<Mutex<File>>,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+/// Install the service logger. Until this runs, and in every process that
+/// never calls it, lines at `info` and above go to stderr.
+pub fn init(config: &LogConfig) -> io::Result<()> {
+    let file = match &config.file {
+        Some(path) => {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| {
+                    io::Error::new(
+                        e.kind(),
+                        format!("Log file {} could not be opened: {e}", path.display()),
+                    )
+                })?;
+            Some(Mutex::new(file))
+        }
+        None => None,
+    };
+    LOGGER
+        .set(Logger {
+            level: config.level,
+            file,
+        })
+        .map_err(|_| io::Error::other("logger already initialised"))
+}
+
+/// Write one log line; use the [`error!`], [`warn!`], [`info!`] and
+/// [`debug!`] macros instead of calling this directly.
+pub fn write(level: LogLevel, args: fmt::Arguments<'_>) {
+    let (max, file) = match LOGGER.get() {
+        Some(logger) => (logger.level, logger.file.as_ref()),
+        None => (LogLevel::default(), None),
+    };
+    if level > max {
+        return;
+    }
+    let line = format!(
+        "{} {level} {args}\n",
+        chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ")
+    );
+    match file {
+        Some(file) => {
+            let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+            let _ = file.write_all(line.as_bytes());
+        }
+        None => {
+            let _ = io::stderr().write_all(line.as_bytes());
+        }
+    }
+}
+
+macro_rules! error {
+    ($($arg:tt)*) => {
+        $crate::logging::write($crate::logging::LogLevel::Error, format_args!($($arg)*))
+    };
+}
+
+// Defined under another name because a bare `warn` in a `use` clashes with
+// the built-in lint attribute.
+macro_rules! warn_ {
+    ($($arg:tt)*) => {
+        $crate::logging::write($crate::logging::LogLevel::Warn, format_args!($($arg)*))
+    };
+}
+
+macro_rules! info {
+    ($($arg:tt)*) => {
+        $crate::logging::write($crate::logging::LogLevel::Info, format_args!($($arg)*))
+    };
+}
+
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        $crate::logging::write($crate::logging::LogLevel::Debug, format_args!($($arg)*))
+    };
+}
+
+pub(crate) use {debug, error, info, warn_ as warn};
@@ -1,17 +1,41 @@
 use clap::{Parser, Subcommand};
+use std::env;
 use std::path::PathBuf;
 
+mod api;
 mod backend;
 mod bench;
+mod cache;
+mod catalog;
+mod config;
+mod conjunction;
 mod daemon;
+#[cfg(test)]
+mod http_stub;
+mod logging;
+mod orbits;
+mod passes;
+mod screen;
+mod server;
+mod source;
+mod spacetrack;
+#[cfg(test)]
+mod test_util;
 mod tle;
 use backend::Backend;
 use bench::{bench_backend, BenchError};
+use cache::CatalogCache;
+use catalog::ExportFormat;
+use config::ServiceConfig;
+use openastroviz_core::{Geodetic, NoradId};
+use passes::{parse_utc, run_passes, PassQuery};
+use screen::{run_screen, Primaries, ScreenFormat, ScreenQuery};
 
 #[derive(Parser)]
 #[command(author, version, about = "OpenAstroViz daemon")]
 struct Cli {
-    /// Path to a configuration file for the daemon.
+    /// Path to a configuration file for the daemon (defaults to
+    /// `OPENASTROVIZD_CONFIG` when set)
     #[arg(long, global = true)]
     config: Option<PathBuf>,
 
@@ -36,13 +60,104 @@ enum Commands {
         /// Backend to benchmark (e.g. cuda)
         backend: Backend,
     },
+    /// Predict passes of a satellite over a ground site
+    Passes {
+        /// NORAD catalog number of the satellite, decimal or Alpha-5
+        #[arg(long)]
+        norad: NoradId,
+        /// Observer geodetic latitude in degrees
+        #[arg(long, allow_negative_numbers = true)]
+        lat: f64,
+        /// Observer longitude in degrees, east positive
+        #[arg(long, allow_negative_numbers = true)]
+        lon: f64,
+        /// Observer height above the WGS-84 ellipsoid in km
+        #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+        alt_km: f64,
+        /// Minimum elevation in degrees for a pass to count
+        #[arg(long)]
+        min_elevation: Option<f64>,
+        /// Start of the search window in UTC (defaults to now)
+        #[arg(long, value_parser = parse_utc)]
+        start: Option<chrono::NaiveDateTime>,
+        /// Length of the search window in hours
+        #[arg(long, default_value_t = 24.0)]
+        hours: f64,
+        /// Read the elements from a local TLE or OMM catalog file instead of CelesTrak
+        #[arg(long)]
+        tle: Option<PathBuf>,
+    },
+    /// Screen the catalog for close approaches over the coming days
+    Screen {
+        /// Catalog numbers to screen against the catalog, comma separated,
+        /// or `all` to screen every object against every other
+        #[arg(long, default_value = "all")]
+        primary: Primaries,
+        /// Length of the screening window in days
+        #[arg(long, default_value_t = 7.0)]
+        days: f64,
+        /// Report approaches closer than this many km (defaults to
+        /// `conjunction.threshold_km` from the config)
+        #[arg(long)]
+        threshold_km: Option<f64>,
+        /// Start of the screening window in UTC (defaults to now)
+        #[arg(long, value_parser = parse_utc)]
+        start: Option<chrono::NaiveDateTime>,
+        /// Screen a local TLE or OMM catalog file instead of the cached catalog
+        #[arg(long)]
+        catalog: Option<PathBuf>,
+        /// Format to write to standard output
+        #[arg(long, value_enum, default_value_t = ScreenFormat::Csv)]
+        format: ScreenFormat,
+    },
+    /// Inspect element catalogs
+    Catalog {
+        #[command(subcommand)]
+        command: CatalogCommand,
+    },
+    /// Inspect the daemon configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Validate the config file and environment overrides, then print the
+    /// effective configuration
+    Check,
+}
+
+#[derive(Subcommand)]
+enum CatalogCommand {
+    /// Check every record of a TLE catalog and print a JSON report
+    Validate {
+        /// 2- or 3-line TLE catalog to check
+        file: PathBuf,
+    },
+    /// Rewrite a TLE or OMM catalog in another format
+    Convert {
+        /// TLE or OMM catalog to read
+        file: PathBuf,
+        /// Format to write to standard output
+        #[arg(long)]
+        to: ExportFormat,
+        /// Only keep these catalog numbers (repeatable)
+        #[arg(long)]
+        norad: Vec<NoradId>,
+    },
 }
 
 fn main() {
     let cli = Cli::parse();
+    let config_path = cli
+        .config
+        .clone()
+        .or_else(|| env::var_os("OPENASTROVIZD_CONFIG").map(PathBuf::from));
 
     if cli.run_service {
-        if let Err(e) = daemon::run_service(cli.config.as_ref()) {
+        if let Err(e) = daemon::run_service(config_path.as_deref()) {
             eprintln!("Failed to run daemon service: {e}");
             std::process::exit(1);
         }
@@ -50,27 +165,50 @@ fn main() {
     }
 
     match cli.command {
-        Some(Commands::Start) => match daemon::start_daemon() {
-            Ok(message) => println!("{message}"),
-            Err(e) => {
-                eprintln!("Failed to start daemon: {e}");
-                std::process::exit(1);
+        Some(Commands::Start) => {
+            match ServiceConfig::load(config_path.as_deref()).and_then(|config| {
+                daemon::start_daemon(&config.daemon.pid_file, config_path.as_deref())
+            }) {
+                Ok(message) => println!("{message}"),
+                Err(e) => {
+                    eprintln!("Failed to start daemon: {e}");
+                    std::process::exit(1);
+                }
             }
-        },
-        Some(Commands::Stop) => match daemon::stop_daemon() {
+        }
+        Some(Commands::Stop) => match ServiceConfig::load(config_path.as_deref())
+            .and_then(|config| daemon::stop_daemon(&config.daemon.pid_file))
+        {
             Ok(message) => println!("{message}"),
             Err(e) => {
                 eprintln!("Failed to stop daemon: {e}");
                 std::process::exit(1);
             }
         },
-        Some(Commands::Status) => match daemon::check_status() {
-            Ok(status) => println!("{status}"),
-            Err(e) => {
-                eprintln!("Failed to check daemon status: {e}");
-                std::process::exit(1);
+        Some(Commands::Status) => {
+            let config = match ServiceConfig::load(config_path.as_deref()) {
+                Ok(config) => config,
+                Err(e) => {
+                    eprintln!("Failed to check daemon status: {e}");
+                    std::process::exit(1);
+                }
+            };
+            match daemon::check_status(&config.daemon.pid_file) {
+                Ok(status) => println!("{status}"),
+                Err(e) => {
+                    eprintln!("Failed to check daemon status: {e}");
+                    std::process::exit(1);
+                }
             }
-        },
+            match CatalogCache::new(config.cache.dir).summary(chrono::Utc::now()) {
+                Ok(Some(summary)) => println!("{summary}"),
+                Ok(None) => println!("No cached catalog"),
+                Err(e) => {
+                    eprintln!("Failed to read the catalog cache: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
         Some(Commands::Bench { backend }) => match bench_backend(backend) {
             Ok(outcome) => {
                 println!(
@@ -87,6 +225,98 @@ fn main() {
                 std::process::exit(1);
             }
         },
+        Some(Commands::Passes {
+            norad,
+            lat,
+            lon,
+            alt_km,
+            min_elevation,
+            start,
+            hours,
+            tle,
+        }) => {
+            let query = PassQuery {
+                norad_id: norad,
+                location: Geodetic {
+                    latitude_deg: lat,
+                    longitude_deg: lon,
+                    height_km: alt_km,
+                },
+                min_elevation_deg: min_elevation,
+                start,
+                hours,
+                tle_path: tle,
+            };
+            match run_passes(&query) {
+                Ok(table) => print!("{table}"),
+                Err(e) => {
+                    eprintln!("Failed to predict passes: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(Commands::Screen {
+            primary,
+            days,
+            threshold_km,
+            start,
+            catalog,
+            format,
+        }) => {
+            let result = ServiceConfig::load(config_path.as_deref())
+                .map_err(|e| e.to_string())
+                .and_then(|config| {
+                    let query = ScreenQuery {
+                        primaries: primary,
+                        start,
+                        days,
+                        threshold_km: threshold_km.unwrap_or(config.conjunction.threshold_km),
+                        catalog_path: catalog,
+                        cache_dir: config.cache.dir,
+                        format,
+                    };
+                    run_screen(&query, &mut std::io::stderr())
+                });
+            match result {
+                Ok(output) => print!("{output}"),
+                Err(e) => {
+                    eprintln!("Failed to screen: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(Commands::Catalog {
+            command: CatalogCommand::Validate { file },
+        }) => match catalog::validate_file(&file) {
+            Ok((report, valid)) => {
+                println!("{report}");
+                if !valid {
+                    std::process::exit(1);
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to validate catalog: {e}");
+                std::process::exit(2);
+            }
+        },
+        Some(Commands::Catalog {
+            command: CatalogCommand::Convert { file, to, norad },
+        }) => match catalog::convert_file(&file, to, &norad) {
+            Ok(output) => print!("{output}"),
+            Err(e) => {
+                eprintln!("Failed to convert catalog: {e}");
+                std::process::exit(1);
+            }
+        },
+        Some(Commands::Config {
+            command: ConfigCommand::Check,
+        }) => match config::check(config_path.as_deref()) {
+            Ok(report) => print!("{report}"),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        },
         None => {
             println!("openastrovizd {}", env!("CARGO_PKG_VERSION"));
         }
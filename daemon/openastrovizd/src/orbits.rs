@@ -0,0 +1,468 @@
+//! The orbital picture served to web clients, in the JSON shapes of
+//! `web/src/types/orbit.ts`, and the frames it is built from.
+//!
+//! Positions and velocities are TEME, straight from SGP4. The daemon has no
+//! telemetry feed, so an object's health describes its element set: how old
+//! it is, with `lastContact` at its epoch.
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use openastroviz_core::{NoradId, ObjectStatus, StateBuffer};
+use openastroviz_cpu::CpuBackend;
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::conjunction::Screener;
+use crate::source::LiveCatalog;
+use crate::tle::OrbitalRecord;
+
+/// Element sets older than this are flagged `warning`.
+const STALE_WARNING: Duration = Duration::days(7);
+/// Element sets older than this are flagged `critical`.
+const STALE_CRITICAL: Duration = Duration::days(30);
+/// Half the span the client's timeline shows around the frame epoch.
+const TIMELINE_HALF_SPAN: Duration = Duration::minutes(90);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectKind {
+    Satellite,
+    Debris,
+    RocketBody,
+}
+
+impl ObjectKind {
+    /// Classify an object by catalog naming, where fragments end in `DEB`
+    /// and spent stages carry `R/B`.
+    pub fn from_name(name: &str) -> Self {
+        let name = name.to_ascii_uppercase();
+        if name.contains("R/B") {
+            Self::RocketBody
+        } else if name.split_whitespace().any(|word| word == "DEB") {
+            Self::Debris
+        } else {
+            Self::Satellite
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Nominal,
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectHealth {
+    pub status: HealthStatus,
+    /// Battery, thermal and comms are on a 0–1 scale; without telemetry they
+    /// stay at 1, no known fault.
+    pub battery: f64,
+    pub thermal: f64,
+    pub comms: f64,
+    #[serde(serialize_with = "iso")]
+    pub last_contact: DateTime<Utc>,
+}
+
+impl ObjectHealth {
+    /// Health of an object whose elements date from `epoch`, seen at `now`.
+    pub fn from_element_age(epoch: DateTime<Utc>, now: DateTime<Utc>) -> Self {
+        let age = now - epoch;
+        let status = if age > STALE_CRITICAL {
+            HealthStatus::Critical
+        } else if age > STALE_WARNING {
+            HealthStatus::Warning
+        } else {
+            HealthStatus::Nominal
+        };
+        Self {
+            status,
+            battery: 1.0,
+            thermal: 1.0,
+            comms: 1.0,
+            last_contact: epoch,
+        }
+    }
+}
+
+/// A predicted close approach between two objects.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloseApproach {
+    #[serde(serialize_with = "iso")]
+    pub time: DateTime<Utc>,
+    pub primary_id: String,
+    pub secondary_id: String,
+    pub miss_distance_km: f64,
+    pub relative_velocity_kps: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrbitalObject {
+    /// The catalog number as text, the key clients track objects by.
+    pub id: String,
+    pub norad_id: u32,
+    pub name: String,
+    pub kind: ObjectKind,
+    pub position: [f64; 3],
+    pub velocity: [f64; 3],
+    pub health: ObjectHealth,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SnapshotStats {
+    pub tracked: usize,
+    pub debris: usize,
+    pub warnings: usize,
+    pub critical: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrbitalSnapshot {
+    #[serde(serialize_with = "iso")]
+    pub epoch: DateTime<Utc>,
+    #[serde(serialize_with = "iso")]
+    pub start: DateTime<Utc>,
+    #[serde(serialize_with = "iso")]
+    pub end: DateTime<Utc>,
+    pub objects: Vec<OrbitalObject>,
+    pub close_approaches: Vec<CloseApproach>,
+    pub stats: SnapshotStats,
+}
+
+/// The moving part of an object, sent on every update.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ObjectUpdate<'a> {
+    pub id: &'a str,
+    pub position: [f64; 3],
+    pub velocity: [f64; 3],
+    pub health: &'a ObjectHealth,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrbitalUpdate<'a> {
+    #[serde(serialize_with = "iso")]
+    pub epoch: DateTime<Utc>,
+    pub objects: Vec<ObjectUpdate<'a>>,
+    pub close_approaches: &'a [CloseApproach],
+}
+
+/// A message pushed to stream subscribers.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum StreamMessage<'a> {
+    Snapshot { snapshot: &'a OrbitalSnapshot },
+    Update { update: OrbitalUpdate<'a> },
+    Error { message: String },
+}
+
+/// A message sent by a client.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ClientMessage {
+    Subscribe { stream: String },
+    Unsubscribe { stream: String },
+}
+
+/// Timestamps the way JavaScript's `toISOString` writes them.
+fn iso<S: Serializer>(time: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&time.to_rfc3339_opts(SecondsFormat::Millis, true))
+}
+
+/// The whole catalog propagated to one instant.
+#[derive(Debug)]
+pub struct Frame {
+    pub snapshot: OrbitalSnapshot,
+    /// Changes whenever the set of objects differs from the previous frame's,
+    /// which clients can only learn from a snapshot.
+    pub layout: u64,
+    snapshot_json: OnceLock<String>,
+    update_json: OnceLock<String>,
+}
+
+impl Frame {
+    pub fn new(snapshot: OrbitalSnapshot, layout: u64) -> Self {
+        Self {
+            snapshot,
+            layout,
+            snapshot_json: OnceLock::new(),
+            update_json: OnceLock::new(),
+        }
+    }
+
+    /// The frame as a `snapshot` message, serialised once however many
+    /// clients receive it.
+    pub fn snapshot_message(&self) -> &str {
+        self.snapshot_json.get_or_init(|| {
+            to_json(&StreamMessage::Snapshot {
+                snapshot: &self.snapshot,
+            })
+        })
+    }
+
+    /// The frame as an `update` message for clients that hold its layout.
+    pub fn update_message(&self) -> &str {
+        self.update_json.get_or_init(|| {
+            let snapshot = &self.snapshot;
+            to_json(&StreamMessage::Update {
+                update: OrbitalUpdate {
+                    epoch: snapshot.epoch,
+                    objects: snapshot
+                        .objects
+                        .iter()
+                        .map(|object| ObjectUpdate {
+                            id: &object.id,
+                            position: object.position,
+                            velocity: object.velocity,
+                            health: &object.health,
+                        })
+                        .collect(),
+                    close_approaches: &snapshot.close_approaches,
+                },
+            })
+        })
+    }
+}
+
+pub fn to_json(message: &StreamMessage<'_>) -> String {
+    serde_json::to_string(message).expect("stream messages always serialise")
+}
+
+/// What a frame says about an object besides its state.
+#[derive(Debug, Clone)]
+pub struct ObjectInfo {
+    norad_id: NoradId,
+    name: String,
+    kind: ObjectKind,
+    epoch: DateTime<Utc>,
+}
+
+impl ObjectInfo {
+    pub fn new(record: &OrbitalRecord) -> Self {
+        Self {
+            norad_id: record.norad_id,
+            name: record.name.clone(),
+            kind: ObjectKind::from_name(&record.name),
+            epoch: record.propagator.epoch().and_utc(),
+        }
+    }
+
+    /// The object in state `(position, velocity)` at `epoch`.
+    pub fn object(
+        &self,
+        position: [f64; 3],
+        velocity: [f64; 3],
+        epoch: DateTime<Utc>,
+    ) -> OrbitalObject {
+        OrbitalObject {
+            id: self.norad_id.get().to_string(),
+            norad_id: self.norad_id.get(),
+            name: self.name.clone(),
+            kind: self.kind,
+            position,
+            velocity,
+            health: ObjectHealth::from_element_age(self.epoch, epoch),
+        }
+    }
+}
+
+/// Propagates the live catalog into frames on the CPU backend, reloading it
+/// only when the catalog changes, and screens each frame for close
+/// approaches.
+#[derive(Debug, Default)]
+pub struct Framer {
+    backend: CpuBackend,
+    screener: Screener,
+    info: Vec<ObjectInfo>,
+    states: StateBuffer,
+    revision: Option<u64>,
+    layout: u64,
+    last_ids: Vec<u32>,
+    /// Where the last frame's screening window ended.
+    screened_until: Option<DateTime<Utc>>,
+}
+
+impl Framer {
+    pub fn new(screener: Screener) -> Self {
+        Self {
+            backend: CpuBackend::default(),
+            screener,
+            info: Vec::new(),
+            states: StateBuffer::default(),
+            revision: None,
+            layout: 0,
+            last_ids: Vec::new(),
+            screened_until: None,
+        }
+    }
+
+    /// Take the records of `catalog` unless they are already loaded.
+    pub fn load(&mut self, catalog: &LiveCatalog) {
+        if self.revision == Some(catalog.revision) {
+            return;
+        }
+        self.revision = Some(catalog.revision);
+        self.info = catalog.records.iter().map(ObjectInfo::new).collect();
+        self.backend.load(
+            catalog
+                .records
+                .iter()
+                .map(|record| record.propagator.clone())
+                .collect(),
+        );
+    }
+
+    /// Propagate the loaded catalog to `epoch` and screen it for approaches
+    /// since the previous frame's window. Objects that fail to propagate are
+    /// left out of the frame.
+    pub fn frame(&mut self, epoch: DateTime<Utc>) -> Frame {
+        self.backend
+            .propagate_datetime(epoch.naive_utc(), &mut self.states);
+        let states = &self.states;
+        let objects: Vec<OrbitalObject> = self
+            .info
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| states.status[i] == ObjectStatus::Ok)
+            .map(|(i, info)| {
+                info.object(
+                    [states.x_km[i], states.y_km[i], states.z_km[i]],
+                    [states.vx_km_s[i], states.vy_km_s[i], states.vz_km_s[i]],
+                    epoch,
+                )
+            })
+            .collect();
+        if !objects
+            .iter()
+            .map(|object| object.norad_id)
+            .eq(self.last_ids.iter().copied())
+        {
+            self.layout += 1;
+            self.last_ids = objects.iter().map(|object| object.norad_id).collect();
+        }
+        let mut snapshot = snapshot(epoch, objects);
+        let window = self.screener.window(epoch, self.screened_until);
+        self.screened_until = Some(window.end);
+        snapshot.close_approaches = self.screener.screen(epoch, window, &snapshot.objects);
+        Frame::new(snapshot, self.layout)
+    }
+}
+
+/// A snapshot of `objects` at `epoch`, with its stats.
+pub fn snapshot(epoch: DateTime<Utc>, objects: Vec<OrbitalObject>) -> OrbitalSnapshot {
+    let count = |pred: &dyn Fn(&OrbitalObject) -> bool| objects.iter().filter(|o| pred(o)).count();
+    let stats = SnapshotStats {
+        tracked: objects.len(),
+        debris: count(&|o| o.kind == ObjectKind::Debris),
+        warnings: count(&|o| o.health.status == HealthStatus::Warning),
+        critical: count(&|o| o.health.status == HealthStatus::Critical),
+    };
+    OrbitalSnapshot {
+        epoch,
+        start: epoch - TIMELINE_HALF_SPAN,
+        end: epoch + TIMELINE_HALF_SPAN,
+        objects,
+        close_approaches: Vec::new(),
+        stats,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{GEO, VANGUARD};
+    use crate::tle::{parse_catalog_with, ParseMode};
+
+    fn catalog(revision: u64) -> LiveCatalog {
+        LiveCatalog {
+            records: parse_catalog_with(&format!("{VANGUARD}{GEO}"), ParseMode::Lenient)
+                .unwrap()
+                .records,
+            fetched_at: None,
+            sources: Vec::new(),
+            revision,
+        }
+    }
+
+    #[test]
+    fn classifies_objects_by_name() {
+        assert_eq!(ObjectKind::from_name("ISS (ZARYA)"), ObjectKind::Satellite);
+        assert_eq!(ObjectKind::from_name("COSMOS 2251 DEB"), ObjectKind::Debris);
+        assert_eq!(ObjectKind::from_name("SL-16 R/B"), ObjectKind::RocketBody);
+        assert_eq!(ObjectKind::from_name("DEBUT"), ObjectKind::Satellite);
+    }
+
+    #[test]
+    fn health_follows_element_age() {
+        let now: DateTime<Utc> = "2026-10-18T12:00:00Z".parse().unwrap();
+        let status = |days| ObjectHealth::from_element_age(now - Duration::days(days), now).status;
+        assert_eq!(status(1), HealthStatus::Nominal);
+        assert_eq!(status(8), HealthStatus::Warning);
+        assert_eq!(status(31), HealthStatus::Critical);
+    }
+
+    #[test]
+    fn frames_match_the_client_types() {
+        let mut framer = Framer::default();
+        framer.load(&catalog(1));
+        let epoch: DateTime<Utc> = "2006-06-26T00:00:00Z".parse().unwrap();
+        let frame = framer.frame(epoch);
+        let snapshot: serde_json::Value = serde_json::from_str(frame.snapshot_message()).unwrap();
+        assert_eq!(snapshot["kind"], "snapshot");
+        let snapshot = &snapshot["snapshot"];
+        assert_eq!(snapshot["epoch"], "2006-06-26T00:00:00.000Z");
+        assert_eq!(snapshot["start"], "2006-06-25T22:30:00.000Z");
+        assert_eq!(snapshot["closeApproaches"], serde_json::json!([]));
+        assert_eq!(
+            snapshot["stats"],
+            serde_json::json!({"tracked": 2, "debris": 0, "warnings": 0, "critical": 1})
+        );
+        let rocket = &snapshot["objects"][1];
+        assert_eq!(rocket["id"], "28626");
+        assert_eq!(rocket["noradId"], 28626);
+        assert_eq!(rocket["kind"], "rocket_body");
+        assert_eq!(rocket["health"]["status"], "nominal");
+        assert_eq!(rocket["health"]["lastContact"], "2006-06-25T11:12:14.455Z");
+        let radius = rocket["position"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|x| x.as_f64().unwrap().powi(2))
+            .sum::<f64>()
+            .sqrt();
+        assert!((radius - 42_164.0).abs() < 100.0, "GEO radius {radius}");
+
+        let update: serde_json::Value = serde_json::from_str(frame.update_message()).unwrap();
+        assert_eq!(update["kind"], "update");
+        assert_eq!(update["update"]["epoch"], "2006-06-26T00:00:00.000Z");
+        let object = &update["update"]["objects"][0];
+        assert_eq!(object["id"], "5");
+        assert!(object.get("name").is_none());
+        assert_eq!(object["position"], snapshot["objects"][0]["position"]);
+    }
+
+    #[test]
+    fn layout_changes_with_the_object_set() {
+        let mut framer = Framer::default();
+        let epoch: DateTime<Utc> = "2006-06-26T00:00:00Z".parse().unwrap();
+        framer.load(&catalog(1));
+        let first = framer.frame(epoch).layout;
+        assert_eq!(framer.frame(epoch + Duration::seconds(1)).layout, first);
+
+        // A new revision with the same objects keeps the layout.
+        framer.load(&catalog(2));
+        assert_eq!(framer.frame(epoch).layout, first);
+
+        let mut fewer = catalog(3);
+        fewer.records.truncate(1);
+        framer.load(&fewer);
+        let frame = framer.frame(epoch);
+        assert_ne!(frame.layout, first);
+        assert_eq!(frame.snapshot.objects.len(), 1);
+    }
+}
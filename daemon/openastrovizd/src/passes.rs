@@ -0,0 +1,196 @@
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use openastroviz_core::observer::Observer;
+use openastroviz_core::passes::{Pass, PassPredictor, PassVisibility};
+use openastroviz_core::{Geodetic, NoradId, Sgp4Propagator};
+
+use crate::tle::{fetch_by_norad, parse_catalog};
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Parameters of a `passes` invocation.
+#[derive(Debug, Clone)]
+pub struct PassQuery {
+    pub norad_id: NoradId,
+    pub location: Geodetic,
+    pub min_elevation_deg: Option<f64>,
+    pub start: Option<NaiveDateTime>,
+    pub hours: f64,
+    /// Local TLE catalog to read instead of querying CelesTrak.
+    pub tle_path: Option<PathBuf>,
+}
+
+/// Parse a UTC timestamp such as `2020-07-13T00:00:00Z`.
+pub fn parse_utc(value: &str) -> Result<NaiveDateTime, String> {
+    let trimmed = value.trim_end_matches('Z');
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(trimmed, format).ok())
+        .ok_or_else(|| format!("invalid UTC timestamp `{value}`, expected YYYY-MM-DDTHH:MM:SSZ"))
+}
+
+/// Load the object, predict its passes and render them as a table.
+pub fn run_passes(query: &PassQuery) -> Result<String, String> {
+    if !query.hours.is_finite() || query.hours <= 0.0 {
+        return Err(format!("--hours must be positive, got {}", query.hours));
+    }
+
+    let start = query.start.unwrap_or_else(|| Utc::now().naive_utc());
+    let end = Duration::try_seconds((query.hours * 3600.0).round() as i64)
+        .and_then(|span| start.checked_add_signed(span))
+        .ok_or_else(|| format!("--hours {} is too long", query.hours))?;
+
+    let propagator = load_propagator(query)?;
+    let mut observer = Observer::new(query.location);
+    if let Some(mask) = query.min_elevation_deg {
+        observer = observer.with_elevation_mask(mask);
+    }
+    let passes = PassPredictor::new(&propagator, observer)
+        .passes(start, end)
+        .map_err(|e| format!("pass prediction failed: {e}"))?;
+
+    let name = propagator.object_name().unwrap_or("UNKNOWN");
+    Ok(format_passes(name, query.norad_id, start, end, &passes))
+}
+
+fn load_propagator(query: &PassQuery) -> Result<Sgp4Propagator, String> {
+    let body = match &query.tle_path {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {e}", path.display()))?,
+        None => {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|e| format!("tokio runtime init failed: {e}"))?;
+            runtime
+                .block_on(fetch_by_norad(&reqwest::Client::new(), query.norad_id))
+                .map_err(|e| format!("failed to download elements for {}: {e}", query.norad_id))?
+        }
+    };
+
+    let catalog = parse_catalog(&body)?;
+    if let Some(failure) = catalog
+        .dropped
+        .iter()
+        .find(|failure| failure.norad_id == query.norad_id)
+    {
+        return Err(format!("cannot propagate elements: {failure}"));
+    }
+    catalog
+        .records
+        .into_iter()
+        .find(|record| record.norad_id == query.norad_id)
+        .map(|record| record.propagator)
+        .ok_or_else(|| format!("NORAD ID {} is not in the catalog", query.norad_id))
+}
+
+fn format_passes(
+    name: &str,
+    norad_id: NoradId,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    passes: &[Pass],
+) -> String {
+    let mut out = format!(
+        "Passes of {name} ({norad_id}) from {} to {} UTC\n",
+        start.format(TIME_FORMAT),
+        end.format(TIME_FORMAT)
+    );
+    if passes.is_empty() {
+        out.push_str("No passes found.\n");
+        return out;
+    }
+
+    let _ = writeln!(
+        out,
+        "{:<19}  {:>5}  {:<19}  {:>7}  {:<19}  {:>5}  {:<8}",
+        "AOS (UTC)", "AZ", "TCA (UTC)", "MAX EL", "LOS (UTC)", "AZ", "VISIBLE"
+    );
+    for pass in passes {
+        let _ = writeln!(
+            out,
+            "{:<19}  {:>5.1}  {:<19}  {:>7.1}  {:<19}  {:>5.1}  {:<8}",
+            pass.aos.time.format(TIME_FORMAT),
+            pass.aos.look.azimuth_deg,
+            pass.culmination.time.format(TIME_FORMAT),
+            pass.max_elevation_deg(),
+            pass.los.time.format(TIME_FORMAT),
+            pass.los.look.azimuth_deg,
+            visibility_label(pass.visibility),
+        );
+    }
+    out
+}
+
+fn visibility_label(visibility: PassVisibility) -> &'static str {
+    match visibility {
+        PassVisibility::Visible => "visible",
+        PassVisibility::Eclipsed => "eclipsed",
+        PassVisibility::Daylight => "daylight",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn iss_catalog() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/iss.tle")
+    }
+
+    fn query(norad_id: u32) -> PassQuery {
+        PassQuery {
+            norad_id: NoradId::new(norad_id.into()).unwrap(),
+            location: Geodetic {
+                latitude_deg: 40.015,
+                longitude_deg: -105.27,
+                height_km: 1.655,
+            },
+            min_elevation_deg: None,
+            start: Some(parse_utc("2020-07-13T00:00:00Z").unwrap()),
+            hours: 24.0,
+            tle_path: Some(iss_catalog()),
+        }
+    }
+
+    #[test]
+    fn parses_utc_timestamps() {
+        let expected = NaiveDateTime::parse_from_str("2020-07-13 06:30:00", TIME_FORMAT).unwrap();
+        assert_eq!(parse_utc("2020-07-13T06:30:00Z").unwrap(), expected);
+        assert_eq!(parse_utc("2020-07-13 06:30:00").unwrap(), expected);
+        assert!(parse_utc("13/07/2020").is_err());
+    }
+
+    #[test]
+    fn renders_pass_table_from_local_catalog() {
+        let table = run_passes(&query(25544)).expect("passes should be predicted");
+        assert!(table.starts_with("Passes of ISS (ZARYA) (25544)"));
+        assert!(table.contains("MAX EL"));
+        assert!(table.lines().count() > 2);
+    }
+
+    #[test]
+    fn reports_missing_object() {
+        let err = run_passes(&query(5)).expect_err("object is not in the catalog");
+        assert!(err.contains("NORAD ID 5 is not in the catalog"), "{err}");
+    }
+
+    #[test]
+    fn rejects_non_positive_window() {
+        let err = run_passes(&PassQuery {
+            hours: 0.0,
+            ..query(25544)
+        })
+        .expect_err("window must be positive");
+        assert!(err.contains("--hours"));
+
+        let err = run_passes(&PassQuery {
+            hours: 1e12,
+            ..query(25544)
+        })
+        .expect_err("window must be representable");
+        assert!(err.contains("too long"), "{err}");
+    }
+}
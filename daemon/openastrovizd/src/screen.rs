@@ -0,0 +1,790 @@
+//! Long-window conjunction screening, run offline by `screen`.
+//!
+//! Screening the whole catalog for a week means billions of object pairs,
+//! so pairs go through filters of increasing cost and only the survivors
+//! reach the exact time of closest approach (TCA) solver:
+//!
+//! 1. The catalog is propagated every [`SAMPLE_STEP_S`] and hashed into a
+//!    voxel grid, keeping pairs in the same or touching voxels.
+//! 2. Pairs whose perigee-to-apogee bands do not overlap cannot meet.
+//! 3. Pairs that do not pass within the threshold moving in straight lines
+//!    for half a step either way are not approaching now.
+//! 4. Pairs whose orbits are too far apart where their planes intersect
+//!    cannot meet at all (the orbit-path filter).
+//! 5. The rest are refined with [`ApproachSolver`] around the sample.
+//!
+//! When screening a few primaries against the catalog, the band filter also
+//! drops every object that cannot meet any primary before propagation.
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Instant;
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use clap::ValueEnum;
+use openastroviz_core::conjunction::{ApproachSolver, ClosestApproach};
+use openastroviz_core::{NoradId, ObjectStatus, StateBuffer};
+use openastroviz_cpu::CpuBackend;
+use serde::Serialize;
+
+use crate::cache::CatalogCache;
+use crate::conjunction::{dot, linear_approach, sub, GridConfig, MAX_RELATIVE_SPEED_KPS};
+use crate::tle::{keep_newest, parse_catalog, OrbitalRecord};
+
+/// Seconds between catalog samples.
+const SAMPLE_STEP_S: f64 = 20.0;
+/// Sampling step of the TCA solver within the two steps around a sample.
+const REFINE_STEP_S: i64 = 5;
+/// How far two objects can stray from straight lines in half a sample step,
+/// pulled apart by gravity at up to 0.02 km/s².
+const LINEAR_PAD_KM: f64 = 1.0;
+/// Margin for the difference between the mean-element orbits the band and
+/// path filters use and the propagated ones: short-period terms and the
+/// Kozai mean motion.
+const ORBIT_PAD_KM: f64 = 25.0;
+/// Below this angle between two orbit planes, in radians, the line where
+/// they meet is too ill-defined for the path filter, which then passes.
+const MIN_PLANE_ANGLE_RAD: f64 = 0.01;
+/// Progress lines per screening.
+const PROGRESS_REPORTS: usize = 10;
+
+const EARTH_RADIUS_KM: f64 = 6378.137;
+const MU_KM3_MIN2: f64 = 398_600.441_8 * 3600.0;
+const J2: f64 = 1.082_626_68e-3;
+
+/// Which objects are screened against the rest of the catalog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Primaries {
+    /// Every object against every other.
+    All,
+    Ids(Vec<NoradId>),
+}
+
+impl FromStr for Primaries {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        if text.trim().eq_ignore_ascii_case("all") {
+            return Ok(Self::All);
+        }
+        text.split(',')
+            .map(|id| {
+                id.trim()
+                    .parse::<NoradId>()
+                    .map_err(|e| format!("invalid NORAD ID `{}`: {e}", id.trim()))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self::Ids)
+    }
+}
+
+/// Encodings `screen` can write.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ScreenFormat {
+    /// One approach per line with a header
+    Csv,
+    /// A JSON document with the screening window and its approaches
+    Json,
+}
+
+/// Parameters of a `screen` invocation.
+#[derive(Debug, Clone)]
+pub struct ScreenQuery {
+    pub primaries: Primaries,
+    pub start: Option<NaiveDateTime>,
+    pub days: f64,
+    pub threshold_km: f64,
+    /// Catalog to screen instead of the cached one.
+    pub catalog_path: Option<PathBuf>,
+    /// Where the service caches its catalogs.
+    pub cache_dir: PathBuf,
+    pub format: ScreenFormat,
+}
+
+/// Screen the catalog and render the approaches found, writing progress and
+/// throughput to `progress` as it goes.
+pub fn run_screen(query: &ScreenQuery, progress: &mut dyn Write) -> Result<String, String> {
+    if !query.days.is_finite() || query.days <= 0.0 {
+        return Err(format!("--days must be positive, got {}", query.days));
+    }
+    if !query.threshold_km.is_finite() || query.threshold_km <= 0.0 {
+        return Err(format!(
+            "--threshold-km must be positive, got {}",
+            query.threshold_km
+        ));
+    }
+    let start = query.start.unwrap_or_else(|| Utc::now().naive_utc());
+    let end = Duration::try_milliseconds((query.days * 86_400_000.0).round() as i64)
+        .and_then(|span| start.checked_add_signed(span))
+        .ok_or_else(|| format!("--days {} is too long", query.days))?;
+    let records = load_catalog(query)?;
+    let screening = Screening::new(&records, &query.primaries, query.threshold_km, end)?;
+    let approaches = screening.run(start, end, progress);
+    Ok(match query.format {
+        ScreenFormat::Csv => format_csv(&approaches),
+        ScreenFormat::Json => format_json(start, end, query.threshold_km, &approaches),
+    })
+}
+
+fn load_catalog(query: &ScreenQuery) -> Result<Vec<OrbitalRecord>, String> {
+    if let Some(path) = &query.catalog_path {
+        let body = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+        return Ok(keep_newest(parse_catalog(&body)?.records));
+    }
+    let cache = CatalogCache::new(&query.cache_dir);
+    let mut records = Vec::new();
+    for meta in cache
+        .entries()
+        .map_err(|e| format!("failed to read the catalog cache: {e}"))?
+    {
+        if let Some((_, cached)) = cache
+            .load(&meta.source)
+            .map_err(|e| format!("failed to read the cached {}: {e}", meta.source))?
+        {
+            records.extend(cached);
+        }
+    }
+    if records.is_empty() {
+        return Err(format!(
+            "no cached catalog in {}; pass --catalog or let the service download one first",
+            query.cache_dir.display()
+        ));
+    }
+    Ok(keep_newest(records))
+}
+
+/// A refined approach between two catalog objects.
+#[derive(Debug, Clone)]
+struct Found {
+    primary_id: NoradId,
+    primary_name: String,
+    secondary_id: NoradId,
+    secondary_name: String,
+    approach: ClosestApproach,
+}
+
+/// How many pairs each stage let through, for the progress report.
+#[derive(Debug, Default)]
+struct Counts {
+    propagations: u64,
+    grid: u64,
+    bands: u64,
+    linear: u64,
+    paths: u64,
+}
+
+struct Screening<'a> {
+    records: Vec<&'a OrbitalRecord>,
+    orbits: Vec<MeanOrbit>,
+    /// Which of `records` are primaries; all of them when screening all.
+    primary: Vec<bool>,
+    threshold_km: f64,
+}
+
+impl<'a> Screening<'a> {
+    fn new(
+        catalog: &'a [OrbitalRecord],
+        primaries: &Primaries,
+        threshold_km: f64,
+        end: NaiveDateTime,
+    ) -> Result<Self, String> {
+        let orbits: Vec<MeanOrbit> = catalog
+            .iter()
+            .map(|record| MeanOrbit::new(record, end))
+            .collect();
+        let is_primary = match primaries {
+            Primaries::All => vec![true; catalog.len()],
+            Primaries::Ids(ids) => {
+                let mut is_primary = vec![false; catalog.len()];
+                for id in ids {
+                    let at = catalog
+                        .iter()
+                        .position(|record| record.norad_id == *id)
+                        .ok_or_else(|| format!("NORAD ID {id} is not in the catalog"))?;
+                    is_primary[at] = true;
+                }
+                is_primary
+            }
+        };
+        // With a list of primaries, objects whose band meets none of theirs
+        // need not be propagated at all.
+        let keep: Vec<usize> = match primaries {
+            Primaries::All => (0..catalog.len()).collect(),
+            Primaries::Ids(_) => {
+                let primary_orbits: Vec<&MeanOrbit> = (0..catalog.len())
+                    .filter(|&i| is_primary[i])
+                    .map(|i| &orbits[i])
+                    .collect();
+                (0..catalog.len())
+                    .filter(|&i| {
+                        is_primary[i]
+                            || primary_orbits
+                                .iter()
+                                .any(|primary| orbits[i].bands_meet(primary, threshold_km))
+                    })
+                    .collect()
+            }
+        };
+        Ok(Self {
+            records: keep.iter().map(|&i| &catalog[i]).collect(),
+            orbits: keep.iter().map(|&i| orbits[i]).collect(),
+            primary: keep.iter().map(|&i| is_primary[i]).collect(),
+            threshold_km,
+        })
+    }
+
+    fn run(
+        &self,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        progress: &mut dyn Write,
+    ) -> Vec<Found> {
+        let started = Instant::now();
+        let span_s = (end - start).num_milliseconds() as f64 / 1000.0;
+        let samples = (span_s / SAMPLE_STEP_S).ceil() as usize + 1;
+        let step = Duration::milliseconds((SAMPLE_STEP_S * 1000.0) as i64);
+        let half_step_s = SAMPLE_STEP_S / 2.0;
+        let grid = GridConfig::new(
+            self.threshold_km + LINEAR_PAD_KM + MAX_RELATIVE_SPEED_KPS * half_step_s,
+        );
+        let backend = CpuBackend::new(
+            self.records
+                .iter()
+                .map(|record| record.propagator.clone())
+                .collect(),
+        );
+        let mut states = StateBuffer::default();
+        let mut counts = Counts::default();
+        let mut found: Vec<Found> = Vec::new();
+        // Approaches already refined per pair, so neighbouring samples that
+        // flag the same encounter report it once.
+        let mut seen: HashMap<(usize, usize), Vec<NaiveDateTime>> = HashMap::new();
+        let mut reported = 0;
+
+        for sample in 0..samples {
+            let time = (start + step * sample as i32).min(end);
+            backend.propagate_datetime(time, &mut states);
+            let ok: Vec<usize> = (0..self.records.len())
+                .filter(|&i| states.status[i] == ObjectStatus::Ok)
+                .collect();
+            counts.propagations += ok.len() as u64;
+            let position = |i: usize| [states.x_km[i], states.y_km[i], states.z_km[i]];
+            let velocity = |i: usize| [states.vx_km_s[i], states.vy_km_s[i], states.vz_km_s[i]];
+            let positions: Vec<[f64; 3]> = ok.iter().map(|&i| position(i)).collect();
+
+            for (a, b) in grid.candidate_pairs(&positions) {
+                let (a, b) = (ok[a], ok[b]);
+                if !self.primary[a] && !self.primary[b] {
+                    continue;
+                }
+                counts.grid += 1;
+                if !self.orbits[a].bands_meet(&self.orbits[b], self.threshold_km) {
+                    continue;
+                }
+                counts.bands += 1;
+                let (_, miss) = linear_approach(
+                    sub(position(b), position(a)),
+                    sub(velocity(b), velocity(a)),
+                    half_step_s,
+                );
+                if miss >= self.threshold_km + LINEAR_PAD_KM {
+                    continue;
+                }
+                counts.linear += 1;
+                let since_epoch = |i: usize| {
+                    (time - self.records[i].propagator.epoch()).num_milliseconds() as f64 / 60_000.0
+                };
+                if !self.orbits[a].paths_meet(
+                    since_epoch(a),
+                    &self.orbits[b],
+                    since_epoch(b),
+                    self.threshold_km,
+                ) {
+                    continue;
+                }
+                counts.paths += 1;
+                let (primary, secondary) = self.orient(a, b);
+                let Some(approach) = self.refine(primary, secondary, time, step, &seen) else {
+                    continue;
+                };
+                seen.entry((primary, secondary))
+                    .or_default()
+                    .push(approach.tca);
+                found.push(Found {
+                    primary_id: self.records[primary].norad_id,
+                    primary_name: self.records[primary].name.clone(),
+                    secondary_id: self.records[secondary].norad_id,
+                    secondary_name: self.records[secondary].name.clone(),
+                    approach,
+                });
+            }
+
+            let percent = (sample + 1) * 100 / samples;
+            if percent >= (reported + 1) * 100 / PROGRESS_REPORTS {
+                reported = percent * PROGRESS_REPORTS / 100;
+                let elapsed = started.elapsed().as_secs_f64().max(1e-9);
+                let _ = writeln!(
+                    progress,
+                    "Screened {:.2} of {:.2} days ({percent}%): {} propagations at {:.0}/s, \
+                     {} candidate pairs, {} approaches",
+                    (time - start).num_milliseconds() as f64 / 86_400_000.0,
+                    span_s / 86_400.0,
+                    counts.propagations,
+                    counts.propagations as f64 / elapsed,
+                    counts.grid,
+                    found.len(),
+                );
+            }
+        }
+
+        let elapsed = started.elapsed().as_secs_f64().max(1e-9);
+        let _ = writeln!(
+            progress,
+            "Screened {} objects ({} primaries) in {elapsed:.1} s: {} propagations ({:.0}/s); \
+             candidate pairs {}, after the band filter {}, the straight-line check {} and \
+             the orbit-path filter {}; {} approaches under {} km",
+            self.records.len(),
+            self.primary.iter().filter(|&&primary| primary).count(),
+            counts.propagations,
+            counts.propagations as f64 / elapsed,
+            counts.grid,
+            counts.bands,
+            counts.linear,
+            counts.paths,
+            found.len(),
+            self.threshold_km,
+        );
+
+        found.sort_by(|a, b| {
+            a.approach
+                .tca
+                .cmp(&b.approach.tca)
+                .then_with(|| a.primary_id.cmp(&b.primary_id))
+                .then_with(|| a.secondary_id.cmp(&b.secondary_id))
+        });
+        found
+    }
+
+    /// The pair as (primary, secondary): the primary of the two, or the one
+    /// with the lower NORAD ID when both are.
+    fn orient(&self, a: usize, b: usize) -> (usize, usize) {
+        match (self.primary[a], self.primary[b]) {
+            (true, false) => (a, b),
+            (false, true) => (b, a),
+            _ if self.records[a].norad_id <= self.records[b].norad_id => (a, b),
+            _ => (b, a),
+        }
+    }
+
+    /// The approach within a step of `time`, if it is under the threshold,
+    /// falls inside that window and was not found from an earlier sample.
+    fn refine(
+        &self,
+        primary: usize,
+        secondary: usize,
+        time: NaiveDateTime,
+        step: Duration,
+        seen: &HashMap<(usize, usize), Vec<NaiveDateTime>>,
+    ) -> Option<ClosestApproach> {
+        let (from, to) = (time - step, time + step);
+        // An object that stops propagating inside the window has decayed,
+        // and has nothing left to collide with.
+        let approach = ApproachSolver::new(
+            &self.records[primary].propagator,
+            &self.records[secondary].propagator,
+        )
+        .with_step(Duration::seconds(REFINE_STEP_S))
+        .closest_approach(from, to)
+        .ok()??;
+        let inside = approach.tca > from && approach.tca < to;
+        let repeated = seen
+            .get(&(primary, secondary))
+            .is_some_and(|times| times.iter().any(|&tca| (tca - approach.tca).abs() < step));
+        (inside && !repeated && approach.miss_distance_km < self.threshold_km).then_some(approach)
+    }
+}
+
+/// The shape and orientation of an orbit from its mean elements, with the
+/// secular J2 drift of its node and perigee.
+#[derive(Debug, Clone, Copy)]
+struct MeanOrbit {
+    semi_major_axis_km: f64,
+    eccentricity: f64,
+    inclination: f64,
+    right_ascension: f64,
+    argument_of_perigee: f64,
+    /// Node and perigee drift in rad/min.
+    node_rate: f64,
+    perigee_rate: f64,
+    /// How far the semi-major axis may fall between the element epoch and
+    /// the end of the screening window at the current decay rate.
+    decay_km: f64,
+}
+
+impl MeanOrbit {
+    /// The orbit of `record`, allowing for decay until `end`.
+    fn new(record: &OrbitalRecord, end: NaiveDateTime) -> Self {
+        let elements = record.propagator.elements();
+        let mean_motion = elements.mean_motion * std::f64::consts::TAU / 1440.0;
+        let a = (MU_KM3_MIN2 / (mean_motion * mean_motion)).cbrt();
+        let e = elements.eccentricity;
+        let i = elements.inclination.to_radians();
+        let p = a * (1.0 - e * e);
+        let j2_rate = 1.5 * J2 * (EARTH_RADIUS_KM / p).powi(2) * mean_motion;
+        // The TLE field is half the first derivative, in rev/day². Stale
+        // elements have been decaying since their epoch, not just over the
+        // window.
+        let days = (end - record.propagator.epoch()).num_seconds().max(0) as f64 / 86_400.0;
+        let mean_motion_gain = 2.0 * elements.mean_motion_dot.max(0.0) * days;
+        let decay_km = 2.0 / 3.0 * a * mean_motion_gain / elements.mean_motion;
+        Self {
+            semi_major_axis_km: a,
+            eccentricity: e,
+            inclination: i,
+            right_ascension: elements.right_ascension.to_radians(),
+            argument_of_perigee: elements.argument_of_perigee.to_radians(),
+            node_rate: -j2_rate * i.cos(),
+            perigee_rate: j2_rate * (2.0 - 2.5 * i.sin().powi(2)),
+            decay_km,
+        }
+    }
+
+    fn lowest_perigee_km(&self) -> f64 {
+        self.semi_major_axis_km * (1.0 - self.eccentricity) - self.decay_km
+    }
+
+    fn apogee_km(&self) -> f64 {
+        self.semi_major_axis_km * (1.0 + self.eccentricity)
+    }
+
+    /// Whether the radii the two orbits sweep come within `threshold_km`.
+    fn bands_meet(&self, other: &MeanOrbit, threshold_km: f64) -> bool {
+        let reach = threshold_km + ORBIT_PAD_KM;
+        self.lowest_perigee_km().max(other.lowest_perigee_km())
+            <= self.apogee_km().min(other.apogee_km()) + reach
+    }
+
+    /// Whether the two orbits, `minutes` and `other_minutes` after their
+    /// epochs, pass within `threshold_km` of each other where their planes
+    /// intersect.
+    fn paths_meet(
+        &self,
+        minutes: f64,
+        other: &MeanOrbit,
+        other_minutes: f64,
+        threshold_km: f64,
+    ) -> bool {
+        let (p1, q1, w1) = self.axes(minutes);
+        let (p2, q2, w2) = other.axes(other_minutes);
+        let node = cross(w1, w2);
+        let sin_angle = dot(node, node).sqrt();
+        if sin_angle < MIN_PLANE_ANGLE_RAD {
+            return true;
+        }
+        // Near the node line the orbits diverge at the plane angle, so a
+        // close pass can lie a little off it, where the radii differ by up
+        // to the eccentricity times the offset.
+        // Decay lowers either orbit by up to its margin.
+        let reach = threshold_km
+            + ORBIT_PAD_KM
+            + self.decay_km
+            + other.decay_km
+            + (self.eccentricity + other.eccentricity) * threshold_km / sin_angle;
+        [1.0, -1.0].into_iter().any(|sign| {
+            let direction = node.map(|x| sign * x);
+            (self.radius_towards(direction, p1, q1) - other.radius_towards(direction, p2, q2)).abs()
+                <= reach
+        })
+    }
+
+    /// Perigee direction, the direction 90° ahead of it in the orbit plane,
+    /// and the orbit normal, `minutes` after the epoch.
+    fn axes(&self, minutes: f64) -> ([f64; 3], [f64; 3], [f64; 3]) {
+        let (sin_node, cos_node) = (self.right_ascension + self.node_rate * minutes).sin_cos();
+        let (sin_perigee, cos_perigee) =
+            (self.argument_of_perigee + self.perigee_rate * minutes).sin_cos();
+        let (sin_i, cos_i) = self.inclination.sin_cos();
+        let perigee = [
+            cos_node * cos_perigee - sin_node * sin_perigee * cos_i,
+            sin_node * cos_perigee + cos_node * sin_perigee * cos_i,
+            sin_perigee * sin_i,
+        ];
+        let ahead = [
+            -cos_node * sin_perigee - sin_node * cos_perigee * cos_i,
+            -sin_node * sin_perigee + cos_node * cos_perigee * cos_i,
+            cos_perigee * sin_i,
+        ];
+        let normal = [sin_node * sin_i, -cos_node * sin_i, cos_i];
+        (perigee, ahead, normal)
+    }
+
+    /// Radius of the orbit in `direction`, which lies in its plane.
+    fn radius_towards(&self, direction: [f64; 3], perigee: [f64; 3], ahead: [f64; 3]) -> f64 {
+        let true_anomaly = dot(direction, ahead).atan2(dot(direction, perigee));
+        let e = self.eccentricity;
+        self.semi_major_axis_km * (1.0 - e * e) / (1.0 + e * true_anomaly.cos())
+    }
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3fZ";
+
+fn format_csv(found: &[Found]) -> String {
+    let mut out = String::from(
+        "primary_id,primary_name,secondary_id,secondary_name,tca,miss_distance_km,\
+         relative_speed_km_s,radial_km,transverse_km,normal_km\n",
+    );
+    for found in found {
+        let approach = &found.approach;
+        let [radial, transverse, normal] = approach.miss_rtn_km;
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{},{:.3},{:.3},{:.3},{:.3},{:.3}",
+            found.primary_id.get(),
+            csv_field(&found.primary_name),
+            found.secondary_id.get(),
+            csv_field(&found.secondary_name),
+            approach.tca.format(TIME_FORMAT),
+            approach.miss_distance_km,
+            approach.relative_speed_km_s(),
+            radial,
+            transverse,
+            normal,
+        );
+    }
+    out
+}
+
+/// `text` quoted if it holds anything CSV would split on.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Machine-readable result of `screen`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Report<'a> {
+    start: String,
+    end: String,
+    threshold_km: f64,
+    approaches: Vec<ReportedApproach<'a>>,
+}
+
+/// An approach in the fields of the web client's `CloseApproach`, plus the
+/// names and the RTN miss vector.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportedApproach<'a> {
+    time: String,
+    primary_id: String,
+    primary_name: &'a str,
+    secondary_id: String,
+    secondary_name: &'a str,
+    miss_distance_km: f64,
+    relative_velocity_kps: f64,
+    /// Secondary minus primary position in the primary's radial,
+    /// transverse and normal axes.
+    miss_rtn_km: [f64; 3],
+}
+
+fn format_json(
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    threshold_km: f64,
+    found: &[Found],
+) -> String {
+    let report = Report {
+        start: start.format(TIME_FORMAT).to_string(),
+        end: end.format(TIME_FORMAT).to_string(),
+        threshold_km,
+        approaches: found
+            .iter()
+            .map(|found| ReportedApproach {
+                time: found.approach.tca.format(TIME_FORMAT).to_string(),
+                primary_id: found.primary_id.get().to_string(),
+                primary_name: &found.primary_name,
+                secondary_id: found.secondary_id.get().to_string(),
+                secondary_name: &found.secondary_name,
+                miss_distance_km: found.approach.miss_distance_km,
+                relative_velocity_kps: found.approach.relative_speed_km_s(),
+                miss_rtn_km: found.approach.miss_rtn_km,
+            })
+            .collect(),
+    };
+    let mut out = serde_json::to_string_pretty(&report).expect("reports always serialise");
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::passes::parse_utc;
+
+    fn id(norad_id: u32) -> NoradId {
+        NoradId::new(norad_id.into()).unwrap()
+    }
+
+    fn query(primaries: Primaries) -> ScreenQuery {
+        ScreenQuery {
+            primaries,
+            start: Some(parse_utc("2020-07-12T21:00:00Z").unwrap()),
+            days: 0.25,
+            threshold_km: 5.0,
+            catalog_path: Some(
+                PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/crossing.tle"),
+            ),
+            cache_dir: PathBuf::from("/nonexistent"),
+            format: ScreenFormat::Csv,
+        }
+    }
+
+    fn circular(altitude_km: f64, inclination_deg: f64, right_ascension_deg: f64) -> MeanOrbit {
+        MeanOrbit {
+            semi_major_axis_km: EARTH_RADIUS_KM + altitude_km,
+            eccentricity: 0.0,
+            inclination: inclination_deg.to_radians(),
+            right_ascension: right_ascension_deg.to_radians(),
+            argument_of_perigee: 0.0,
+            node_rate: 0.0,
+            perigee_rate: 0.0,
+            decay_km: 0.0,
+        }
+    }
+
+    #[test]
+    fn parses_primaries() {
+        assert_eq!("all".parse::<Primaries>().unwrap(), Primaries::All);
+        assert_eq!(
+            "25544, 5".parse::<Primaries>().unwrap(),
+            Primaries::Ids(vec![id(25544), id(5)])
+        );
+        assert!("25544,ISS".parse::<Primaries>().is_err());
+    }
+
+    #[test]
+    fn finds_only_the_crossing_pair() {
+        let mut progress = Vec::new();
+        let csv = run_screen(&query(Primaries::All), &mut progress).unwrap();
+        let rows: Vec<&str> = csv.lines().skip(1).collect();
+        // The two orbits meet at both nodes, twice per revolution.
+        assert_eq!(rows.len(), 8, "{csv}");
+        for row in &rows {
+            assert!(row.starts_with("90001,CROSSING A,90002,CROSSING B DEB,"));
+            let miss: f64 = row.split(',').nth(5).unwrap().parse().unwrap();
+            assert!((miss - 2.7).abs() < 0.1, "{row}");
+        }
+        assert!(rows[0].contains(",2020-07-12T21:16:0"), "{}", rows[0]);
+
+        let progress = String::from_utf8(progress).unwrap();
+        assert_eq!(progress.lines().count(), PROGRESS_REPORTS + 1);
+        assert!(progress.contains("propagations"), "{progress}");
+    }
+
+    #[test]
+    fn listed_primaries_come_first() {
+        let mut query = query(Primaries::Ids(vec![id(90002)]));
+        query.format = ScreenFormat::Json;
+        let json = run_screen(&query, &mut std::io::sink()).unwrap();
+        let report: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let approaches = report["approaches"].as_array().unwrap();
+        assert_eq!(approaches.len(), 8);
+        assert_eq!(approaches[0]["primaryId"], "90002");
+        assert_eq!(approaches[0]["secondaryId"], "90001");
+        assert_eq!(report["end"], "2020-07-13T03:00:00.000Z");
+    }
+
+    #[test]
+    fn rejects_bad_queries() {
+        let err = run_screen(
+            &query(Primaries::Ids(vec![id(99999)])),
+            &mut std::io::sink(),
+        )
+        .unwrap_err();
+        assert!(err.contains("not in the catalog"), "{err}");
+
+        let mut bad_days = query(Primaries::All);
+        bad_days.days = 0.0;
+        assert!(run_screen(&bad_days, &mut std::io::sink())
+            .unwrap_err()
+            .contains("--days"));
+        bad_days.days = 1e9;
+        assert!(run_screen(&bad_days, &mut std::io::sink())
+            .unwrap_err()
+            .contains("too long"));
+
+        let mut no_cache = query(Primaries::All);
+        no_cache.catalog_path = None;
+        assert!(run_screen(&no_cache, &mut std::io::sink())
+            .unwrap_err()
+            .contains("no cached catalog"));
+    }
+
+    #[test]
+    fn bands_separate_leo_from_geo() {
+        let leo = circular(400.0, 51.6, 0.0);
+        let geo = circular(35_786.0, 0.1, 0.0);
+        assert!(!leo.bands_meet(&geo, 5.0));
+        assert!(leo.bands_meet(&circular(420.0, 98.0, 0.0), 5.0));
+    }
+
+    #[test]
+    fn paths_must_cross_at_the_same_radius() {
+        let orbit = circular(400.0, 45.0, 0.0);
+        assert!(orbit.paths_meet(0.0, &circular(410.0, 135.0, 0.0), 0.0, 5.0));
+        assert!(!orbit.paths_meet(0.0, &circular(450.0, 135.0, 0.0), 0.0, 5.0));
+        // Nearly coplanar orbits always pass.
+        assert!(orbit.paths_meet(0.0, &circular(450.0, 45.1, 0.0), 0.0, 5.0));
+    }
+
+    #[test]
+    fn decay_margins_span_the_window_from_the_epoch() {
+        // A fast-decaying ISS: 0.01 rev/day² added to its mean motion.
+        let line1 = "1 25544U 98067A   20194.88612269  .00500000  00000-0 -31515-4 0  999";
+        let line1 = format!("{line1}{}", openastroviz_core::tle::checksum(line1));
+        let catalog = format!(
+            "DECAYING\n{line1}\n\
+             2 25544  51.6461 221.2784 0001413  89.1723 280.4612 15.49507896236008\n"
+        );
+        let record = &parse_catalog(&catalog).unwrap().records[0];
+        let epoch = record.propagator.epoch();
+        let decay = |end| MeanOrbit::new(record, end).decay_km;
+
+        let week = decay(epoch + Duration::days(7));
+        assert!(week > 5.0, "{week}");
+        assert!((decay(epoch + Duration::days(30)) / week - 30.0 / 7.0).abs() < 1e-9);
+        // Stale elements have decayed since their epoch, not since the start.
+        assert!((decay(epoch + Duration::days(37)) / week - 37.0 / 7.0).abs() < 1e-9);
+        assert_eq!(decay(epoch - Duration::days(1)), 0.0);
+
+        // The band then reaches down to lower orbits.
+        let orbit = MeanOrbit::new(record, epoch + Duration::days(30));
+        let below = circular(
+            orbit.lowest_perigee_km() - EARTH_RADIUS_KM - 30.0,
+            98.0,
+            0.0,
+        );
+        assert!(orbit.bands_meet(&below, 5.0));
+        assert!(!MeanOrbit::new(record, epoch).bands_meet(&below, 5.0));
+    }
+
+    #[test]
+    fn quotes_csv_names() {
+        assert_eq!(csv_field("ISS (ZARYA)"), "ISS (ZARYA)");
+        assert_eq!(csv_field("A, \"B\""), "\"A, \"\"B\"\"\"");
+    }
+}
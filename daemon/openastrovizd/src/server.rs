@@ -0,0 +1,290 @@
+//! The network face of the service: the `/ws/orbits` WebSocket stream and,
+//! in [`crate::api`], the HTTP API.
+//!
+//! A ticker propagates the live catalog once per `[server] tick_ms` and
+//! publishes the [`Frame`] on a watch channel. Each subscriber gets a full
+//! snapshot when it subscribes and whenever the set of objects changes, and
+//! an update with the new states otherwise. Slow clients skip frames rather
+//! than queueing them.
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::Response;
+use axum::routing::get;
+use axum::Router;
+use chrono::{TimeDelta, Utc};
+use tokio::net::TcpListener;
+use tokio::sync::{watch, RwLock};
+
+use crate::api;
+use crate::conjunction::Screener;
+use crate::logging::{debug, error};
+use crate::orbits::{self, ClientMessage, Frame, Framer, StreamMessage};
+use crate::source::LiveCatalog;
+
+/// The only stream clients can subscribe to so far.
+const ORBITS_STREAM: &str = "orbits";
+
+/// What request handlers share.
+#[derive(Clone)]
+pub struct AppState {
+    pub frames: watch::Receiver<Arc<Frame>>,
+    pub catalog: Arc<RwLock<LiveCatalog>>,
+    pub screener: Screener,
+}
+
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/ws/orbits", get(orbits_socket))
+        .merge(api::routes())
+        .with_state(state)
+}
+
+/// Serve `router` on `listener` until the process ends.
+pub async fn serve(listener: TcpListener, state: AppState) -> io::Result<()> {
+    axum::serve(listener, router(state)).await
+}
+
+/// A channel holding an empty frame, to be fed by [`tick`].
+pub fn frame_channel() -> (watch::Sender<Arc<Frame>>, watch::Receiver<Arc<Frame>>) {
+    watch::channel(Arc::new(Frame::new(
+        orbits::snapshot(Utc::now(), Vec::new()),
+        0,
+    )))
+}
+
+/// Propagate `catalog` every `interval`, screen it with `screener` and
+/// publish each frame on `frames`.
+pub async fn tick(
+    catalog: Arc<RwLock<LiveCatalog>>,
+    interval: Duration,
+    screener: Screener,
+    frames: watch::Sender<Arc<Frame>>,
+) {
+    let mut framer = Framer::new(screener);
+    let origin = (tokio::time::Instant::now(), Utc::now());
+    let mut ticks = tokio::time::interval_at(origin.0, interval);
+    ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    loop {
+        // Frames stand for the instant they were due, not when they got to
+        // run, so their screening windows meet end to end.
+        let due = ticks.tick().await;
+        let epoch = origin.1 + TimeDelta::from_std(due - origin.0).unwrap_or_default();
+        framer.load(&*catalog.read().await);
+        // Propagation is CPU-bound and fans out over rayon, so keep it off
+        // the async workers.
+        let built = tokio::task::spawn_blocking(move || {
+            let frame = framer.frame(epoch);
+            (framer, frame)
+        })
+        .await;
+        let frame = match built {
+            Ok((built, frame)) => {
+                framer = built;
+                frame
+            }
+            Err(err) => {
+                // The framer went down with the task; start afresh, reloading
+                // the catalog on the next tick, rather than freeze the stream.
+                error!("Orbital frame propagation failed: {err}; rebuilding the propagator");
+                framer = Framer::new(screener);
+                continue;
+            }
+        };
+        frames.send_replace(Arc::new(frame));
+    }
+}
+
+async fn orbits_socket(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
+    ws.on_upgrade(move |socket| stream_orbits(socket, state.frames))
+}
+
+async fn stream_orbits(mut socket: WebSocket, mut frames: watch::Receiver<Arc<Frame>>) {
+    let mut subscribed = false;
+    // The layout of the last snapshot sent; updates only make sense to a
+    // client that holds the same objects.
+    let mut sent_layout = None;
+    loop {
+        tokio::select! {
+            incoming = socket.recv() => {
+                let text = match incoming {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+                let reply = match serde_json::from_str::<ClientMessage>(&text) {
+                    Ok(ClientMessage::Subscribe { stream }) if stream == ORBITS_STREAM => {
+                        subscribed = true;
+                        sent_layout = None;
+                        frames.mark_changed();
+                        None
+                    }
+                    Ok(ClientMessage::Unsubscribe { stream }) if stream == ORBITS_STREAM => {
+                        subscribed = false;
+                        None
+                    }
+                    Ok(
+                        ClientMessage::Subscribe { stream }
+                        | ClientMessage::Unsubscribe { stream },
+                    ) => Some(format!("unknown stream `{stream}`")),
+                    Err(err) => Some(format!("unreadable message: {err}")),
+                };
+                if let Some(message) = reply {
+                    debug!("Orbital stream client sent a bad message: {message}");
+                    let error = orbits::to_json(&StreamMessage::Error { message });
+                    if socket.send(Message::Text(error.into())).await.is_err() {
+                        break;
+                    }
+                }
+            }
+            changed = frames.changed(), if subscribed => {
+                if changed.is_err() {
+                    break;
+                }
+                let frame = Arc::clone(&frames.borrow_and_update());
+                let message = if sent_layout == Some(frame.layout) {
+                    frame.update_message()
+                } else {
+                    sent_layout = Some(frame.layout);
+                    frame.snapshot_message()
+                };
+                if socket.send(Message::Text(message.into())).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{GEO, VANGUARD};
+    use crate::tle::{parse_catalog_with, ParseMode};
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite;
+
+    fn records(text: &str) -> Vec<crate::tle::OrbitalRecord> {
+        parse_catalog_with(text, ParseMode::Lenient)
+            .unwrap()
+            .records
+    }
+
+    async fn next_json<S>(socket: &mut S) -> serde_json::Value
+    where
+        S: StreamExt<Item = Result<tungstenite::Message, tungstenite::Error>> + Unpin,
+    {
+        loop {
+            let message = tokio::time::timeout(Duration::from_secs(5), socket.next())
+                .await
+                .expect("no message within 5 s")
+                .expect("socket closed")
+                .unwrap();
+            if let tungstenite::Message::Text(text) = message {
+                return serde_json::from_str(&text).unwrap();
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn streams_snapshots_then_updates() {
+        let catalog = Arc::new(RwLock::new(LiveCatalog {
+            records: records(VANGUARD),
+            fetched_at: None,
+            sources: Vec::new(),
+            revision: 1,
+        }));
+        let (frames, receiver) = frame_channel();
+        tokio::spawn(tick(
+            Arc::clone(&catalog),
+            Duration::from_millis(20),
+            Screener::default(),
+            frames,
+        ));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/ws/orbits", listener.local_addr().unwrap());
+        tokio::spawn(serve(
+            listener,
+            AppState {
+                frames: receiver,
+                catalog: Arc::clone(&catalog),
+                screener: Screener::default(),
+            },
+        ));
+
+        let (mut socket, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+        socket
+            .send(tungstenite::Message::text(
+                r#"{"kind":"subscribe","stream":"orbits"}"#,
+            ))
+            .await
+            .unwrap();
+        // The first frame may still be the empty one published at startup.
+        let mut message = next_json(&mut socket).await;
+        while message["snapshot"]["stats"]["tracked"] == 0 {
+            assert_eq!(message["kind"], "snapshot");
+            message = next_json(&mut socket).await;
+        }
+        assert_eq!(message["kind"], "snapshot");
+        assert_eq!(message["snapshot"]["objects"][0]["name"], "VANGUARD 1");
+
+        let update = next_json(&mut socket).await;
+        assert_eq!(update["kind"], "update");
+        assert_eq!(update["update"]["objects"][0]["id"], "5");
+
+        // A new object reaches the client as a fresh snapshot.
+        {
+            let mut live = catalog.write().await;
+            live.records.extend(records(GEO));
+            live.revision += 1;
+        }
+        let mut message = next_json(&mut socket).await;
+        while message["kind"] == "update" {
+            message = next_json(&mut socket).await;
+        }
+        assert_eq!(message["kind"], "snapshot");
+        assert_eq!(message["snapshot"]["stats"]["tracked"], 2);
+        assert_eq!(message["snapshot"]["objects"][1]["kind"], "rocket_body");
+
+        socket
+            .send(tungstenite::Message::text(
+                r#"{"kind":"subscribe","stream":"telemetry"}"#,
+            ))
+            .await
+            .unwrap();
+        let mut message = next_json(&mut socket).await;
+        while message["kind"] != "error" {
+            message = next_json(&mut socket).await;
+        }
+        assert_eq!(message["message"], "unknown stream `telemetry`");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn waits_for_a_subscription() {
+        let (_frames, receiver) = frame_channel();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/ws/orbits", listener.local_addr().unwrap());
+        tokio::spawn(serve(
+            listener,
+            AppState {
+                frames: receiver,
+                catalog: Arc::default(),
+                screener: Screener::default(),
+            },
+        ));
+
+        let (mut socket, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+        let early = tokio::time::timeout(Duration::from_millis(200), socket.next()).await;
+        assert!(early.is_err(), "sent {early:?} before any subscription");
+        socket
+            .send(tungstenite::Message::text(
+                r#"{"kind":"subscribe","stream":"orbits"}"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(next_json(&mut socket).await["kind"], "snapshot");
+    }
+}
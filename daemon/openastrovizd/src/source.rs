@@ -0,0 +1,1114 @@
+//! Where the refresh loop reads the orbital catalog from.
+//!
+//! A [`CatalogSource`] is chosen in the `[catalog]` section of the daemon
+//! config file. Local files and directories let the service run without
+//! network access; several sources can be merged, for example a directory
+//! of operator-maintained element sets on top of a mirrored feed.
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cache::{format_age, CacheMeta, CatalogCache};
+use crate::logging::{debug, error, info, warn};
+use crate::spacetrack::{
+    RateLimiter, SharedLimiter, SpaceTrack, SpaceTrackConfig, MIN_QUERY_SPACING,
+};
+use crate::tle::{
+    fetch_catalog, keep_newest, parse_catalog_with, OrbitalRecord, ParseMode, CELESTRAK_ACTIVE_URL,
+};
+
+/// A place to read the orbital catalog from. Any format
+/// [`parse_catalog_with`] reads is accepted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+pub enum CatalogSource {
+    /// A single catalog file, read again whenever it changes.
+    File { path: PathBuf },
+    /// Every file in a directory, read again whenever one is added, removed
+    /// or modified. Hidden files are ignored, so a feed can be written to a
+    /// dotfile and renamed into place.
+    Directory { path: PathBuf },
+    /// A catalog downloaded once per HTTP refresh interval.
+    Http { url: String },
+    /// A Space-Track GP query, run at most once an hour.
+    #[serde(rename = "spacetrack")]
+    SpaceTrack(SpaceTrackConfig),
+    /// Several sources combined. When more than one holds an object, the
+    /// element set with the newest epoch wins.
+    Merged { sources: Vec<CatalogSource> },
+}
+
+impl Default for CatalogSource {
+    fn default() -> Self {
+        Self::Http {
+            url: CELESTRAK_ACTIVE_URL.to_string(),
+        }
+    }
+}
+
+impl CatalogSource {
+    /// Resolve relative paths against `base`, the directory of the config
+    /// file that named them.
+    pub fn resolve(self, base: &Path) -> Self {
+        match self {
+            Self::File { path } => Self::File {
+                path: base.join(path),
+            },
+            Self::Directory { path } => Self::Directory {
+                path: base.join(path),
+            },
+            Self::Http { url } => Self::Http { url },
+            Self::SpaceTrack(config) => Self::SpaceTrack(config),
+            Self::Merged { sources } => Self::Merged {
+                sources: sources
+                    .into_iter()
+                    .map(|source| source.resolve(base))
+                    .collect(),
+            },
+        }
+    }
+
+    /// Complete Space-Track credentials from the environment, see
+    /// [`SpaceTrackConfig::fill_credentials`].
+    pub fn fill_credentials(&mut self, var: &impl Fn(&str) -> Option<String>) {
+        match self {
+            Self::SpaceTrack(config) => config.fill_credentials(var),
+            Self::Merged { sources } => sources
+                .iter_mut()
+                .for_each(|source| source.fill_credentials(var)),
+            _ => {}
+        }
+    }
+
+    /// Fail if a Space-Track source, here or merged in, lacks credentials.
+    pub fn require_credentials(&self) -> Result<(), String> {
+        match self {
+            Self::SpaceTrack(config) => config.require_credentials(),
+            Self::Merged { sources } => sources
+                .iter()
+                .try_for_each(|source| source.require_credentials()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Record what is wrong with this source, under its config key `key`.
+    fn check(&self, key: &str, problems: &mut Vec<String>) {
+        match self {
+            Self::File { path } | Self::Directory { path } if path.as_os_str().is_empty() => {
+                problems.push(format!("{key}.path: must not be empty"));
+            }
+            Self::File { .. } | Self::Directory { .. } => {}
+            Self::Http { url } => check_url(&format!("{key}.url"), url, problems),
+            Self::SpaceTrack(config) => {
+                check_url(&format!("{key}.base_url"), &config.base_url, problems)
+            }
+            Self::Merged { sources } if sources.is_empty() => {
+                problems.push(format!(
+                    "{key}.sources: a merged source needs at least one source"
+                ));
+            }
+            Self::Merged { sources } => {
+                for (i, source) in sources.iter().enumerate() {
+                    source.check(&format!("{key}.sources[{i}]"), problems);
+                }
+            }
+        }
+    }
+
+    /// Whether the source is downloaded, and so subject to [`HttpPolicy`].
+    fn is_remote(&self) -> bool {
+        matches!(self, Self::Http { .. } | Self::SpaceTrack(_))
+    }
+
+    /// Append the file, directory and remote sources this one is made of.
+    fn flatten_into(&self, leaves: &mut Vec<CatalogSource>) {
+        match self {
+            Self::Merged { sources } => {
+                for source in sources {
+                    source.flatten_into(leaves);
+                }
+            }
+            leaf => leaves.push(leaf.clone()),
+        }
+    }
+
+    /// How the source is named in logs and in the catalog cache.
+    pub fn name(&self) -> String {
+        match self {
+            Self::File { path } => format!("file:{}", path.display()),
+            Self::Directory { path } => format!("directory:{}", path.display()),
+            Self::Http { url } => url.clone(),
+            Self::SpaceTrack(config) => config.query_url(),
+            Self::Merged { .. } => String::from("merged"),
+        }
+    }
+
+    /// What the files behind a local source look like now, used to notice
+    /// changes without reading them. `None` for remote sources.
+    fn stamp(&self) -> Option<Stamp> {
+        let stamp = match self {
+            Self::File { path } => file_stamp(path).map(|stamp| vec![stamp]),
+            Self::Directory { path } => catalog_files(path)
+                .and_then(|files| files.iter().map(|file| file_stamp(file)).collect()),
+            Self::Http { .. } | Self::SpaceTrack(_) | Self::Merged { .. } => return None,
+        };
+        Some(stamp.map_err(|e| e.kind()))
+    }
+
+    /// Read the catalog text behind a file, directory or HTTP source.
+    /// `held` describes the copy already held, so an HTTP source can answer
+    /// that it has not changed, which yields `None`.
+    async fn load(
+        &self,
+        client: &reqwest::Client,
+        held: Option<&CacheMeta>,
+    ) -> Result<Option<Fetched>, LoadError> {
+        let paths = match self {
+            Self::File { path } => vec![path.clone()],
+            Self::Directory { path } => {
+                catalog_files(path).map_err(|e| format!("cannot list {}: {e}", path.display()))?
+            }
+            Self::Http { url } => {
+                let etag = held.and_then(|meta| meta.etag.as_deref());
+                let last_modified = held.and_then(|meta| meta.last_modified.as_deref());
+                let download = fetch_catalog(client, url, etag, last_modified)
+                    .await
+                    .map_err(|e| LoadError {
+                        message: format!("fetch of {url} failed: {e}"),
+                        retry_after: e.retry_after,
+                    })?;
+                return Ok(download.map(|download| Fetched {
+                    bodies: vec![(url.clone(), download.body)],
+                    fetched_at: Utc::now(),
+                    etag: download.etag,
+                    last_modified: download.last_modified,
+                }));
+            }
+            Self::SpaceTrack(_) => unreachable!("Space-Track loads through its session"),
+            Self::Merged { .. } => unreachable!("merged sources are flattened"),
+        };
+        let mut fetched = Fetched {
+            bodies: Vec::with_capacity(paths.len()),
+            fetched_at: DateTime::<Utc>::MIN_UTC,
+            etag: None,
+            last_modified: None,
+        };
+        for path in paths {
+            let read = |e: io::Error| format!("cannot read {}: {e}", path.display());
+            let (_, modified, _) = file_stamp(&path).map_err(read)?;
+            fetched.fetched_at = fetched.fetched_at.max(modified.into());
+            let body = fs::read_to_string(&path).map_err(read)?;
+            fetched.bodies.push((path.display().to_string(), body));
+        }
+        if fetched.bodies.is_empty() {
+            fetched.fetched_at = Utc::now();
+        }
+        Ok(Some(fetched))
+    }
+}
+
+/// Catalog text read from a source, as `(origin, body)` pairs so problems can
+/// be traced back to a file, with its fetch metadata. Local sources date
+/// their catalog by its newest file.
+struct Fetched {
+    bodies: Vec<(String, String)>,
+    fetched_at: DateTime<Utc>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+/// Why a source could not be read, and how long its server asked us to wait.
+struct LoadError {
+    message: String,
+    retry_after: Option<Duration>,
+}
+
+impl From<String> for LoadError {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            retry_after: None,
+        }
+    }
+}
+
+/// When HTTP sources are downloaded. CelesTrak blocks clients that fetch
+/// unchanged data too often, so every limit applies per source and across
+/// retries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HttpPolicy {
+    /// How long a download is used before it is fetched again.
+    pub interval: Duration,
+    /// Least time between two requests to the same source.
+    pub min_spacing: Duration,
+    /// Delay after the first failed download, doubled with each further
+    /// failure up to `max_retry`.
+    pub initial_retry: Duration,
+    pub max_retry: Duration,
+}
+
+impl HttpPolicy {
+    /// Delay before retrying after `failures` consecutive failures, stretched
+    /// by up to a quarter by `jitter` in `[0, 1)` so that restarted daemons
+    /// do not retry in lockstep.
+    fn retry_delay(&self, failures: u32, jitter: f64) -> Duration {
+        let doublings = failures.saturating_sub(1).min(30);
+        self.initial_retry
+            .saturating_mul(1 << doublings)
+            .min(self.max_retry)
+            .mul_f64(1.0 + jitter / 4.0)
+            .max(self.min_spacing)
+    }
+}
+
+/// A fresh value in `[0, 1)` from the randomly keyed std hasher.
+fn jitter() -> f64 {
+    let bits = RandomState::new().build_hasher().finish() >> 11;
+    bits as f64 / (1u64 << 53) as f64
+}
+
+/// The catalog section of the daemon config file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CatalogConfig {
+    /// Sources to read, merged when there is more than one. CelesTrak's
+    /// active catalog when empty.
+    pub sources: Vec<CatalogSource>,
+    /// How often file and directory sources are checked for changes.
+    pub poll_interval_secs: u64,
+    /// How often HTTP sources are downloaded again.
+    pub http_interval_secs: u64,
+    /// Least time between two requests to the same HTTP source, retries
+    /// included.
+    pub min_fetch_spacing_secs: u64,
+    /// First retry delay after a failed download, doubled per failure.
+    pub retry_initial_secs: u64,
+    /// Longest retry delay.
+    pub retry_max_secs: u64,
+    /// How long to wait for an HTTP source to accept a connection.
+    pub connect_timeout_secs: u64,
+    /// Longest an HTTP request may take, download included.
+    pub request_timeout_secs: u64,
+}
+
+impl Default for CatalogConfig {
+    fn default() -> Self {
+        Self {
+            sources: Vec::new(),
+            poll_interval_secs: 30,
+            http_interval_secs: 24 * 60 * 60,
+            min_fetch_spacing_secs: 10 * 60,
+            retry_initial_secs: 5 * 60,
+            retry_max_secs: 6 * 60 * 60,
+            connect_timeout_secs: 10,
+            request_timeout_secs: 5 * 60,
+        }
+    }
+}
+
+impl CatalogConfig {
+    /// The single source described by this section.
+    pub fn source(&self) -> CatalogSource {
+        match self.sources.as_slice() {
+            [] => CatalogSource::default(),
+            [source] => source.clone(),
+            sources => CatalogSource::Merged {
+                sources: sources.to_vec(),
+            },
+        }
+    }
+
+    /// Record every setting in this section that the service cannot run
+    /// with, prefixed by its config key.
+    pub fn check(&self, problems: &mut Vec<String>) {
+        for (i, source) in self.sources.iter().enumerate() {
+            source.check(&format!("catalog.sources[{i}]"), problems);
+        }
+        for (key, secs) in [
+            ("poll_interval_secs", self.poll_interval_secs),
+            ("http_interval_secs", self.http_interval_secs),
+            ("retry_initial_secs", self.retry_initial_secs),
+            ("connect_timeout_secs", self.connect_timeout_secs),
+            ("request_timeout_secs", self.request_timeout_secs),
+        ] {
+            if secs == 0 {
+                problems.push(format!("catalog.{key}: must be at least 1"));
+            }
+        }
+        if self.retry_initial_secs > self.retry_max_secs {
+            problems.push(format!(
+                "catalog.retry_initial_secs: {} is longer than catalog.retry_max_secs ({})",
+                self.retry_initial_secs, self.retry_max_secs
+            ));
+        }
+    }
+
+    pub fn http_policy(&self) -> HttpPolicy {
+        HttpPolicy {
+            interval: Duration::from_secs(self.http_interval_secs),
+            min_spacing: Duration::from_secs(self.min_fetch_spacing_secs),
+            initial_retry: Duration::from_secs(self.retry_initial_secs),
+            max_retry: Duration::from_secs(self.retry_max_secs),
+        }
+    }
+
+    /// The client HTTP sources are downloaded with, which gives up on a
+    /// network that hangs instead of refusing.
+    pub fn http_client(&self) -> reqwest::Client {
+        reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(self.connect_timeout_secs))
+            .timeout(Duration::from_secs(self.request_timeout_secs))
+            .build()
+            .expect("TLS backend failed to initialise")
+    }
+}
+
+fn check_url(key: &str, url: &str, problems: &mut Vec<String>) {
+    match url.split_once("://") {
+        Some(("http" | "https", host)) if !host.is_empty() => {}
+        _ => problems.push(format!("{key}: `{url}` is not an http:// or https:// URL")),
+    }
+}
+
+type Stamp = Result<Vec<(PathBuf, SystemTime, u64)>, io::ErrorKind>;
+
+fn file_stamp(path: &Path) -> io::Result<(PathBuf, SystemTime, u64)> {
+    let metadata = fs::metadata(path)?;
+    Ok((path.to_path_buf(), metadata.modified()?, metadata.len()))
+}
+
+/// The non-hidden regular files in `dir`, sorted by name.
+fn catalog_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if !hidden && entry.file_type()?.is_file() {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// One file, directory or HTTP source and the records it last yielded.
+#[derive(Debug)]
+struct Feed {
+    source: CatalogSource,
+    stamp: Option<Stamp>,
+    /// Earliest time an HTTP source may be requested again.
+    next_request: Option<Instant>,
+    /// Consecutive failed downloads.
+    failures: u32,
+    /// The logged-in client of a Space-Track source.
+    session: Option<SpaceTrack>,
+    meta: Option<CacheMeta>,
+    records: Vec<OrbitalRecord>,
+}
+
+impl Feed {
+    /// Least time between two requests, the GP query limit for Space-Track.
+    fn min_spacing(&self, policy: &HttpPolicy) -> Duration {
+        match self.source {
+            CatalogSource::SpaceTrack(_) => policy.min_spacing.max(MIN_QUERY_SPACING),
+            _ => policy.min_spacing,
+        }
+    }
+
+    fn is_due(&mut self, now: Instant, policy: &HttpPolicy) -> bool {
+        if self.source.is_remote() {
+            // A cached download counts, so restarts do not re-fetch.
+            let interval = policy.interval.max(self.min_spacing(policy));
+            let stale = self.meta.as_ref().is_none_or(|meta| {
+                (Utc::now() - meta.fetched_at).to_std().unwrap_or_default() >= interval
+            });
+            return stale && self.next_request.is_none_or(|at| now >= at);
+        }
+        let stamp = self.source.stamp();
+        if self.stamp.is_some() && stamp == self.stamp {
+            return false;
+        }
+        self.stamp = stamp;
+        true
+    }
+
+    /// Read the source again. Records it can no longer provide are kept
+    /// until it yields at least one usable record, which is then cached.
+    async fn reload(
+        &mut self,
+        client: &reqwest::Client,
+        now: Instant,
+        policy: &HttpPolicy,
+        cache: Option<&CatalogCache>,
+    ) {
+        self.next_request = Some(now + self.min_spacing(policy));
+        let loaded = match &mut self.session {
+            Some(session) => session
+                .fetch(client)
+                .await
+                .map(|body| {
+                    Some(Fetched {
+                        bodies: vec![(self.source.name(), body)],
+                        fetched_at: Utc::now(),
+                        etag: None,
+                        last_modified: None,
+                    })
+                })
+                .map_err(LoadError::from),
+            None => self.source.load(client, self.meta.as_ref()).await,
+        };
+        let fetched = match loaded {
+            Ok(Some(fetched)) => fetched,
+            Ok(None) => {
+                // Not modified: what we hold is current as of now.
+                debug!("Catalog source {} not modified", self.source.name());
+                self.failures = 0;
+                if let Some(meta) = &mut self.meta {
+                    meta.fetched_at = Utc::now();
+                    store(cache, meta, &self.records);
+                }
+                return;
+            }
+            Err(err) => {
+                let delay = self.back_off(now, policy, err.retry_after);
+                warn!(
+                    "Catalog source error: {}; retrying in {}",
+                    err.message,
+                    format_age(chrono::Duration::from_std(delay).unwrap_or(chrono::Duration::MAX))
+                );
+                return;
+            }
+        };
+        let mut records = Vec::new();
+        for (origin, body) in &fetched.bodies {
+            // One bad record must not discard the rest of the catalog.
+            match parse_catalog_with(body, ParseMode::Lenient) {
+                Ok(parsed) => {
+                    for rejection in &parsed.rejected {
+                        warn!("Rejected catalog record in {origin} at {rejection}");
+                    }
+                    for failure in &parsed.dropped {
+                        info!("Dropped from live orbital state: {failure}");
+                    }
+                    records.extend(parsed.records);
+                }
+                Err(err) => warn!("Catalog parse error in {origin}: {err}"),
+            }
+        }
+        if records.is_empty() {
+            warn!(
+                "Catalog source {} held no usable records; keeping its previous records",
+                self.source.name()
+            );
+            // A mirror serving an error page is retried like a failed fetch.
+            self.back_off(now, policy, None);
+            return;
+        }
+        self.failures = 0;
+        let meta = CacheMeta {
+            source: self.source.name(),
+            fetched_at: fetched.fetched_at,
+            etag: fetched.etag,
+            last_modified: fetched.last_modified,
+            objects: records.len(),
+        };
+        store(cache, &meta, &records);
+        self.meta = Some(meta);
+        self.records = records;
+    }
+
+    /// Schedule the next request of an HTTP source after a failure, at least
+    /// as late as the server asked, and return the delay.
+    fn back_off(
+        &mut self,
+        now: Instant,
+        policy: &HttpPolicy,
+        retry_after: Option<Duration>,
+    ) -> Duration {
+        self.failures += 1;
+        let delay = policy
+            .retry_delay(self.failures, jitter())
+            .max(retry_after.unwrap_or_default())
+            .max(self.min_spacing(policy));
+        self.next_request = Some(now + delay);
+        delay
+    }
+}
+
+fn store(cache: Option<&CatalogCache>, meta: &CacheMeta, records: &[OrbitalRecord]) {
+    if let Some(cache) = cache {
+        if let Err(err) = cache.store(meta, records) {
+            error!("Cannot cache catalog from {}: {err}", meta.source);
+        }
+    }
+}
+
+/// The merged catalog served to clients.
+#[derive(Debug, Clone, Default)]
+pub struct LiveCatalog {
+    pub records: Vec<OrbitalRecord>,
+    /// When the oldest contributing source was fetched; `None` while empty.
+    pub fetched_at: Option<DateTime<Utc>>,
+    /// Bumped by the service whenever `records` change, so consumers know
+    /// when to reload them.
+    pub revision: u64,
+    /// Where each part of the catalog came from and when, for sources that
+    /// have yielded records.
+    pub sources: Vec<CacheMeta>,
+}
+
+impl LiveCatalog {
+    /// How old the catalog is at `now`, for warning clients of stale data.
+    pub fn age(&self, now: DateTime<Utc>) -> Option<chrono::Duration> {
+        self.fetched_at.map(|fetched_at| now - fetched_at)
+    }
+}
+
+/// Reads the catalog from a [`CatalogSource`], re-reading each part of it
+/// only when it changes or falls due, and warm-starting from the cache.
+#[derive(Debug)]
+pub struct CatalogFeed {
+    feeds: Vec<Feed>,
+    policy: HttpPolicy,
+    cache: Option<CatalogCache>,
+    published: bool,
+}
+
+impl CatalogFeed {
+    pub fn new(source: &CatalogSource, policy: HttpPolicy, cache: Option<CatalogCache>) -> Self {
+        let mut leaves = Vec::new();
+        source.flatten_into(&mut leaves);
+        // Space-Track limits each account, however many sources use it.
+        let mut limiters: HashMap<(String, Option<String>), SharedLimiter> = HashMap::new();
+        let feeds = leaves
+            .into_iter()
+            .map(|source| {
+                let cached = cache.as_ref().and_then(|cache| {
+                    cache.load(&source.name()).unwrap_or_else(|err| {
+                        warn!("Ignoring cached catalog for {}: {err}", source.name());
+                        None
+                    })
+                });
+                let (meta, records) = cached.unzip();
+                let session = match &source {
+                    CatalogSource::SpaceTrack(config) => {
+                        let limiter = limiters
+                            .entry((config.base_url.clone(), config.identity.clone()))
+                            .or_insert_with(|| RateLimiter::spacetrack().shared());
+                        Some(SpaceTrack::new(config.clone(), Arc::clone(limiter)))
+                    }
+                    _ => None,
+                };
+                Feed {
+                    source,
+                    session,
+                    stamp: None,
+                    next_request: None,
+                    failures: 0,
+                    meta,
+                    records: records.unwrap_or_default(),
+                }
+            })
+            .collect();
+        Self {
+            feeds,
+            policy,
+            cache,
+            published: false,
+        }
+    }
+
+    /// Reload every source that changed or is due and return the merged
+    /// catalog, or `None` when nothing changed since the last call.
+    ///
+    /// The first call returns the cached catalog, if there is one, without
+    /// reading any source, so a slow or unreachable source cannot hold it
+    /// back; due sources are re-read from the next call on.
+    pub async fn refresh(&mut self, client: &reqwest::Client) -> Option<LiveCatalog> {
+        let first = !self.published;
+        self.published = true;
+        if first && self.feeds.iter().any(|feed| !feed.records.is_empty()) {
+            return Some(self.merged());
+        }
+        let now = Instant::now();
+        let mut reloaded = first;
+        for feed in &mut self.feeds {
+            if feed.is_due(now, &self.policy) {
+                feed.reload(client, now, &self.policy, self.cache.as_ref())
+                    .await;
+                reloaded = true;
+            }
+        }
+        reloaded.then(|| self.merged())
+    }
+
+    /// Every source's records, one per object, keeping the newest epoch.
+    fn merged(&self) -> LiveCatalog {
+        LiveCatalog {
+            records: keep_newest(
+                self.feeds
+                    .iter()
+                    .flat_map(|feed| feed.records.iter().cloned()),
+            ),
+            fetched_at: self
+                .feeds
+                .iter()
+                .filter(|feed| !feed.records.is_empty())
+                .filter_map(|feed| feed.meta.as_ref().map(|meta| meta.fetched_at))
+                .min(),
+            revision: 0,
+            sources: self
+                .feeds
+                .iter()
+                .filter(|feed| !feed.records.is_empty())
+                .filter_map(|feed| feed.meta.clone())
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_stub::{HttpStub, Response};
+    use crate::test_util::{self, ISS, VANGUARD};
+    use std::thread;
+
+    const ISS_OLDER: &str = "ISS (OLDER)\n1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927\n2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537\n";
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        test_util::scratch_dir("source", name)
+    }
+
+    fn policy() -> HttpPolicy {
+        CatalogConfig::default().http_policy()
+    }
+
+    fn refresh(feed: &mut CatalogFeed) -> Option<Vec<(u32, String)>> {
+        let client = reqwest::Client::new();
+        test_util::block_on(feed.refresh(&client)).map(|catalog| {
+            catalog
+                .records
+                .into_iter()
+                .map(|record| (record.norad_id.get(), record.name))
+                .collect()
+        })
+    }
+
+    #[test]
+    fn reads_config_sections() {
+        let config: CatalogConfig = toml::from_str(
+            r#"
+            poll_interval_secs = 5
+            [[sources]]
+            kind = "directory"
+            path = "tle.d"
+            [[sources]]
+            kind = "merged"
+            sources = [
+                { kind = "file", path = "/srv/catalog.json" },
+                { kind = "http", url = "https://mirror.example/gp.json" },
+            ]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.poll_interval_secs, 5);
+        assert_eq!(config.http_interval_secs, 24 * 60 * 60);
+        let source = config.source().resolve(Path::new("/etc/openastrovizd"));
+        assert_eq!(
+            source,
+            CatalogSource::Merged {
+                sources: vec![
+                    CatalogSource::Directory {
+                        path: PathBuf::from("/etc/openastrovizd/tle.d")
+                    },
+                    CatalogSource::Merged {
+                        sources: vec![
+                            CatalogSource::File {
+                                path: PathBuf::from("/srv/catalog.json")
+                            },
+                            CatalogSource::Http {
+                                url: String::from("https://mirror.example/gp.json")
+                            },
+                        ]
+                    },
+                ]
+            }
+        );
+        assert_eq!(CatalogConfig::default().source(), CatalogSource::default());
+    }
+
+    #[test]
+    fn rejects_unknown_source_kinds_and_fields() {
+        let err = toml::from_str::<CatalogConfig>("[[sources]]\nkind = \"ftp\"\n").unwrap_err();
+        assert!(err.to_string().contains("unknown variant `ftp`"), "{err}");
+        let err = toml::from_str::<CatalogConfig>(
+            "[[sources]]\nkind = \"file\"\npath = \"a.tle\"\nurl = \"x\"\n",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown field `url`"), "{err}");
+    }
+
+    #[test]
+    fn rereads_a_file_only_when_it_changes() {
+        let dir = scratch_dir("file");
+        let path = dir.join("catalog.tle");
+        fs::write(&path, ISS).unwrap();
+        let mut feed =
+            CatalogFeed::new(&CatalogSource::File { path: path.clone() }, policy(), None);
+
+        assert_eq!(
+            refresh(&mut feed),
+            Some(vec![(25544, "ISS (ZARYA)".into())])
+        );
+        assert_eq!(refresh(&mut feed), None);
+
+        fs::write(&path, format!("{ISS}{VANGUARD}")).unwrap();
+        let names: Vec<u32> = refresh(&mut feed).unwrap().iter().map(|r| r.0).collect();
+        assert_eq!(names, [25544, 5]);
+
+        // A catalog that disappears or turns to garbage keeps the last records.
+        fs::write(&path, "<html>mirror error page</html>\n").unwrap();
+        assert_eq!(refresh(&mut feed).map(|records| records.len()), Some(2));
+        fs::remove_file(&path).unwrap();
+        assert_eq!(refresh(&mut feed).map(|records| records.len()), Some(2));
+        assert_eq!(refresh(&mut feed), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn watches_a_directory() {
+        let dir = scratch_dir("directory");
+        fs::write(dir.join("iss.tle"), ISS).unwrap();
+        fs::write(dir.join(".vanguard.tle.partial"), VANGUARD).unwrap();
+        let mut feed = CatalogFeed::new(
+            &CatalogSource::Directory { path: dir.clone() },
+            policy(),
+            None,
+        );
+
+        assert_eq!(
+            refresh(&mut feed),
+            Some(vec![(25544, "ISS (ZARYA)".into())])
+        );
+        assert_eq!(refresh(&mut feed), None);
+
+        fs::rename(dir.join(".vanguard.tle.partial"), dir.join("vanguard.tle")).unwrap();
+        let ids: Vec<u32> = refresh(&mut feed).unwrap().iter().map(|r| r.0).collect();
+        assert_eq!(ids, [25544, 5]);
+
+        fs::remove_file(dir.join("iss.tle")).unwrap();
+        let ids: Vec<u32> = refresh(&mut feed).unwrap().iter().map(|r| r.0).collect();
+        assert_eq!(ids, [5]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn merges_sources_keeping_the_newest_epoch() {
+        let dir = scratch_dir("merged");
+        fs::write(dir.join("old.tle"), format!("{ISS_OLDER}{VANGUARD}")).unwrap();
+        fs::write(
+            dir.join("new.json"),
+            include_str!("../../../core/tests/data/ISS.omm.json"),
+        )
+        .unwrap();
+        let source = CatalogSource::Merged {
+            sources: vec![
+                CatalogSource::File {
+                    path: dir.join("old.tle"),
+                },
+                CatalogSource::File {
+                    path: dir.join("new.json"),
+                },
+            ],
+        };
+        let mut feed = CatalogFeed::new(&source, policy(), None);
+        assert_eq!(
+            refresh(&mut feed),
+            Some(vec![
+                (25544, "ISS (ZARYA)".into()),
+                (5, "VANGUARD 1".into())
+            ])
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn warm_starts_from_the_cache() {
+        let dir = scratch_dir("warm-start");
+        let cache = CatalogCache::new(dir.join("cache"));
+        // Nothing listens on the discard port, so a download would fail.
+        let source = CatalogSource::Http {
+            url: String::from("http://127.0.0.1:9/gp.json"),
+        };
+        let records = crate::tle::parse_catalog(ISS).unwrap().records;
+        let fetched_at = Utc::now() - chrono::Duration::hours(2);
+        let meta = CacheMeta {
+            source: source.name(),
+            fetched_at,
+            etag: Some(String::from("\"v1\"")),
+            last_modified: None,
+            objects: 1,
+        };
+        cache.store(&meta, &records).unwrap();
+
+        // A fresh cache is served without downloading.
+        let mut feed = CatalogFeed::new(&source, policy(), Some(cache.clone()));
+        let client = reqwest::Client::new();
+        let runtime = test_util::runtime();
+        let catalog = runtime.block_on(feed.refresh(&client)).unwrap();
+        assert_eq!(catalog.records.len(), 1);
+        assert_eq!(catalog.fetched_at, Some(fetched_at));
+        let age = catalog
+            .age(fetched_at + chrono::Duration::hours(3))
+            .unwrap();
+        assert_eq!(age, chrono::Duration::hours(3));
+        assert!(runtime.block_on(feed.refresh(&client)).is_none());
+
+        // A stale one is served first, and kept when the download fails.
+        let mut feed = CatalogFeed::new(
+            &source,
+            HttpPolicy {
+                interval: Duration::from_secs(60),
+                min_spacing: Duration::ZERO,
+                ..policy()
+            },
+            Some(cache),
+        );
+        let catalog = runtime.block_on(feed.refresh(&client)).unwrap();
+        assert_eq!(catalog.records.len(), 1);
+        assert_eq!(catalog.fetched_at, Some(fetched_at));
+        let catalog = runtime.block_on(feed.refresh(&client)).unwrap();
+        assert_eq!(catalog.records.len(), 1);
+        assert_eq!(catalog.fetched_at, Some(fetched_at));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn shares_one_rate_limiter_per_space_track_account() {
+        let account = |identity: &str, predicate: &str| {
+            CatalogSource::SpaceTrack(SpaceTrackConfig {
+                base_url: String::from("https://www.space-track.org"),
+                predicate: predicate.to_string(),
+                identity: Some(identity.to_string()),
+                password: None,
+            })
+        };
+        let source = CatalogSource::Merged {
+            sources: vec![
+                account("analyst@example.org", "OBJECT_TYPE/PAYLOAD"),
+                account("analyst@example.org", "OBJECT_TYPE/DEBRIS"),
+                account("other@example.org", "OBJECT_TYPE/DEBRIS"),
+            ],
+        };
+        let feed = CatalogFeed::new(&source, policy(), None);
+        let limiters: Vec<&SharedLimiter> = feed
+            .feeds
+            .iter()
+            .map(|feed| feed.session.as_ref().unwrap().limiter())
+            .collect();
+        assert!(Arc::ptr_eq(limiters[0], limiters[1]));
+        assert!(!Arc::ptr_eq(limiters[0], limiters[2]));
+    }
+
+    #[test]
+    fn serves_a_stale_cache_before_downloading() {
+        let dir = scratch_dir("warm-hang");
+        let cache = CatalogCache::new(dir.join("cache"));
+        // Connections are queued by the kernel but never answered.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let source = CatalogSource::Http {
+            url: format!("http://{}/gp.json", listener.local_addr().unwrap()),
+        };
+        let records = crate::tle::parse_catalog(ISS).unwrap().records;
+        let fetched_at = Utc::now() - chrono::Duration::days(2);
+        let meta = CacheMeta {
+            source: source.name(),
+            fetched_at,
+            etag: None,
+            last_modified: None,
+            objects: 1,
+        };
+        cache.store(&meta, &records).unwrap();
+
+        let mut feed = CatalogFeed::new(&source, policy(), Some(cache));
+        let client = CatalogConfig {
+            request_timeout_secs: 1,
+            ..CatalogConfig::default()
+        }
+        .http_client();
+        let runtime = test_util::runtime();
+        let refresh = |feed: &mut CatalogFeed| {
+            runtime
+                .block_on(async {
+                    tokio::time::timeout(Duration::from_secs(5), feed.refresh(&client)).await
+                })
+                .expect("refresh hung")
+                .unwrap()
+        };
+        let catalog = refresh(&mut feed);
+        assert_eq!(catalog.records.len(), 1);
+        assert_eq!(catalog.fetched_at, Some(fetched_at));
+
+        // The download is still due; it times out and the cache is kept.
+        let started = Instant::now();
+        let catalog = refresh(&mut feed);
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        assert_eq!(catalog.records.len(), 1);
+        assert_eq!(catalog.fetched_at, Some(fetched_at));
+        drop(listener);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn caches_local_sources_dated_by_their_files() {
+        let dir = scratch_dir("cache-local");
+        let path = dir.join("catalog.tle");
+        fs::write(&path, format!("{ISS}{VANGUARD}")).unwrap();
+        let modified: DateTime<Utc> = fs::metadata(&path).unwrap().modified().unwrap().into();
+        let cache = CatalogCache::new(dir.join("cache"));
+        let source = CatalogSource::File { path: path.clone() };
+
+        let mut feed = CatalogFeed::new(&source, policy(), Some(cache.clone()));
+        assert_eq!(refresh(&mut feed).map(|records| records.len()), Some(2));
+        let entries = cache.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].source, format!("file:{}", path.display()));
+        assert_eq!(entries[0].fetched_at, modified);
+        assert_eq!(entries[0].objects, 2);
+
+        // The records outlive the file across a restart.
+        fs::remove_file(&path).unwrap();
+        let mut feed = CatalogFeed::new(&source, policy(), Some(cache));
+        assert_eq!(refresh(&mut feed).map(|records| records.len()), Some(2));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    fn quick_policy() -> HttpPolicy {
+        HttpPolicy {
+            interval: Duration::ZERO,
+            min_spacing: Duration::ZERO,
+            initial_retry: Duration::from_millis(100),
+            max_retry: Duration::from_secs(1),
+        }
+    }
+
+    #[test]
+    fn downloads_only_changed_catalogs() {
+        let stub = HttpStub::start(|request| {
+            if request.header("If-None-Match") == Some("\"v1\"") {
+                Response::new(304, "")
+            } else {
+                Response::new(200, ISS)
+                    .header("ETag", "\"v1\"")
+                    .header("Last-Modified", "Sun, 18 Oct 2026 06:00:00 GMT")
+            }
+        });
+        let dir = scratch_dir("conditional");
+        let cache = CatalogCache::new(&dir);
+        let source = CatalogSource::Http {
+            url: stub.url("/gp.php?GROUP=active&FORMAT=tle"),
+        };
+        let mut feed = CatalogFeed::new(&source, quick_policy(), Some(cache.clone()));
+
+        assert_eq!(
+            refresh(&mut feed),
+            Some(vec![(25544, "ISS (ZARYA)".into())])
+        );
+        let first = cache.entries().unwrap().remove(0);
+        assert_eq!(first.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(
+            refresh(&mut feed),
+            Some(vec![(25544, "ISS (ZARYA)".into())])
+        );
+
+        let requests = stub.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].target, "/gp.php?GROUP=active&FORMAT=tle");
+        assert_eq!(requests[0].header("If-None-Match"), None);
+        assert_eq!(requests[1].header("If-None-Match"), Some("\"v1\""));
+        assert_eq!(
+            requests[1].header("If-Modified-Since"),
+            Some("Sun, 18 Oct 2026 06:00:00 GMT")
+        );
+        // The unchanged catalog counts as fetched again.
+        assert!(cache.entries().unwrap()[0].fetched_at >= first.fetched_at);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn retry_delays_grow_within_the_limits() {
+        let policy = HttpPolicy {
+            interval: Duration::from_secs(3600),
+            min_spacing: Duration::from_secs(90),
+            initial_retry: Duration::from_secs(60),
+            max_retry: Duration::from_secs(600),
+        };
+        let delays: Vec<u64> = (1..=6)
+            .map(|failures| policy.retry_delay(failures, 0.0).as_secs())
+            .collect();
+        assert_eq!(delays, [90, 120, 240, 480, 600, 600]);
+        assert_eq!(policy.retry_delay(2, 0.5), Duration::from_secs(135));
+        assert_eq!(policy.retry_delay(u32::MAX, 0.0), Duration::from_secs(600));
+        assert!((0.0..1.0).contains(&jitter()));
+    }
+
+    #[test]
+    fn backs_off_after_failed_downloads() {
+        let stub = HttpStub::start(|_| Response::new(503, "try later"));
+        let source = CatalogSource::Http {
+            url: stub.url("/gp.json"),
+        };
+        let mut feed = CatalogFeed::new(&source, quick_policy(), None);
+
+        refresh(&mut feed);
+        refresh(&mut feed);
+        assert_eq!(stub.requests().len(), 1);
+        thread::sleep(Duration::from_millis(150));
+        refresh(&mut feed);
+        refresh(&mut feed);
+        assert_eq!(stub.requests().len(), 2);
+        // The second delay is at least twice the first.
+        thread::sleep(Duration::from_millis(150));
+        refresh(&mut feed);
+        assert_eq!(stub.requests().len(), 2);
+        thread::sleep(Duration::from_millis(150));
+        refresh(&mut feed);
+        assert_eq!(stub.requests().len(), 3);
+    }
+
+    #[test]
+    fn honours_retry_after_and_spacing() {
+        let stub = HttpStub::start(|_| Response::new(429, "").header("Retry-After", "3600"));
+        let source = CatalogSource::Http {
+            url: stub.url("/gp.json"),
+        };
+        let mut feed = CatalogFeed::new(&source, quick_policy(), None);
+        refresh(&mut feed);
+        thread::sleep(Duration::from_millis(300));
+        refresh(&mut feed);
+        assert_eq!(stub.requests().len(), 1);
+
+        let stub = HttpStub::start(|_| Response::new(200, ISS));
+        let source = CatalogSource::Http {
+            url: stub.url("/gp.json"),
+        };
+        let policy = HttpPolicy {
+            min_spacing: Duration::from_secs(3600),
+            ..quick_policy()
+        };
+        let mut feed = CatalogFeed::new(&source, policy, None);
+        refresh(&mut feed);
+        refresh(&mut feed);
+        assert_eq!(stub.requests().len(), 1);
+    }
+}
@@ -0,0 +1,454 @@
+//! Space-Track.org, the authoritative source of the full GP catalog,
+//! including debris and analyst objects CelesTrak does not republish.
+//!
+//! Space-Track requires a login, kept as a cookie session, and enforces
+//! documented limits: 30 requests a minute, 300 an hour, and GP queries no
+//! more than once an hour. Accounts that exceed them get suspended.
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize, Serializer};
+use tokio::sync::Mutex;
+
+pub const SPACETRACK_URL: &str = "https://www.space-track.org";
+/// Every object still in orbit with elements from the last 30 days, the
+/// query Space-Track recommends for a full-catalog pull.
+pub const DEFAULT_PREDICATE: &str = "decay_date/null-val/epoch/>now-30/orderby/norad_cat_id";
+/// Least time between two GP queries.
+pub const MIN_QUERY_SPACING: Duration = Duration::from_secs(60 * 60);
+
+const IDENTITY_VAR: &str = "OPENASTROVIZD_SPACETRACK_IDENTITY";
+const PASSWORD_VAR: &str = "OPENASTROVIZD_SPACETRACK_PASSWORD";
+
+/// A credential kept out of logs, `Debug` output and `config check`.
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\"***\"")
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str("***")
+    }
+}
+
+/// A `kind = "spacetrack"` catalog source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpaceTrackConfig {
+    #[serde(default = "default_base_url")]
+    pub base_url: String,
+    /// The `class/gp` query predicates, e.g.
+    /// `OBJECT_TYPE/DEBRIS/decay_date/null-val`.
+    #[serde(default = "default_predicate")]
+    pub predicate: String,
+    /// Account name, usually an e-mail address. Overridden by
+    /// `OPENASTROVIZD_SPACETRACK_IDENTITY`.
+    pub identity: Option<String>,
+    /// Overridden by `OPENASTROVIZD_SPACETRACK_PASSWORD`.
+    pub password: Option<Secret>,
+}
+
+fn default_base_url() -> String {
+    SPACETRACK_URL.to_string()
+}
+
+fn default_predicate() -> String {
+    DEFAULT_PREDICATE.to_string()
+}
+
+impl SpaceTrackConfig {
+    /// Take credentials from the environment where set, via `var` so tests
+    /// need not touch the process environment.
+    pub fn fill_credentials(&mut self, var: impl Fn(&str) -> Option<String>) {
+        if let Some(identity) = var(IDENTITY_VAR) {
+            self.identity = Some(identity);
+        }
+        if let Some(password) = var(PASSWORD_VAR) {
+            self.password = Some(Secret::new(password));
+        }
+    }
+
+    /// Fail if either credential is missing.
+    pub fn require_credentials(&self) -> Result<(), String> {
+        if self.identity.is_none() || self.password.is_none() {
+            return Err(format!(
+                "Space-Track source needs `identity` and `password`, in the config file or \
+                 {IDENTITY_VAR} and {PASSWORD_VAR}"
+            ));
+        }
+        Ok(())
+    }
+
+    /// The GP query URL, which also names the source.
+    pub fn query_url(&self) -> String {
+        format!(
+            "{}/basicspacedata/query/class/gp/{}/format/json",
+            self.base_url.trim_end_matches('/'),
+            self.predicate.trim_matches('/')
+        )
+    }
+}
+
+/// Sliding-window request limits.
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// `(requests, per)` pairs that must all hold.
+    limits: Vec<(usize, Duration)>,
+    sent: VecDeque<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: Vec<(usize, Duration)>) -> Self {
+        Self {
+            limits,
+            sent: VecDeque::new(),
+        }
+    }
+
+    /// Space-Track's documented limits.
+    pub fn spacetrack() -> Self {
+        Self::new(vec![
+            (30, Duration::from_secs(60)),
+            (300, Duration::from_secs(60 * 60)),
+        ])
+    }
+
+    /// A limiter for several clients to share. The limits apply per
+    /// account, so every source logging in as one identity needs the same
+    /// limiter.
+    pub fn shared(self) -> SharedLimiter {
+        Arc::new(Mutex::new(self))
+    }
+
+    /// How long from `now` until another request is allowed.
+    pub fn delay(&self, now: Instant) -> Duration {
+        self.limits
+            .iter()
+            .filter_map(|&(requests, per)| {
+                let sent = self.sent.len();
+                // The request that has to leave the window first.
+                let oldest = self.sent.get(sent.checked_sub(requests)?)?;
+                Some((*oldest + per).saturating_duration_since(now))
+            })
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Wait until a request is allowed and count it.
+    pub async fn acquire(&mut self) {
+        let delay = self.delay(Instant::now());
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        let now = Instant::now();
+        let longest = self.limits.iter().map(|&(_, per)| per).max();
+        while let (Some(&first), Some(longest)) = (self.sent.front(), longest) {
+            if now.duration_since(first) < longest {
+                break;
+            }
+            self.sent.pop_front();
+        }
+        self.sent.push_back(now);
+    }
+}
+
+/// A [`RateLimiter`] shared by the clients of one account. Waiting for it
+/// holds the lock, so requests go out in turn.
+pub type SharedLimiter = Arc<Mutex<RateLimiter>>;
+
+/// A logged-in Space-Track client.
+#[derive(Debug)]
+pub struct SpaceTrack {
+    config: SpaceTrackConfig,
+    /// `Cookie` header value of the current session.
+    cookie: Option<String>,
+    limiter: SharedLimiter,
+}
+
+impl SpaceTrack {
+    pub fn new(config: SpaceTrackConfig, limiter: SharedLimiter) -> Self {
+        Self {
+            config,
+            cookie: None,
+            limiter,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn limiter(&self) -> &SharedLimiter {
+        &self.limiter
+    }
+
+    /// Run the GP query, logging in first when there is no session and once
+    /// more when the session has expired.
+    pub async fn fetch(&mut self, client: &reqwest::Client) -> Result<String, String> {
+        if self.cookie.is_none() {
+            self.login(client).await?;
+        }
+        match self.query(client).await? {
+            Some(body) => Ok(body),
+            None => {
+                self.cookie = None;
+                self.login(client).await?;
+                self.query(client)
+                    .await?
+                    .ok_or_else(|| String::from("Space-Track rejected a fresh session"))
+            }
+        }
+    }
+
+    async fn login(&mut self, client: &reqwest::Client) -> Result<(), String> {
+        let identity = self.config.identity.as_deref().unwrap_or_default();
+        let password = self.config.password.as_ref().map_or("", Secret::expose);
+        self.limiter.lock().await.acquire().await;
+        let url = format!(
+            "{}/ajaxauth/login",
+            self.config.base_url.trim_end_matches('/')
+        );
+        let response = client
+            .post(&url)
+            .form(&[("identity", identity), ("password", password)])
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| format!("Space-Track login failed: {e}"))?;
+        let cookie = response
+            .headers()
+            .get_all(reqwest::header::SET_COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| value.split(';').next())
+            .map(str::trim)
+            .collect::<Vec<_>>()
+            .join("; ");
+        let body = response
+            .text()
+            .await
+            .map_err(|e| format!("Space-Track login failed: {e}"))?;
+        // A rejected login still answers 200, with this body.
+        if body.contains("\"Failed\"") || cookie.is_empty() {
+            return Err(format!("Space-Track login failed for `{identity}`"));
+        }
+        self.cookie = Some(cookie);
+        Ok(())
+    }
+
+    /// The query result, or `None` when the session is no longer valid.
+    async fn query(&mut self, client: &reqwest::Client) -> Result<Option<String>, String> {
+        self.limiter.lock().await.acquire().await;
+        let url = self.config.query_url();
+        let response = client
+            .get(&url)
+            .header(
+                reqwest::header::COOKIE,
+                self.cookie.as_deref().unwrap_or_default(),
+            )
+            .send()
+            .await
+            .map_err(|e| format!("Space-Track query failed: {e}"))?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            return Ok(None);
+        }
+        response
+            .error_for_status()
+            .map_err(|e| format!("Space-Track query failed: {e}"))?
+            .text()
+            .await
+            .map(Some)
+            .map_err(|e| format!("Space-Track query failed: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_stub::{HttpStub, Response};
+    use crate::source::{CatalogConfig, CatalogFeed, CatalogSource, HttpPolicy};
+    use crate::test_util;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    const OMM: &str = include_str!("../../../core/tests/data/ISS.omm.json");
+
+    fn config(stub: &HttpStub) -> SpaceTrackConfig {
+        SpaceTrackConfig {
+            base_url: stub.url(""),
+            predicate: String::from("NORAD_CAT_ID/25544"),
+            identity: Some(String::from("analyst@example.org")),
+            password: Some(Secret::new("s3cret&more")),
+        }
+    }
+
+    fn fetch(client: &mut SpaceTrack) -> Result<String, String> {
+        test_util::block_on(client.fetch(&reqwest::Client::new()))
+    }
+
+    /// Stands in for the login and query endpoints, handing out session
+    /// `n` on the n-th login and accepting only the latest one.
+    fn space_track() -> (HttpStub, Arc<AtomicUsize>) {
+        let logins = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&logins);
+        let stub = HttpStub::start(move |request| match request.target.as_str() {
+            "/ajaxauth/login" if request.body.contains("password=s3cret%26more") => {
+                let session = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                Response::new(200, "\"\"")
+                    .header("Set-Cookie", &format!("chocolatechip={session}; path=/"))
+            }
+            "/ajaxauth/login" => Response::new(200, r#"{"Login":"Failed"}"#),
+            "/basicspacedata/query/class/gp/NORAD_CAT_ID/25544/format/json" => {
+                let current = format!("chocolatechip={}", counter.load(Ordering::SeqCst));
+                if request.header("Cookie") == Some(current.as_str()) {
+                    Response::new(200, OMM)
+                } else {
+                    Response::new(401, "")
+                }
+            }
+            _ => Response::new(404, ""),
+        });
+        (stub, logins)
+    }
+
+    #[test]
+    fn logs_in_and_queries_the_gp_class() {
+        let (stub, logins) = space_track();
+        let mut client = SpaceTrack::new(config(&stub), RateLimiter::spacetrack().shared());
+        assert_eq!(fetch(&mut client).unwrap(), OMM);
+        assert_eq!(fetch(&mut client).unwrap(), OMM);
+        assert_eq!(logins.load(Ordering::SeqCst), 1);
+
+        let requests = stub.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(
+            requests[0].body,
+            "identity=analyst%40example.org&password=s3cret%26more"
+        );
+        assert_eq!(requests[1].method, "GET");
+        assert_eq!(requests[2].header("Cookie"), Some("chocolatechip=1"));
+    }
+
+    #[test]
+    fn logs_in_again_when_the_session_expires() {
+        let (stub, logins) = space_track();
+        let mut client = SpaceTrack::new(config(&stub), RateLimiter::spacetrack().shared());
+        client.cookie = Some(String::from("chocolatechip=stale"));
+        assert_eq!(fetch(&mut client).unwrap(), OMM);
+        assert_eq!(logins.load(Ordering::SeqCst), 1);
+        assert_eq!(stub.requests().len(), 3);
+    }
+
+    #[test]
+    fn reports_rejected_logins() {
+        let (stub, _) = space_track();
+        let mut config = config(&stub);
+        config.password = Some(Secret::new("wrong"));
+        let mut client = SpaceTrack::new(config, RateLimiter::spacetrack().shared());
+        let err = fetch(&mut client).unwrap_err();
+        assert_eq!(err, "Space-Track login failed for `analyst@example.org`");
+        assert_eq!(stub.requests().len(), 1);
+    }
+
+    #[test]
+    fn holds_requests_to_the_rate_limits() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(vec![
+            (2, Duration::from_secs(60)),
+            (3, Duration::from_secs(3600)),
+        ]);
+        assert_eq!(limiter.delay(start), Duration::ZERO);
+        limiter
+            .sent
+            .extend([start, start + Duration::from_secs(10)]);
+        assert_eq!(
+            limiter.delay(start + Duration::from_secs(20)),
+            Duration::from_secs(40)
+        );
+        assert_eq!(
+            limiter.delay(start + Duration::from_secs(61)),
+            Duration::ZERO
+        );
+        limiter.sent.push_back(start + Duration::from_secs(70));
+        assert_eq!(
+            limiter.delay(start + Duration::from_secs(80)),
+            Duration::from_secs(3520)
+        );
+
+        let mut limiter = RateLimiter::new(vec![(1, Duration::from_millis(100))]);
+        let runtime = test_util::runtime();
+        let begun = Instant::now();
+        runtime.block_on(async {
+            limiter.acquire().await;
+            limiter.acquire().await;
+        });
+        assert!(begun.elapsed() >= Duration::from_millis(100));
+        assert_eq!(limiter.sent.len(), 1);
+    }
+
+    #[test]
+    fn takes_credentials_from_the_environment() {
+        let catalog: CatalogConfig =
+            toml::from_str("[[sources]]\nkind = \"spacetrack\"\nidentity = \"file@example.org\"\n")
+                .unwrap();
+        let CatalogSource::SpaceTrack(mut config) = catalog.source() else {
+            panic!("expected a Space-Track source");
+        };
+        assert_eq!(config.base_url, SPACETRACK_URL);
+        config.fill_credentials(|_| None);
+        let err = config.require_credentials().unwrap_err();
+        assert!(err.contains(PASSWORD_VAR), "{err}");
+
+        let env = |name: &str| match name {
+            IDENTITY_VAR => Some(String::from("env@example.org")),
+            PASSWORD_VAR => Some(String::from("hunter2")),
+            _ => None,
+        };
+        config.fill_credentials(env);
+        config.require_credentials().unwrap();
+        assert_eq!(config.identity.as_deref(), Some("env@example.org"));
+        assert_eq!(config.password, Some(Secret::new("hunter2")));
+        assert!(!format!("{config:?}").contains("hunter2"));
+        assert_eq!(
+            config.query_url(),
+            "https://www.space-track.org/basicspacedata/query/class/gp/\
+             decay_date/null-val/epoch/>now-30/orderby/norad_cat_id/format/json"
+        );
+    }
+
+    #[test]
+    fn feeds_the_catalog_at_most_once_an_hour() {
+        let (stub, _) = space_track();
+        let source = CatalogSource::SpaceTrack(config(&stub));
+        let policy = HttpPolicy {
+            interval: Duration::ZERO,
+            min_spacing: Duration::ZERO,
+            initial_retry: Duration::ZERO,
+            max_retry: Duration::ZERO,
+        };
+        let mut feed = CatalogFeed::new(&source, policy, None);
+        let client = reqwest::Client::new();
+        let runtime = test_util::runtime();
+        let catalog = runtime.block_on(feed.refresh(&client)).unwrap();
+        assert_eq!(catalog.records.len(), 1);
+        assert_eq!(catalog.records[0].norad_id.get(), 25544);
+        assert!(runtime.block_on(feed.refresh(&client)).is_none());
+        assert_eq!(stub.requests().len(), 2);
+    }
+}
@@ -0,0 +1,38 @@
+//! Fixtures and helpers shared by the unit tests. The element sets are the
+//! ones in `tests/data`, which the CLI tests read too.
+use std::fs;
+use std::future::Future;
+use std::path::PathBuf;
+
+/// ISS (ZARYA) in low Earth orbit.
+pub const ISS: &str = include_str!("../tests/data/iss.tle");
+/// VANGUARD 1, on an eccentric orbit with elements from 2000.
+pub const VANGUARD: &str = include_str!("../tests/data/vanguard.tle");
+/// SL-12 R/B, a rocket body in geostationary orbit.
+pub const GEO: &str = include_str!("../tests/data/geo.tle");
+
+/// An empty directory for test `name` of module `module`, unique to this
+/// process. The test removes it when done.
+pub fn scratch_dir(module: &str, name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "openastrovizd-{module}-{}-{name}",
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// A current-thread runtime with IO and timers, for tests that drive async
+/// code step by step.
+pub fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+/// Run `future` to completion on a fresh [`runtime`].
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    runtime().block_on(future)
+}
@@ -1,22 +1,203 @@
-use openastroviz_core::{Sgp4Propagator, StateVector};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
 
-const CELESTRAK_ACTIVE_TLE_URL: &str =
-    "https://celestrak.org/NORAD/elements/gp.php?GROUP=active&FORMAT=tle";
+use chrono::NaiveDateTime;
+use openastroviz_core::omm::{self, OmmFormat};
+use openastroviz_core::{
+    NoradId, PropagationError, PropagationFailure, Sgp4Propagator, StateVector,
+};
+
+pub const CELESTRAK_ACTIVE_URL: &str =
+    "https://celestrak.org/NORAD/elements/gp.php?GROUP=active&FORMAT=json";
+const CELESTRAK_GP_URL: &str = "https://celestrak.org/NORAD/elements/gp.php";
 
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct OrbitalRecord {
+    /// Catalog number, the key objects are tracked and served by.
+    pub norad_id: NoradId,
     pub name: String,
-    pub line1: String,
-    pub line2: String,
     pub propagator: Sgp4Propagator,
     pub epoch_state: StateVector,
 }
 
-/// Download the daily public TLE catalog from CelesTrak.
-pub async fn fetch_tle_catalog(client: &reqwest::Client) -> Result<String, reqwest::Error> {
+/// How catalog parsers treat records they cannot read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Fail the whole catalog on the first bad record.
+    #[default]
+    Strict,
+    /// Skip bad records, list them in [`ParsedCatalog::rejected`] and carry
+    /// on with the next one.
+    Lenient,
+}
+
+/// Where a record starts in its catalog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordLocation {
+    /// 1-based line of TLE text.
+    Line(usize),
+    /// 1-based position of an OMM message.
+    Message(usize),
+}
+
+impl fmt::Display for RecordLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Line(line) => write!(f, "line {line}"),
+            Self::Message(index) => write!(f, "message {index}"),
+        }
+    }
+}
+
+/// A record a lenient parse skipped, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rejection {
+    pub location: RecordLocation,
+    /// Object name, when the record got far enough to carry one.
+    pub name: Option<String>,
+    pub reason: String,
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{} (`{name}`): {}", self.location, self.reason),
+            None => write!(f, "{}: {}", self.location, self.reason),
+        }
+    }
+}
+
+/// Records parsed from a catalog, plus the objects SGP4 rejected at epoch
+/// and, in lenient mode, the records that could not be read.
+#[derive(Debug, Default)]
+pub struct ParsedCatalog {
+    pub records: Vec<OrbitalRecord>,
+    pub dropped: Vec<PropagationFailure>,
+    pub rejected: Vec<Rejection>,
+}
+
+impl ParsedCatalog {
+    /// Add an object, or record why it was dropped if SGP4 rejects it at
+    /// epoch. Any other error fails the catalog.
+    fn push(
+        &mut self,
+        name: String,
+        propagator: Result<Sgp4Propagator, PropagationError>,
+    ) -> Result<(), String> {
+        let propagator = match propagator {
+            Ok(propagator) => propagator,
+            Err(PropagationError::Propagation(failure)) => {
+                self.dropped.push(failure);
+                return Ok(());
+            }
+            Err(e) => return Err(format!("failed to parse elements for `{name}`: {e}")),
+        };
+        let epoch_state = match propagator.propagate_minutes(0.0) {
+            Ok(state) => state,
+            Err(PropagationError::Propagation(failure)) => {
+                self.dropped.push(failure);
+                return Ok(());
+            }
+            Err(e) => return Err(format!("failed to derive epoch state for `{name}`: {e}")),
+        };
+        self.records.push(OrbitalRecord {
+            norad_id: propagator.norad_id(),
+            name,
+            propagator,
+            epoch_state,
+        });
+        Ok(())
+    }
+}
+
+/// A downloaded catalog and the cache validators the server sent with it.
+#[derive(Debug, Clone)]
+pub struct Download {
+    pub body: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+/// A failed download, with how long the server asked us to wait before
+/// trying again, if it said.
+#[derive(Debug)]
+pub struct FetchError {
+    pub error: reqwest::Error,
+    pub retry_after: Option<Duration>,
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl From<reqwest::Error> for FetchError {
+    fn from(error: reqwest::Error) -> Self {
+        Self {
+            error,
+            retry_after: None,
+        }
+    }
+}
+
+/// Download a catalog, by default CelesTrak's active GP catalog as OMM JSON
+/// ([`CELESTRAK_ACTIVE_URL`]).
+///
+/// The validators of the copy already held are sent along, and `None` is
+/// returned when the server answers that it has not changed.
+pub async fn fetch_catalog(
+    client: &reqwest::Client,
+    url: &str,
+    etag: Option<&str>,
+    last_modified: Option<&str>,
+) -> Result<Option<Download>, FetchError> {
+    use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RETRY_AFTER};
+
+    let mut request = client.get(url);
+    if let Some(etag) = etag {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+    if let Some(last_modified) = last_modified {
+        request = request.header(IF_MODIFIED_SINCE, last_modified);
+    }
+    let response = request.send().await?;
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned)
+    };
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(None);
+    }
+    if let Err(error) = response.error_for_status_ref() {
+        // Only delay-seconds; CelesTrak and Space-Track send no dates.
+        let retry_after = header(RETRY_AFTER)
+            .and_then(|value| value.trim().parse().ok())
+            .map(Duration::from_secs);
+        return Err(FetchError { error, retry_after });
+    }
+    let etag = header(ETAG);
+    let last_modified = header(LAST_MODIFIED);
+    Ok(Some(Download {
+        body: response.text().await?,
+        etag,
+        last_modified,
+    }))
+}
+
+/// Download the current elements for a single catalog number from CelesTrak
+/// as OMM JSON.
+pub async fn fetch_by_norad(
+    client: &reqwest::Client,
+    norad_id: NoradId,
+) -> Result<String, reqwest::Error> {
     client
-        .get(CELESTRAK_ACTIVE_TLE_URL)
+        .get(format!("{CELESTRAK_GP_URL}?CATNR={norad_id}&FORMAT=json"))
         .send()
         .await?
         .error_for_status()?
@@ -24,10 +205,71 @@ pub async fn fetch_tle_catalog(client: &reqwest::Client) -> Result<String, reqwe
         .await
 }
 
+/// Parse a catalog in any format CelesTrak and Space-Track serve: OMM XML,
+/// KVN or JSON, or 3-line TLE text.
+pub fn parse_catalog(body: &str) -> Result<ParsedCatalog, String> {
+    parse_catalog_with(body, ParseMode::Strict)
+}
+
+/// Parse a catalog in any supported format with the given mode.
+///
+/// In lenient mode only an OMM document that cannot be read at all, such as
+/// truncated JSON, is an error.
+pub fn parse_catalog_with(body: &str, mode: ParseMode) -> Result<ParsedCatalog, String> {
+    match (OmmFormat::detect(body), mode) {
+        (Some(format), _) => parse_omm_catalog(body, format, mode),
+        (None, ParseMode::Strict) => parse_tle_catalog(body),
+        (None, ParseMode::Lenient) => Ok(parse_tle_catalog_lenient(body)),
+    }
+}
+
+/// Parse a catalog of OMMs in the given encoding.
+///
+/// Objects SGP4 rejects at epoch are dropped as in [`parse_tle_catalog`].
+/// Invalid messages fail the whole catalog in strict mode and are listed in
+/// [`ParsedCatalog::rejected`] in lenient mode.
+pub fn parse_omm_catalog(
+    body: &str,
+    format: OmmFormat,
+    mode: ParseMode,
+) -> Result<ParsedCatalog, String> {
+    let mut catalog = ParsedCatalog::default();
+    let messages = omm::parse_each(body, format).map_err(|e| e.to_string())?;
+    for (index, message) in messages.into_iter().enumerate() {
+        let location = RecordLocation::Message(index + 1);
+        let outcome = message
+            .map_err(|e| (None, e.to_string()))
+            .and_then(|elements| {
+                let name = elements
+                    .object_name
+                    .clone()
+                    .unwrap_or_else(|| elements.norad_id.to_string());
+                catalog
+                    .push(name.clone(), Sgp4Propagator::from_elements(elements))
+                    .map_err(|reason| (Some(name), reason))
+            });
+        if let Err((name, reason)) = outcome {
+            match mode {
+                ParseMode::Strict => return Err(format!("{location}: {reason}")),
+                ParseMode::Lenient => catalog.rejected.push(Rejection {
+                    location,
+                    name,
+                    reason,
+                }),
+            }
+        }
+    }
+    Ok(catalog)
+}
+
 /// Parse a CelesTrak/Space-Track-style 3-line TLE catalog.
-pub fn parse_tle_catalog(body: &str) -> Result<Vec<OrbitalRecord>, String> {
+///
+/// Malformed lines fail the whole catalog. Objects that parse but cannot be
+/// propagated at their epoch, such as ones that have already decayed, are
+/// moved to [`ParsedCatalog::dropped`] instead.
+pub fn parse_tle_catalog(body: &str) -> Result<ParsedCatalog, String> {
     let mut lines = body.lines().map(str::trim).filter(|line| !line.is_empty());
-    let mut records = Vec::new();
+    let mut catalog = ParsedCatalog::default();
 
     while let Some(name) = lines.next() {
         let line1 = lines
@@ -44,32 +286,141 @@ pub fn parse_tle_catalog(body: &str) -> Result<Vec<OrbitalRecord>, String> {
             return Err(format!("invalid TLE line 2 for `{name}`"));
         }
 
-        let propagator = Sgp4Propagator::from_tle(Some(name.to_owned()), line1, line2)
-            .map_err(|e| format!("failed to parse TLE for `{name}`: {e}"))?;
-        let epoch_state = propagator
-            .propagate_minutes(0.0)
-            .map_err(|e| format!("failed to derive epoch state for `{name}`: {e}"))?;
+        catalog.push(
+            name.to_owned(),
+            Sgp4Propagator::from_tle(Some(name.to_owned()), line1, line2),
+        )?;
+    }
+
+    Ok(catalog)
+}
 
-        records.push(OrbitalRecord {
-            name: name.to_owned(),
-            line1: line1.to_owned(),
-            line2: line2.to_owned(),
-            propagator,
-            epoch_state,
-        });
+/// Parse 2- or 3-line TLE text, skipping whatever cannot be read.
+///
+/// A record is an element line 1 directly followed by its line 2, optionally
+/// preceded by a name line (with or without the `0 ` prefix of the 3LE
+/// format). Stray element lines, names without elements and garbage are
+/// rejected one line at a time, so parsing resynchronises on the next
+/// well-formed pair.
+pub fn parse_tle_catalog_lenient(body: &str) -> ParsedCatalog {
+    let lines: Vec<(usize, &str)> = body
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .collect();
+    let mut catalog = ParsedCatalog::default();
+    let mut name: Option<(usize, &str)> = None;
+    let reject = |catalog: &mut ParsedCatalog, line, name: Option<&str>, reason: &str| {
+        catalog.rejected.push(Rejection {
+            location: RecordLocation::Line(line),
+            name: name.map(str::to_owned),
+            reason: reason.to_owned(),
+        })
+    };
+
+    let mut index = 0;
+    while index < lines.len() {
+        let (number, line) = lines[index];
+        let next = lines.get(index + 1).map(|(_, line)| *line);
+        if is_element_line(line, '1') && next.is_some_and(|next| is_element_line(next, '2')) {
+            let line2 = next.unwrap_or_default();
+            let (start, object_name) = match name.take() {
+                Some((start, name)) => (start, Some(name.to_owned())),
+                None => (number, None),
+            };
+            let propagator = Sgp4Propagator::from_tle(object_name.clone(), line, line2);
+            let record_name = match (&object_name, &propagator) {
+                (Some(name), _) => name.clone(),
+                (None, Ok(propagator)) => propagator.norad_id().to_string(),
+                (None, Err(_)) => line.get(2..7).unwrap_or_default().trim().to_owned(),
+            };
+            if let Err(reason) = catalog.push(record_name, propagator) {
+                reject(&mut catalog, start, object_name.as_deref(), &reason);
+            }
+            index += 2;
+            continue;
+        }
+
+        if is_element_line(line, '1') || is_element_line(line, '2') {
+            let pending = name.take();
+            let reason = if line.starts_with('1') {
+                "line 1 is not followed by line 2"
+            } else {
+                "line 2 is not preceded by line 1"
+            };
+            reject(&mut catalog, number, pending.map(|(_, name)| name), reason);
+        } else {
+            let candidate = line.strip_prefix("0 ").unwrap_or(line);
+            if let Some((start, stale)) = name.replace((number, candidate)) {
+                reject(&mut catalog, start, Some(stale), "no element lines follow");
+            }
+        }
+        index += 1;
     }
+    if let Some((start, stale)) = name {
+        reject(&mut catalog, start, Some(stale), "no element lines follow");
+    }
+    catalog
+}
 
-    Ok(records)
+/// Whether `line` looks like TLE element line `number`: the digit, a blank,
+/// and roughly the full width.
+fn is_element_line(line: &str, number: char) -> bool {
+    let mut chars = line.chars();
+    chars.next() == Some(number) && chars.next() == Some(' ') && line.len() >= 64
+}
+
+/// One record per object, keeping the element set with the newest epoch.
+/// Objects stay in the order they first appear.
+pub fn keep_newest(records: impl IntoIterator<Item = OrbitalRecord>) -> Vec<OrbitalRecord> {
+    let mut kept: Vec<OrbitalRecord> = Vec::new();
+    let mut index = HashMap::new();
+    for record in records {
+        match index.get(&record.norad_id) {
+            Some(&at) => {
+                let current: &mut OrbitalRecord = &mut kept[at];
+                if record.propagator.epoch() > current.propagator.epoch() {
+                    *current = record;
+                }
+            }
+            None => {
+                index.insert(record.norad_id, kept.len());
+                kept.push(record);
+            }
+        }
+    }
+    kept
+}
+
+/// Remove records that fail to propagate to `time`, typically because they
+/// have decayed, and return why each one was dropped.
+pub fn drop_failed(
+    records: &mut Vec<OrbitalRecord>,
+    time: NaiveDateTime,
+) -> Vec<PropagationFailure> {
+    let mut dropped = Vec::new();
+    records.retain(|record| match record.propagator.propagate_datetime(time) {
+        Err(PropagationError::Propagation(failure)) => {
+            dropped.push(failure);
+            false
+        }
+        _ => true,
+    });
+    dropped
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::ISS;
+    use openastroviz_core::FailureKind;
 
     #[test]
     fn parses_three_line_catalog() {
-        let src = "ISS (ZARYA)\n1 25544U 98067A   20194.88612269 -.00002218  00000-0 -31515-4 0  9992\n2 25544  51.6461 221.2784 0001413  89.1723 280.4612 15.49507896236008\n";
-        let records = parse_tle_catalog(src).expect("catalog should parse");
+        let records = parse_tle_catalog(ISS)
+            .expect("catalog should parse")
+            .records;
         assert_eq!(records.len(), 1);
         assert_eq!(records[0].name, "ISS (ZARYA)");
     }
@@ -95,9 +446,184 @@ mod tests {
     #[test]
     fn parses_multiple_objects_and_ignores_blank_lines() {
         let src = "\nISS (ZARYA)\n1 25544U 98067A   20194.88612269 -.00002218  00000-0 -31515-4 0  9992\n2 25544  51.6461 221.2784 0001413  89.1723 280.4612 15.49507896236008\n\nISS CLONE\n1 25544U 98067A   20194.88612269 -.00002218  00000-0 -31515-4 0  9992\n2 25544  51.6461 221.2784 0001413  89.1723 280.4612 15.49507896236008\n";
-        let records = parse_tle_catalog(src).expect("catalog should parse");
+        let records = parse_tle_catalog(src)
+            .expect("catalog should parse")
+            .records;
         assert_eq!(records.len(), 2);
         assert_eq!(records[0].name, "ISS (ZARYA)");
         assert_eq!(records[1].name, "ISS CLONE");
     }
+
+    #[test]
+    fn parses_omm_json_catalog() {
+        let src = include_str!("../../../core/tests/data/ISS.omm.json");
+        let records = parse_catalog(src).expect("catalog should parse").records;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].name, "ISS (ZARYA)");
+        assert_eq!(records[0].norad_id.get(), 25544);
+    }
+
+    #[test]
+    fn omm_and_tle_catalogs_yield_the_same_record() {
+        let expected = &parse_catalog(ISS).unwrap().records[0];
+        for src in [
+            include_str!("../../../core/tests/data/ISS.omm.xml"),
+            include_str!("../../../core/tests/data/ISS.omm.kvn"),
+        ] {
+            let record = &parse_catalog(src).unwrap().records[0];
+            assert_eq!(record.name, expected.name);
+            for axis in 0..3 {
+                let dr =
+                    record.epoch_state.position_km[axis] - expected.epoch_state.position_km[axis];
+                assert!(dr.abs() < 1e-3, "{dr} km");
+            }
+        }
+    }
+
+    #[test]
+    fn accepts_catalog_numbers_beyond_five_digits() {
+        let src = include_str!("../../../core/tests/data/ISS.omm.json").replace("25544", "270544");
+        let records = parse_catalog(&src).expect("catalog should parse").records;
+        assert_eq!(records[0].norad_id.get(), 270544);
+    }
+
+    #[test]
+    fn keys_alpha5_objects_by_full_catalog_number() {
+        let src = "ISS (ZARYA)\n1 25544U 98067A   20194.88612269 -.00002218  00000-0 -31515-4 0  9992\n2 25544  51.6461 221.2784 0001413  89.1723 280.4612 15.49507896236008\nALPHA-5\n1 A5544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2925\n2 A5544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563535\n";
+        let records = parse_catalog(src).expect("catalog should parse").records;
+        let ids: Vec<u32> = records.iter().map(|record| record.norad_id.get()).collect();
+        assert_eq!(ids, [25544, 105544]);
+    }
+
+    #[test]
+    fn rejects_invalid_omm() {
+        let err = parse_catalog(r#"[{"OBJECT_NAME": "ISS (ZARYA)"}]"#).expect_err("must fail");
+        assert!(err.contains("NORAD_CAT_ID"), "{err}");
+    }
+
+    const VANGUARD_LINES: &str = "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753\n2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667\n";
+
+    #[test]
+    fn lenient_mode_resynchronises_after_bad_records() {
+        let src = format!(
+            "<html>mirror error page</html>\n{ISS}CORRUPT\n1 25544U 98067A   20194.8861226X -.00002218  00000-0 -31515-4 0  9992\n2 25544  51.6461 221.2784 0001413  89.1723 280.4612 15.49507896236008\n1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753\n0 VANGUARD 1\n{VANGUARD_LINES}{VANGUARD_LINES}TRAILING NAME\n"
+        );
+        let catalog = parse_catalog_with(&src, ParseMode::Lenient).unwrap();
+
+        let names: Vec<&str> = catalog.records.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["ISS (ZARYA)", "VANGUARD 1", "5"]);
+        let rejected: Vec<(RecordLocation, Option<&str>)> = catalog
+            .rejected
+            .iter()
+            .map(|r| (r.location, r.name.as_deref()))
+            .collect();
+        assert_eq!(
+            rejected,
+            [
+                (
+                    RecordLocation::Line(1),
+                    Some("<html>mirror error page</html>")
+                ),
+                (RecordLocation::Line(5), Some("CORRUPT")),
+                (RecordLocation::Line(8), None),
+                (RecordLocation::Line(14), Some("TRAILING NAME")),
+            ]
+        );
+        assert_eq!(
+            catalog.rejected[2].reason,
+            "line 1 is not followed by line 2"
+        );
+        assert!(catalog.rejected[1].reason.contains("failed to parse"));
+    }
+
+    #[test]
+    fn lenient_mode_skips_a_leading_html_page() {
+        let page = "<!DOCTYPE html>\n<html><head><title>Login</title></head>\n<body>Sign in to continue</body></html>\n";
+        let catalog = parse_catalog_with(&format!("{page}{ISS}"), ParseMode::Lenient).unwrap();
+        let ids: Vec<u32> = catalog.records.iter().map(|r| r.norad_id.get()).collect();
+        assert_eq!(ids, [25544]);
+        assert_eq!(catalog.rejected.len(), 3);
+
+        // On its own the page yields nothing, rather than an XML error.
+        let catalog = parse_catalog_with(page, ParseMode::Lenient).unwrap();
+        assert!(catalog.records.is_empty());
+        assert!(!catalog.rejected.is_empty());
+        assert!(parse_catalog(page).is_err());
+    }
+
+    #[test]
+    fn lenient_mode_reads_two_line_catalogs() {
+        let src = format!("{VANGUARD_LINES}\n{}", ISS.split_once('\n').unwrap().1);
+        let catalog = parse_catalog_with(&src, ParseMode::Lenient).unwrap();
+        assert!(catalog.rejected.is_empty());
+        let ids: Vec<u32> = catalog.records.iter().map(|r| r.norad_id.get()).collect();
+        assert_eq!(ids, [5, 25544]);
+        assert_eq!(catalog.records[1].name, "25544");
+    }
+
+    #[test]
+    fn lenient_mode_skips_invalid_omm_messages() {
+        let iss = include_str!("../../../core/tests/data/ISS.omm.json");
+        let object = iss.trim().trim_start_matches('[').trim_end_matches(']');
+        let broken = object
+            .replace("\"BSTAR\"", "\"B_STAR\"")
+            .replace("25544", "25545");
+        let src = format!("[{object}, {broken}]");
+
+        let err = parse_catalog(&src).expect_err("strict mode must fail");
+        assert!(err.starts_with("message 2: "), "{err}");
+
+        let catalog = parse_catalog_with(&src, ParseMode::Lenient).unwrap();
+        assert_eq!(catalog.records.len(), 1);
+        assert_eq!(
+            catalog.rejected,
+            [Rejection {
+                location: RecordLocation::Message(2),
+                name: None,
+                reason: "OMM is missing BSTAR".to_owned(),
+            }]
+        );
+        assert_eq!(
+            catalog.rejected[0].to_string(),
+            "message 2: OMM is missing BSTAR"
+        );
+    }
+
+    #[test]
+    fn lenient_mode_still_fails_on_unreadable_documents() {
+        let err = parse_catalog_with("[{\"OBJECT_NAME\": ", ParseMode::Lenient)
+            .expect_err("truncated JSON must fail");
+        assert!(err.contains("JSON"), "{err}");
+    }
+
+    #[test]
+    fn drops_objects_that_fail_at_epoch() {
+        // The second object's perturbed eccentricity diverges at epoch.
+        let src = "ISS (ZARYA)\n1 25544U 98067A   20194.88612269 -.00002218  00000-0 -31515-4 0  9992\n2 25544  51.6461 221.2784 0001413  89.1723 280.4612 15.49507896236008\nDIVERGING\n1 33334U 78066F   06174.85818871  .00000620  00000-0  10000-3 0  6806\n2 33334  68.4714 236.1303 5602877 123.7484 302.5767  0.00001000 67521\n";
+        let catalog = parse_tle_catalog(src).expect("catalog should parse");
+        assert_eq!(catalog.records.len(), 1);
+        assert_eq!(catalog.dropped.len(), 1);
+        assert_eq!(catalog.dropped[0].norad_id.get(), 33334);
+        assert_eq!(
+            catalog.dropped[0].kind,
+            FailureKind::PerturbedEccentricityOutOfRange
+        );
+    }
+
+    #[test]
+    fn drops_decayed_objects_from_the_live_catalog() {
+        let src = "VANGUARD 1\n1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753\n2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667\nREENTRY\n1 28872U 05037B   05333.02012661  .25992681  00000-0  24476-3 0  1534\n2 28872  96.4736 157.9986 0303955 244.0492 110.6523 16.46015938 10708\n";
+        let mut records = parse_tle_catalog(src)
+            .expect("catalog should parse")
+            .records;
+        assert_eq!(records.len(), 2);
+
+        let an_hour_later = records[1].propagator.epoch() + chrono::Duration::minutes(60);
+        let dropped = drop_failed(&mut records, an_hour_later);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].name, "VANGUARD 1");
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(dropped[0].norad_id.get(), 28872);
+        assert_eq!(dropped[0].kind, FailureKind::Decayed);
+    }
 }
@@ -6,7 +6,7 @@ use std::sync::Mutex;
 static TEST_MUTEX: Mutex<()> = Mutex::new(());
 
 mod util;
-use util::cleanup;
+use util::{cleanup, offline_config, remove_config};
 
 #[test]
 fn runs_without_args_shows_version() {
@@ -63,41 +63,54 @@ fn help_includes_description() {
 fn start_subcommand_outputs_message() {
     let _lock = TEST_MUTEX.lock().unwrap();
     cleanup();
+    let config = offline_config("cli-start");
     Command::cargo_bin("openastrovizd")
         .unwrap()
         .arg("start")
+        .arg("--config")
+        .arg(&config)
         .assert()
         .success()
         .stdout(contains("Daemon started"));
     cleanup();
+    remove_config(&config);
 }
 
 #[test]
 fn start_then_status_subcommand_reports_running() {
     let _lock = TEST_MUTEX.lock().unwrap();
     cleanup();
+    let config = offline_config("cli-status");
     Command::cargo_bin("openastrovizd")
         .unwrap()
         .arg("start")
+        .arg("--config")
+        .arg(&config)
         .assert()
         .success();
     Command::cargo_bin("openastrovizd")
         .unwrap()
         .arg("status")
+        .arg("--config")
+        .arg(&config)
         .assert()
         .success()
         .stdout(contains("Daemon is running"));
     cleanup();
+    remove_config(&config);
 }
 
 #[test]
 fn stop_subcommand_stops_daemon_and_removes_pid() {
     let _lock = TEST_MUTEX.lock().unwrap();
     cleanup();
+    let config = offline_config("cli-stop");
     // Start the daemon first
     Command::cargo_bin("openastrovizd")
         .unwrap()
         .arg("start")
+        .arg("--config")
+        .arg(&config)
         .assert()
         .success();
     let pid_path = std::env::temp_dir().join("openastrovizd.pid");
@@ -107,6 +120,8 @@ fn stop_subcommand_stops_daemon_and_removes_pid() {
     Command::cargo_bin("openastrovizd")
         .unwrap()
         .arg("stop")
+        .arg("--config")
+        .arg(&config)
         .assert()
         .success()
         .stdout(contains("Daemon stopped"));
@@ -115,9 +130,253 @@ fn stop_subcommand_stops_daemon_and_removes_pid() {
     Command::cargo_bin("openastrovizd")
         .unwrap()
         .arg("status")
+        .arg("--config")
+        .arg(&config)
         .assert()
         .success()
         .stdout(contains("Daemon is not running"));
     assert!(!pid_path.exists());
     cleanup();
+    remove_config(&config);
+}
+
+#[test]
+fn passes_subcommand_prints_table_from_local_catalog() {
+    let tle = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/iss.tle");
+    Command::cargo_bin("openastrovizd")
+        .unwrap()
+        .args([
+            "passes", "--norad", "25544", "--lat", "40.015", "--lon", "-105.27",
+        ])
+        .args(["--start", "2020-07-13T00:00:00Z", "--tle"])
+        .arg(&tle)
+        .assert()
+        .success()
+        .stdout(contains("ISS (ZARYA)").and(contains("MAX EL")));
+}
+
+#[test]
+fn passes_subcommand_fails_for_unknown_object() {
+    let tle = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/iss.tle");
+    Command::cargo_bin("openastrovizd")
+        .unwrap()
+        .args([
+            "passes", "--norad", "5", "--lat", "0", "--lon", "0", "--tle",
+        ])
+        .arg(&tle)
+        .assert()
+        .failure()
+        .stderr(contains("is not in the catalog"));
+}
+
+#[test]
+fn screen_subcommand_reports_close_approaches() {
+    let tle = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/crossing.tle");
+    Command::cargo_bin("openastrovizd")
+        .unwrap()
+        .args([
+            "screen",
+            "--start",
+            "2020-07-12T21:00:00Z",
+            "--days",
+            "0.25",
+        ])
+        .arg("--catalog")
+        .arg(&tle)
+        .assert()
+        .success()
+        .stdout(contains(
+            "90001,CROSSING A,90002,CROSSING B DEB,2020-07-12T21:16:0",
+        ))
+        .stderr(contains("propagations").and(contains("approaches under 5 km")));
+}
+
+#[test]
+fn screen_subcommand_fails_for_unknown_primary() {
+    let tle = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/crossing.tle");
+    Command::cargo_bin("openastrovizd")
+        .unwrap()
+        .args(["screen", "--primary", "99999", "--days", "0.1", "--catalog"])
+        .arg(&tle)
+        .assert()
+        .failure()
+        .stderr(contains("not in the catalog"));
+}
+
+#[test]
+fn catalog_validate_accepts_clean_catalog() {
+    let tle = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/iss.tle");
+    Command::cargo_bin("openastrovizd")
+        .unwrap()
+        .args(["catalog", "validate"])
+        .arg(&tle)
+        .assert()
+        .success()
+        .stdout(contains("\"valid\": true").and(contains("\"objects\": 1")));
+}
+
+#[test]
+fn catalog_validate_reports_problems_as_json() {
+    let tle = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/bad.tle");
+    Command::cargo_bin("openastrovizd")
+        .unwrap()
+        .args(["catalog", "validate"])
+        .arg(&tle)
+        .assert()
+        .code(1)
+        .stdout(
+            contains("\"valid\": false")
+                .and(contains("\"code\": \"checksum\""))
+                .and(contains("\"code\": \"catalog-mismatch\"")),
+        );
+}
+
+#[test]
+fn catalog_convert_writes_omm_json() {
+    let tle = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/iss.tle");
+    Command::cargo_bin("openastrovizd")
+        .unwrap()
+        .args(["catalog", "convert", "--to", "json", "--norad", "25544"])
+        .arg(&tle)
+        .assert()
+        .success()
+        .stdout(
+            contains("\"OBJECT_NAME\": \"ISS (ZARYA)\"").and(contains("\"BSTAR\": -0.000031515")),
+        );
+}
+
+#[test]
+fn service_rejects_unknown_catalog_source() {
+    let config = std::env::temp_dir().join(format!(
+        "openastrovizd-bad-source-{}.toml",
+        std::process::id()
+    ));
+    std::fs::write(&config, "[[catalog.sources]]\nkind = \"ftp\"\n").unwrap();
+    let mut cmd = Command::cargo_bin("openastrovizd").unwrap();
+    cmd.arg("--run-service")
+        .arg("--config")
+        .arg(&config)
+        .assert()
+        .failure()
+        .stderr(contains("is invalid").and(contains("unknown variant `ftp`")));
+    std::fs::remove_file(config).unwrap();
+}
+
+#[test]
+fn status_subcommand_reports_catalog_cache() {
+    let _lock = TEST_MUTEX.lock().unwrap();
+    cleanup();
+    let dir = std::env::temp_dir().join(format!("openastrovizd-status-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let config = dir.join("openastrovizd.toml");
+    std::fs::write(&config, "[cache]\ndir = \"cache\"\n").unwrap();
+    let mut cmd = Command::cargo_bin("openastrovizd").unwrap();
+    cmd.arg("status")
+        .arg("--config")
+        .arg(&config)
+        .assert()
+        .success()
+        .stdout(contains("Daemon is not running").and(contains("No cached catalog")));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn service_requires_spacetrack_credentials() {
+    let config = std::env::temp_dir().join(format!(
+        "openastrovizd-spacetrack-{}.toml",
+        std::process::id()
+    ));
+    std::fs::write(&config, "[[catalog.sources]]\nkind = \"spacetrack\"\n").unwrap();
+    let mut cmd = Command::cargo_bin("openastrovizd").unwrap();
+    cmd.arg("--run-service")
+        .arg("--config")
+        .arg(&config)
+        .env_remove("OPENASTROVIZD_SPACETRACK_IDENTITY")
+        .env_remove("OPENASTROVIZD_SPACETRACK_PASSWORD")
+        .assert()
+        .failure()
+        .stderr(contains("OPENASTROVIZD_SPACETRACK_PASSWORD"));
+    std::fs::remove_file(config).unwrap();
+}
+
+#[test]
+fn status_does_not_need_spacetrack_credentials() {
+    let _lock = TEST_MUTEX.lock().unwrap();
+    cleanup();
+    let config = std::env::temp_dir().join(format!(
+        "openastrovizd-status-spacetrack-{}.toml",
+        std::process::id()
+    ));
+    std::fs::write(&config, "[[catalog.sources]]\nkind = \"spacetrack\"\n").unwrap();
+    let mut cmd = Command::cargo_bin("openastrovizd").unwrap();
+    cmd.arg("status")
+        .arg("--config")
+        .arg(&config)
+        .env_remove("OPENASTROVIZD_SPACETRACK_IDENTITY")
+        .env_remove("OPENASTROVIZD_SPACETRACK_PASSWORD")
+        .assert()
+        .success()
+        .stdout(contains("Daemon is not running"));
+    std::fs::remove_file(config).unwrap();
+}
+
+#[test]
+fn config_check_prints_effective_config() {
+    let config = std::env::temp_dir().join(format!(
+        "openastrovizd-config-check-{}.toml",
+        std::process::id()
+    ));
+    std::fs::write(&config, "[server]\nlisten = \"0.0.0.0:9000\"\n").unwrap();
+    let mut cmd = Command::cargo_bin("openastrovizd").unwrap();
+    cmd.args(["config", "check", "--config"])
+        .arg(&config)
+        .env("OPENASTROVIZD_CONJUNCTION_TOP_N", "3")
+        .assert()
+        .success()
+        .stdout(
+            contains("is valid")
+                .and(contains("Overridden by OPENASTROVIZD_CONJUNCTION_TOP_N"))
+                .and(contains("listen = \"0.0.0.0:9000\""))
+                .and(contains("top_n = 3")),
+        );
+    std::fs::remove_file(config).unwrap();
+}
+
+#[test]
+fn config_check_warns_about_the_cuda_backend() {
+    let config = std::env::temp_dir().join(format!(
+        "openastrovizd-config-cuda-{}.toml",
+        std::process::id()
+    ));
+    std::fs::write(&config, "[compute]\nbackend = \"cuda\"\n").unwrap();
+    let mut cmd = Command::cargo_bin("openastrovizd").unwrap();
+    cmd.args(["config", "check", "--config"])
+        .arg(&config)
+        .assert()
+        .success()
+        .stdout(contains("is valid").and(contains("Warning: compute.backend")));
+    std::fs::remove_file(config).unwrap();
+}
+
+#[test]
+fn config_check_reports_each_problem() {
+    let config = std::env::temp_dir().join(format!(
+        "openastrovizd-config-problems-{}.toml",
+        std::process::id()
+    ));
+    std::fs::write(
+        &config,
+        "[catalog]\npoll_interval_secs = 0\n\n[[catalog.sources]]\nkind = \"http\"\nurl = \"mirror\"\n",
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("openastrovizd").unwrap();
+    cmd.args(["config", "check", "--config"])
+        .arg(&config)
+        .assert()
+        .failure()
+        .stderr(
+            contains("catalog.sources[0].url: `mirror` is not an http:// or https:// URL")
+                .and(contains("catalog.poll_interval_secs: must be at least 1")),
+        );
+    std::fs::remove_file(config).unwrap();
 }
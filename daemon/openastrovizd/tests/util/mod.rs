@@ -1,4 +1,5 @@
 use std::fs;
+use std::path::PathBuf;
 #[cfg(not(unix))]
 use std::process::Command;
 
@@ -19,3 +20,30 @@ pub fn cleanup() {
     }
     let _ = fs::remove_file(pid_path);
 }
+
+/// Writes a config for a service that reads the ISS catalog from a local file
+/// and caches it in a directory of its own, so tests never reach the network
+/// or the user's cache. Remove it with [`remove_config`].
+pub fn offline_config(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("openastrovizd-test-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("catalog.tle"), include_str!("../data/iss.tle")).unwrap();
+    let config = dir.join("openastrovizd.toml");
+    fs::write(
+        &config,
+        "[server]\nlisten = \"127.0.0.1:0\"\n\n\
+         [[catalog.sources]]\nkind = \"file\"\npath = \"catalog.tle\"\n\n\
+         [cache]\ndir = \"cache\"\n",
+    )
+    .unwrap();
+    config
+}
+
+/// Removes a config written by [`offline_config`] and its cache.
+pub fn remove_config(config: &std::path::Path) {
+    if let Some(dir) = config.parent() {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
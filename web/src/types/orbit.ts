@@ -19,7 +19,7 @@ export interface CloseApproach {
 
 export interface OrbitalObject {
   id: string;
-  noradId: number;
+  noradId: number; // full catalog number (up to 9 digits), never Alpha-5 text
   name: string;
   kind: ObjectKind;
   position: [number, number, number]; // km in ECI
@@ -43,11 +43,12 @@ export interface OrbitalSnapshot {
 }
 
 export interface OrbitalStreamMessage {
-  kind: 'snapshot' | 'update' | 'telemetry';
+  kind: 'snapshot' | 'update' | 'telemetry' | 'error';
   snapshot?: OrbitalSnapshot;
   update?: {
     epoch: string;
     objects: Array<Pick<OrbitalObject, 'id' | 'position' | 'velocity' | 'health'>>;
     closeApproaches?: CloseApproach[];
   };
+  message?: string;
 }
@@ -1,11 +1,469 @@
-// OpenAstroViz FP32 SGP4 WebGPU scaffold.
+// OpenAstroViz FP32 SGP4 kernel.
 //
-// The body here intentionally keeps a lightweight placeholder while we port the
-// full CUDA FP32 SGP4 math and data-layout bindings. It validates workgroup
-// sizing and dispatch wiring for browser compute execution.
+// One `PackedElements` and one time offset (minutes since that object's
+// epoch) in, six state planes and one status code out. SGP4 initialization
+// runs on the host in f64 (`PackedElements::from_elements` in
+// openastroviz-core); this kernel evaluates the time-dependent part of the
+// near-earth and deep-space models in f32. It is kept statement for statement
+// in line with `openastroviz_core::fp32::propagate`, its host reference.
+
+// Mirror of `openastroviz_core::PackedElements`; field order is the ABI.
+struct PackedElements {
+    status: u32,
+    method: u32,
+    resonance: u32,
+    inclination: f32,
+    right_ascension: f32,
+    eccentricity: f32,
+    argument_of_perigee: f32,
+    mean_anomaly: f32,
+    mean_motion: f32,
+    right_ascension_dot: f32,
+    argument_of_perigee_dot: f32,
+    mean_anomaly_dot: f32,
+    c1: f32,
+    c4: f32,
+    k0: f32,
+    k1: f32,
+    a0: f32,
+    k2: f32,
+    k3: f32,
+    k4: f32,
+    k5: f32,
+    k6: f32,
+    c5: f32,
+    d2: f32,
+    d3: f32,
+    d4: f32,
+    eta: f32,
+    k7: f32,
+    k8: f32,
+    k9: f32,
+    k10: f32,
+    k11: f32,
+    k12: f32,
+    k13: f32,
+    eccentricity_dot: f32,
+    inclination_dot: f32,
+    solar: array<f32, 13>,
+    lunar: array<f32, 13>,
+    lambda_0: f32,
+    lambda_dot_0: f32,
+    sidereal_time_0: f32,
+    k14: f32,
+    resonance_terms: array<f32, 10>,
+}
+
+// Status codes shared with `openastroviz_core::ObjectStatus`.
+const STATUS_OK: u32 = 0u;
+const STATUS_ECCENTRICITY_OUT_OF_RANGE: u32 = 1u;
+const STATUS_PERTURBED_ECCENTRICITY_OUT_OF_RANGE: u32 = 3u;
+const STATUS_SEMI_LATUS_RECTUM_NEGATIVE: u32 = 4u;
+const STATUS_DECAYED: u32 = 6u;
+const STATUS_INVALID_TIME: u32 = 7u;
+
+// `openastroviz_core::fp32::METHOD_*` and `RESONANCE_*`.
+const METHOD_NEAR_EARTH_LOW_PERIGEE: u32 = 0u;
+const METHOD_DEEP_SPACE: u32 = 2u;
+const RESONANCE_NONE: u32 = 0u;
+const RESONANCE_ONE_DAY: u32 = 1u;
+
+// WGS-84 geopotential.
+const AE: f32 = 6378.137;
+const KE: f32 = 0.07436685316871385;
+const J2: f32 = 0.00108262998905;
+const J3_OVER_J2: f32 = -0.0023388905587420003;
+const VELOCITY_SCALE: f32 = 7.905366296149016;
+
+const SOLAR_ECCENTRICITY: f32 = 0.01675;
+const LUNAR_ECCENTRICITY: f32 = 0.05490;
+const SOLAR_MEAN_MOTION: f32 = 1.19459e-5;
+const LUNAR_MEAN_MOTION: f32 = 1.5835218e-4;
+const SIDEREAL_SPEED: f32 = 4.37526908801129966e-3;
+
+const RESONANCE_STEP: f32 = 720.0;
+const MAX_RESONANCE_STEPS: u32 = 100000u;
+const LAMBDA31: f32 = 0.13130908;
+const LAMBDA22: f32 = 2.8843198;
+const LAMBDA33: f32 = 0.37448087;
+const G22: f32 = 5.7686396;
+const G32: f32 = 0.95240898;
+const G44: f32 = 1.8014998;
+const G52: f32 = 1.0508330;
+const G54: f32 = 4.4108898;
+
+const KEPLER_TOLERANCE: f32 = 1.0e-7;
+const PI: f32 = 3.14159265358979;
+const TAU: f32 = 6.28318530717959;
+
+@group(0) @binding(0) var<storage, read> elements: array<PackedElements>;
+@group(0) @binding(1) var<storage, read> times: array<f32>;
+// Structure of arrays: x, y, z (km) then vx, vy, vz (km/s), `count` entries each.
+@group(0) @binding(2) var<storage, read_write> states: array<f32>;
+@group(0) @binding(3) var<storage, read_write> status: array<u32>;
+
+// Mean elements and short-period coefficients at the target time.
+struct MeanState {
+    status: u32,
+    inclination: f32,
+    right_ascension: f32,
+    eccentricity: f32,
+    argument_of_perigee: f32,
+    mean_anomaly: f32,
+    mean_motion: f32,
+    a: f32,
+    k2: f32,
+    k3: f32,
+    k4: f32,
+    k5: f32,
+    k6: f32,
+}
+
+struct Propagated {
+    status: u32,
+    position: vec3<f32>,
+    velocity: vec3<f32>,
+}
+
+struct Resonance {
+    status: u32,
+    a: f32,
+    mean_anomaly: f32,
+}
+
+fn failed_mean(code: u32) -> MeanState {
+    var mean: MeanState;
+    mean.status = code;
+    return mean;
+}
+
+// Checked through the bit pattern because NaN comparisons may be optimized out.
+fn is_finite(value: f32) -> bool {
+    return (bitcast<u32>(value) & 0x7f800000u) != 0x7f800000u;
+}
+
+fn near_earth_mean_state(e: PackedElements, t: f32, p22: f32, p23: f32) -> MeanState {
+    let t2 = t * t;
+    let p24 = e.mean_anomaly + e.mean_anomaly_dot * t;
+    var argument_of_perigee: f32;
+    var mean_anomaly: f32;
+    var a: f32;
+    var p27: f32;
+    if (e.method == METHOD_NEAR_EARTH_LOW_PERIGEE) {
+        let c1t = 1.0 - e.c1 * t;
+        argument_of_perigee = p23;
+        mean_anomaly = p24 + e.mean_motion * e.k1 * t2;
+        a = e.a0 * c1t * c1t;
+        p27 = e.eccentricity - e.c4 * t;
+    } else {
+        let damping = 1.0 + e.eta * cos(p24);
+        let p25 = e.k13 * (damping * damping * damping - e.k11) + e.k12 * t;
+        let p26 = p24 + p25;
+        let t3 = t2 * t;
+        let t4 = t3 * t;
+        let c1t = 1.0 - e.c1 * t - e.d2 * t2 - e.d3 * t3 - e.d4 * t4;
+        argument_of_perigee = p23 - p25;
+        mean_anomaly = p26 + e.mean_motion * (e.k1 * t2 + e.k8 * t3 + t4 * (e.k9 + t * e.k10));
+        a = e.a0 * c1t * c1t;
+        p27 = e.eccentricity - (e.c4 * t + e.c5 * (sin(p26) - e.k7));
+    }
+    if (p27 < -0.001 || p27 >= 1.0) {
+        return failed_mean(STATUS_ECCENTRICITY_OUT_OF_RANGE);
+    }
+
+    var mean: MeanState;
+    mean.status = STATUS_OK;
+    mean.inclination = e.inclination;
+    mean.right_ascension = p22;
+    mean.eccentricity = max(p27, 1.0e-6);
+    mean.argument_of_perigee = argument_of_perigee;
+    mean.mean_anomaly = mean_anomaly;
+    mean.mean_motion = KE / (a * sqrt(a));
+    mean.a = a;
+    mean.k2 = e.k2;
+    mean.k3 = e.k3;
+    mean.k4 = e.k4;
+    mean.k5 = e.k5;
+    mean.k6 = e.k6;
+    return mean;
+}
+
+// Lunar or solar long-period terms: eccentricity, inclination and mean
+// anomaly offsets, then the argument of perigee and node terms.
+fn third_body_periodics(k: array<f32, 13>, eccentricity: f32, mean_motion: f32, t: f32) -> array<f32, 5> {
+    let mean_anomaly = k[12] + mean_motion * t;
+    let fx = mean_anomaly + 2.0 * eccentricity * sin(mean_anomaly);
+    let sin_fx = sin(fx);
+    let f2 = 0.5 * sin_fx * sin_fx - 0.25;
+    let f3 = -0.5 * sin_fx * cos(fx);
+    return array<f32, 5>(
+        k[0] * f2 + k[1] * f3,
+        k[2] * f2 + k[3] * f3,
+        k[4] * f2 + k[5] * f3 + k[6] * sin_fx,
+        k[7] * f2 + k[8] * f3 + k[9] * sin_fx,
+        k[10] * f2 + k[11] * f3,
+    );
+}
+
+// Integrate the resonance equations from epoch to `t` in 720-minute steps.
+fn integrate_resonance(e: PackedElements, t: f32, p22: f32, p23: f32) -> Resonance {
+    let sidereal_time = (e.sidereal_time_0 + t * SIDEREAL_SPEED) % TAU;
+    var step = -RESONANCE_STEP;
+    if (t > 0.0) {
+        step = RESONANCE_STEP;
+    }
+    let half_step_squared = RESONANCE_STEP * RESONANCE_STEP / 2.0;
+    let d = e.resonance_terms;
+
+    var ti = 0.0;
+    var mean_motion = e.mean_motion;
+    var lambda = e.lambda_0;
+    for (var i = 0u; i < MAX_RESONANCE_STEPS; i++) {
+        let lambda_dot = mean_motion + e.lambda_dot_0;
+        var ni_dot: f32;
+        var ni_ddot: f32;
+        if (e.resonance == RESONANCE_ONE_DAY) {
+            ni_dot = d[0] * sin(lambda - LAMBDA31)
+                + d[1] * sin(2.0 * (lambda - LAMBDA22))
+                + d[2] * sin(3.0 * (lambda - LAMBDA33));
+            ni_ddot = (d[0] * cos(lambda - LAMBDA31)
+                + 2.0 * d[1] * cos(2.0 * (lambda - LAMBDA22))
+                + 3.0 * d[2] * cos(3.0 * (lambda - LAMBDA33)))
+                * lambda_dot;
+        } else {
+            let w = e.argument_of_perigee + e.k14 * ti;
+            ni_dot = d[0] * sin(2.0 * w + lambda - G22)
+                + d[1] * sin(lambda - G22)
+                + d[2] * sin(w + lambda - G32)
+                + d[3] * sin(-w + lambda - G32)
+                + d[4] * sin(2.0 * w + 2.0 * lambda - G44)
+                + d[5] * sin(2.0 * lambda - G44)
+                + d[6] * sin(w + lambda - G52)
+                + d[7] * sin(-w + lambda - G52)
+                + d[8] * sin(w + 2.0 * lambda - G54)
+                + d[9] * sin(-w + 2.0 * lambda - G54);
+            ni_ddot = (d[0] * cos(2.0 * w + lambda - G22)
+                + d[1] * cos(lambda - G22)
+                + d[2] * cos(w + lambda - G32)
+                + d[3] * cos(-w + lambda - G32)
+                + d[6] * cos(w + lambda - G52)
+                + d[7] * cos(-w + lambda - G52)
+                + 2.0 * (d[4] * cos(2.0 * w + 2.0 * lambda - G44)
+                    + d[5] * cos(2.0 * lambda - G44)
+                    + d[8] * cos(w + 2.0 * lambda - G54)
+                    + d[9] * cos(-w + 2.0 * lambda - G54)))
+                * lambda_dot;
+        }
+
+        var done = t - step > ti;
+        if (t > 0.0) {
+            done = t - step < ti;
+        }
+        if (done) {
+            let dt = t - ti;
+            let n = mean_motion + ni_dot * dt + ni_ddot * dt * dt * 0.5;
+            let lambda_t = lambda + lambda_dot * dt + ni_dot * dt * dt * 0.5;
+            var mean_anomaly = lambda_t - 2.0 * p22 + 2.0 * sidereal_time;
+            if (e.resonance == RESONANCE_ONE_DAY) {
+                mean_anomaly = lambda_t - p22 - p23 + sidereal_time;
+            }
+            return Resonance(STATUS_OK, pow(KE / n, 2.0 / 3.0), mean_anomaly);
+        }
+
+        ti += step;
+        mean_motion += ni_dot * step + ni_ddot * half_step_squared;
+        lambda += lambda_dot * step + ni_dot * half_step_squared;
+    }
+    return Resonance(STATUS_INVALID_TIME, 0.0, 0.0);
+}
+
+fn deep_space_mean_state(e: PackedElements, t: f32, p22: f32, p23: f32) -> MeanState {
+    var p28 = e.a0;
+    var p29 = e.mean_anomaly + e.mean_anomaly_dot * t;
+    if (e.resonance != RESONANCE_NONE) {
+        let resonance = integrate_resonance(e, t, p22, p23);
+        if (resonance.status != STATUS_OK) {
+            return failed_mean(resonance.status);
+        }
+        p28 = resonance.a;
+        p29 = resonance.mean_anomaly;
+    }
+
+    let solar = third_body_periodics(e.solar, SOLAR_ECCENTRICITY, SOLAR_MEAN_MOTION, t);
+    let lunar = third_body_periodics(e.lunar, LUNAR_ECCENTRICITY, LUNAR_MEAN_MOTION, t);
+    let delta_eccentricity = solar[0] + lunar[0];
+    let delta_inclination = solar[1] + lunar[1];
+    let delta_mean_anomaly = solar[2] + lunar[2];
+    let p4 = solar[3] + lunar[3];
+    let p5 = solar[4] + lunar[4];
+
+    let inclination = e.inclination + e.inclination_dot * t + delta_inclination;
+    let sin_i = sin(inclination);
+    let cos_i = cos(inclination);
+    var right_ascension: f32;
+    var argument_of_perigee: f32;
+    if (inclination >= 0.2) {
+        right_ascension = p22 + p5 / sin_i;
+        argument_of_perigee = p23 + p4 - cos_i * (p5 / sin_i);
+    } else {
+        // Lyddane modification for low inclinations.
+        let sin_o = sin(p22);
+        let cos_o = cos(p22);
+        let p30 = atan2(
+            sin_i * sin_o + (p5 * cos_o + delta_inclination * cos_i * sin_o),
+            sin_i * cos_o + (-p5 * sin_o + delta_inclination * cos_i * cos_o),
+        );
+        let p22_wrapped = p22 % TAU;
+        right_ascension = p30;
+        if (p30 < p22_wrapped - PI) {
+            right_ascension = p30 + TAU;
+        } else if (p30 > p22_wrapped + PI) {
+            right_ascension = p30 - TAU;
+        }
+        argument_of_perigee = p23 + p4 + cos_i * (p22_wrapped - right_ascension)
+            - delta_inclination * p22_wrapped * sin_i;
+    }
+
+    let p31 = e.eccentricity + e.eccentricity_dot * t - e.c4 * t;
+    if (p31 < -0.001 || p31 >= 1.0) {
+        return failed_mean(STATUS_ECCENTRICITY_OUT_OF_RANGE);
+    }
+    let eccentricity = max(p31, 1.0e-6) + delta_eccentricity;
+    if (eccentricity < 0.0 || eccentricity > 1.0) {
+        return failed_mean(STATUS_PERTURBED_ECCENTRICITY_OUT_OF_RANGE);
+    }
+    let c1t = 1.0 - e.c1 * t;
+    let a = p28 * c1t * c1t;
+    var k5_denominator = 1.5e-12;
+    if (abs(1.0 + cos_i) > 1.5e-12) {
+        k5_denominator = 1.0 + cos_i;
+    }
+
+    var mean: MeanState;
+    mean.status = STATUS_OK;
+    mean.inclination = inclination;
+    mean.right_ascension = right_ascension;
+    mean.eccentricity = eccentricity;
+    mean.argument_of_perigee = argument_of_perigee;
+    mean.mean_anomaly = p29 + delta_mean_anomaly + e.mean_motion * e.k1 * t * t;
+    mean.mean_motion = KE / (a * sqrt(a));
+    mean.a = a;
+    mean.k2 = -0.5 * J3_OVER_J2 * sin_i;
+    mean.k3 = 1.0 - cos_i * cos_i;
+    mean.k4 = 7.0 * cos_i * cos_i - 1.0;
+    mean.k5 = -0.25 * J3_OVER_J2 * sin_i * (3.0 + 5.0 * cos_i) / k5_denominator;
+    mean.k6 = 3.0 * cos_i * cos_i - 1.0;
+    return mean;
+}
+
+fn propagate(e: PackedElements, t: f32) -> Propagated {
+    var out: Propagated;
+    if (e.status != STATUS_OK) {
+        out.status = e.status;
+        return out;
+    }
+    if (!is_finite(t)) {
+        out.status = STATUS_INVALID_TIME;
+        return out;
+    }
+
+    let p22 = e.right_ascension + e.right_ascension_dot * t + e.k0 * t * t;
+    let p23 = e.argument_of_perigee + e.argument_of_perigee_dot * t;
+    var mean: MeanState;
+    if (e.method == METHOD_DEEP_SPACE) {
+        mean = deep_space_mean_state(e, t, p22, p23);
+    } else {
+        mean = near_earth_mean_state(e, t, p22, p23);
+    }
+    if (mean.status != STATUS_OK) {
+        out.status = mean.status;
+        return out;
+    }
+
+    // Long-period periodics and Kepler's equation.
+    let p37 = 1.0 / (mean.a * (1.0 - mean.eccentricity * mean.eccentricity));
+    let axn = mean.eccentricity * cos(mean.argument_of_perigee);
+    let ayn = mean.eccentricity * sin(mean.argument_of_perigee) + p37 * mean.k2;
+    let p38 = (mean.mean_anomaly + mean.argument_of_perigee + p37 * mean.k5 * axn) % TAU;
+
+    var ew = p38;
+    for (var i = 0; i < 10; i++) {
+        let delta = (p38 - ayn * cos(ew) + axn * sin(ew) - ew)
+            / (1.0 - cos(ew) * axn - sin(ew) * ayn);
+        if (abs(delta) < KEPLER_TOLERANCE) {
+            break;
+        }
+        ew += clamp(delta, -0.95, 0.95);
+    }
+
+    // Short-period periodics.
+    let p39 = axn * axn + ayn * ayn;
+    let pl = mean.a * (1.0 - p39);
+    if (pl < 0.0) {
+        out.status = STATUS_SEMI_LATUS_RECTUM_NEGATIVE;
+        return out;
+    }
+    let p40 = axn * sin(ew) - ayn * cos(ew);
+    let r = mean.a * (1.0 - (axn * cos(ew) + ayn * sin(ew)));
+    let r_dot = sqrt(mean.a) * p40 / r;
+    let b = sqrt(1.0 - p39);
+    let p41 = p40 / (1.0 + b);
+    let p42 = mean.a / r * (sin(ew) - ayn - axn * p41);
+    let p43 = mean.a / r * (cos(ew) - axn + ayn * p41);
+    let u = atan2(p42, p43);
+    let p44 = 2.0 * p43 * p42;
+    let p45 = 1.0 - 2.0 * p42 * p42;
+    let p46 = 0.5 * J2 / pl / pl;
+    let rk = r * (1.0 - 1.5 * p46 * b * mean.k6) + 0.5 * (0.5 * J2 / pl) * mean.k3 * p45;
+    if (rk < 1.0) {
+        out.status = STATUS_DECAYED;
+        return out;
+    }
+    let uk = u - 0.25 * p46 * mean.k4 * p44;
+    let inclination_k = mean.inclination
+        + 1.5 * p46 * cos(mean.inclination) * sin(mean.inclination) * p45;
+    let right_ascension_k = mean.right_ascension + 1.5 * p46 * cos(mean.inclination) * p44;
+    let rk_dot = r_dot - mean.mean_motion * (0.5 * J2 / pl) * mean.k3 * p44 / KE;
+    let rfk_dot = sqrt(pl) / r
+        + mean.mean_motion * (0.5 * J2 / pl) * (mean.k3 * p45 + 1.5 * mean.k6) / KE;
+
+    let sin_o = sin(right_ascension_k);
+    let cos_o = cos(right_ascension_k);
+    let sin_i = sin(inclination_k);
+    let cos_i = cos(inclination_k);
+    let sin_u = sin(uk);
+    let cos_u = cos(uk);
+    let along = vec3<f32>(
+        -sin_o * cos_i * sin_u + cos_o * cos_u,
+        cos_o * cos_i * sin_u + sin_o * cos_u,
+        sin_i * sin_u,
+    );
+    let across = vec3<f32>(
+        -sin_o * cos_i * cos_u - cos_o * sin_u,
+        cos_o * cos_i * cos_u - sin_o * sin_u,
+        sin_i * cos_u,
+    );
+
+    out.status = STATUS_OK;
+    out.position = rk * along * AE;
+    out.velocity = (rk_dot * along + rfk_dot * across) * VELOCITY_SCALE;
+    return out;
+}
 
 @compute @workgroup_size(64)
 fn main(@builtin(global_invocation_id) gid: vec3<u32>) {
-    // Placeholder so the compiler keeps the invocation alive.
-    let _lane = gid.x;
+    let count = arrayLength(&status);
+    let index = gid.x;
+    if (index >= count) {
+        return;
+    }
+
+    let result = propagate(elements[index], times[index]);
+    states[index] = result.position.x;
+    states[count + index] = result.position.y;
+    states[2u * count + index] = result.position.z;
+    states[3u * count + index] = result.velocity.x;
+    states[4u * count + index] = result.velocity.y;
+    states[5u * count + index] = result.velocity.z;
+    status[index] = result.status;
 }
@@ -1,19 +1,38 @@
-use openastroviz_core::{GpuBackend, GpuBackendError};
+use std::rc::Rc;
+
+use openastroviz_core::backend::BackendFuture;
+use openastroviz_core::{
+    DispatchTimes, ElementSet, GpuBackend, GpuBackendError, ObjectStatus, PackedElements,
+    Sgp4Propagator, StateBuffer,
+};
 use wasm_bindgen::prelude::*;
+use wgpu::util::DeviceExt;
 
 const WORKGROUP_SIZE: u32 = 64;
 
-/// WGSL compute kernel scaffold for the FP32 SGP4 pipeline.
+/// Position and velocity components written per object.
+const STATE_COMPONENTS: usize = 6;
+
+/// WGSL compute kernel for the FP32 SGP4 pipeline.
 ///
-/// TODO: Port the existing CUDA FP32 orbital update math fully; this shader currently
-/// establishes the dispatch and invocation shape so the React client can drive it.
+/// Reads the host-initialized [`PackedElements`] and mirrors
+/// `openastroviz_core::fp32::propagate`, which the parity tests compare it to.
 const SGP4_FP32_WGSL: &str = include_str!("../shaders/sgp4_fp32.wgsl");
 
+/// Element set resident on the device.
+#[derive(Debug)]
+struct UploadedElements {
+    propagators: Vec<Sgp4Propagator>,
+    buffer: wgpu::Buffer,
+    count: u32,
+}
+
 #[derive(Debug)]
 pub struct WebGpuBackend {
     device: wgpu::Device,
     queue: wgpu::Queue,
     pipeline: wgpu::ComputePipeline,
+    elements: Option<UploadedElements>,
 }
 
 impl WebGpuBackend {
@@ -58,48 +77,232 @@ impl WebGpuBackend {
             device,
             queue,
             pipeline,
+            elements: None,
         })
     }
-}
 
-impl GpuBackend for WebGpuBackend {
-    fn name(&self) -> &'static str {
-        "webgpu"
+    fn storage_buffer(&self, label: &str, size: usize, usage: wgpu::BufferUsages) -> wgpu::Buffer {
+        self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: size as u64,
+            usage,
+            mapped_at_creation: false,
+        })
     }
 
-    fn is_ready(&self) -> bool {
-        true
-    }
+    async fn run(
+        &self,
+        times: DispatchTimes<'_>,
+        states: &mut StateBuffer,
+    ) -> Result<(), GpuBackendError> {
+        let elements = self.elements.as_ref().ok_or(GpuBackendError::NotReady)?;
+        let count = elements.count as usize;
+        let minutes: Vec<f32> = times
+            .minutes_since_epoch(&elements.propagators)?
+            .into_iter()
+            .map(|minutes| minutes as f32)
+            .collect();
 
-    fn dispatch_sgp4_fp32_step(&self, batch_size: u32) -> Result<(), GpuBackendError> {
-        if batch_size == 0 {
-            return Err(GpuBackendError::InvalidBatchSize(batch_size));
-        }
+        let times_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("openastroviz-sgp4-times"),
+                contents: &f32_bytes(&minutes),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+        let state_bytes = count * STATE_COMPONENTS * 4;
+        let status_bytes = count * 4;
+        let storage = wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC;
+        let states_buffer = self.storage_buffer("openastroviz-sgp4-states", state_bytes, storage);
+        let status_buffer = self.storage_buffer("openastroviz-sgp4-status", status_bytes, storage);
+        let readback = self.storage_buffer(
+            "openastroviz-sgp4-readback",
+            state_bytes + status_bytes,
+            wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        );
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("openastroviz-sgp4-bind-group"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: elements.buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: times_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: states_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: status_buffer.as_entire_binding(),
+                },
+            ],
+        });
 
-        let workgroups = batch_size.div_ceil(WORKGROUP_SIZE);
         let mut encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("openastroviz-sgp4-fp32-encoder"),
             });
-
         {
             let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
                 label: Some("openastroviz-sgp4-fp32-pass"),
                 timestamp_writes: None,
             });
             pass.set_pipeline(&self.pipeline);
-            pass.dispatch_workgroups(workgroups, 1, 1);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(elements.count.div_ceil(WORKGROUP_SIZE), 1, 1);
         }
-
+        encoder.copy_buffer_to_buffer(&states_buffer, 0, &readback, 0, state_bytes as u64);
+        encoder.copy_buffer_to_buffer(
+            &status_buffer,
+            0,
+            &readback,
+            state_bytes as u64,
+            status_bytes as u64,
+        );
         self.queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let (sender, receiver) = futures_channel::oneshot::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        // Drives the callback on native targets; browsers resolve it on their own.
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver
+            .await
+            .map_err(|_| GpuBackendError::Dispatch("readback was cancelled".to_string()))?
+            .map_err(|err| GpuBackendError::Dispatch(err.to_string()))?;
+
+        {
+            let data = slice.get_mapped_range();
+            let (state_data, status_data) = data.split_at(state_bytes);
+            let components: Vec<f64> = state_data
+                .chunks_exact(4)
+                .map(|word| f32::from_le_bytes([word[0], word[1], word[2], word[3]]) as f64)
+                .collect();
+
+            states.resize(count);
+            for (index, word) in status_data.chunks_exact(4).enumerate() {
+                let code = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+                let status = ObjectStatus::from_code(code).unwrap_or(ObjectStatus::Pending);
+                let component = |plane: usize| {
+                    if status.is_ok() {
+                        components[plane * count + index]
+                    } else {
+                        f64::NAN
+                    }
+                };
+                states.x_km[index] = component(0);
+                states.y_km[index] = component(1);
+                states.z_km[index] = component(2);
+                states.vx_km_s[index] = component(3);
+                states.vy_km_s[index] = component(4);
+                states.vz_km_s[index] = component(5);
+                states.status[index] = status;
+            }
+        }
+        readback.unmap();
         Ok(())
     }
 }
 
+impl GpuBackend for WebGpuBackend {
+    fn name(&self) -> &'static str {
+        "webgpu"
+    }
+
+    fn is_ready(&self) -> bool {
+        true
+    }
+
+    fn upload_elements(&mut self, elements: &ElementSet) -> Result<(), GpuBackendError> {
+        let count = u32::try_from(elements.len())
+            .map_err(|_| GpuBackendError::InvalidBatchSize(u32::MAX))?;
+        if count == 0 {
+            return Err(GpuBackendError::InvalidBatchSize(count));
+        }
+
+        let buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("openastroviz-sgp4-elements"),
+                contents: packed_bytes(elements.packed()),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+        self.elements = Some(UploadedElements {
+            propagators: elements.propagators().to_vec(),
+            buffer,
+            count,
+        });
+        Ok(())
+    }
+
+    fn propagate<'a>(
+        &'a self,
+        times: DispatchTimes<'a>,
+        states: &'a mut StateBuffer,
+    ) -> BackendFuture<'a> {
+        Box::pin(self.run(times, states))
+    }
+}
+
+fn packed_bytes(elements: &[PackedElements]) -> &[u8] {
+    // SAFETY: `PackedElements` is `#[repr(C)]` and made only of 4-byte
+    // scalars, so it has no padding and every byte is initialized.
+    unsafe { std::slice::from_raw_parts(elements.as_ptr().cast(), std::mem::size_of_val(elements)) }
+}
+
+fn f32_bytes(values: &[f32]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
 #[wasm_bindgen]
 pub struct WebGpuBackendHandle {
-    backend: WebGpuBackend,
+    backend: Rc<WebGpuBackend>,
+}
+
+/// States read back from one dispatch, as flat structure-of-arrays vectors.
+#[wasm_bindgen]
+pub struct StateSnapshot {
+    states: StateBuffer,
+}
+
+#[wasm_bindgen]
+impl StateSnapshot {
+    /// Positions in km as `[x0, y0, z0, x1, ...]`.
+    pub fn positions(&self) -> Vec<f64> {
+        let s = &self.states;
+        (0..s.len())
+            .flat_map(|i| [s.x_km[i], s.y_km[i], s.z_km[i]])
+            .collect()
+    }
+
+    /// Velocities in km/s as `[vx0, vy0, vz0, vx1, ...]`.
+    pub fn velocities(&self) -> Vec<f64> {
+        let s = &self.states;
+        (0..s.len())
+            .flat_map(|i| [s.vx_km_s[i], s.vy_km_s[i], s.vz_km_s[i]])
+            .collect()
+    }
+
+    /// Per-object status codes (0 is success).
+    pub fn status(&self) -> Vec<u32> {
+        self.states
+            .status
+            .iter()
+            .map(|status| status.code())
+            .collect()
+    }
 }
 
 #[wasm_bindgen]
@@ -109,12 +312,40 @@ impl WebGpuBackendHandle {
         self.backend.name().to_string()
     }
 
-    #[wasm_bindgen(js_name = dispatchSgp4Fp32Step)]
-    pub fn dispatch_sgp4_fp32_step(&self, batch_size: u32) -> Result<(), JsValue> {
-        self.backend
-            .dispatch_sgp4_fp32_step(batch_size)
+    /// Upload matching arrays of TLE line 1 and line 2 strings.
+    #[wasm_bindgen(js_name = uploadTles)]
+    pub fn upload_tles(&mut self, line1s: Vec<String>, line2s: Vec<String>) -> Result<(), JsValue> {
+        if line1s.len() != line2s.len() {
+            return Err(JsValue::from_str("line 1 and line 2 counts differ"));
+        }
+        let elements = line1s
+            .iter()
+            .zip(&line2s)
+            .map(|(line1, line2)| Sgp4Propagator::from_tle(None, line1, line2))
+            .collect::<Result<ElementSet, _>>()
+            .map_err(|err| JsValue::from_str(&err.to_string()))?;
+        Rc::get_mut(&mut self.backend)
+            .ok_or_else(|| JsValue::from_str("a dispatch is still in flight"))?
+            .upload_elements(&elements)
             .map_err(|err| JsValue::from_str(&err.to_string()))
     }
+
+    /// Propagate every uploaded object to a Unix time in milliseconds and
+    /// resolve to a `StateSnapshot`.
+    pub fn propagate(&self, unix_millis: f64) -> js_sys::Promise {
+        let backend = Rc::clone(&self.backend);
+        wasm_bindgen_futures::future_to_promise(async move {
+            let time = chrono::DateTime::from_timestamp_millis(unix_millis as i64)
+                .ok_or_else(|| JsValue::from_str("timestamp out of range"))?
+                .naive_utc();
+            let mut states = StateBuffer::default();
+            backend
+                .propagate(DispatchTimes::Uniform(time), &mut states)
+                .await
+                .map_err(|err| JsValue::from_str(&err.to_string()))?;
+            Ok(StateSnapshot { states }.into())
+        })
+    }
 }
 
 #[wasm_bindgen(js_name = initWebGpuBackend)]
@@ -122,5 +353,7 @@ pub async fn init_webgpu_backend() -> Result<WebGpuBackendHandle, JsValue> {
     let backend = WebGpuBackend::initialize()
         .await
         .map_err(|err| JsValue::from_str(&err.to_string()))?;
-    Ok(WebGpuBackendHandle { backend })
+    Ok(WebGpuBackendHandle {
+        backend: Rc::new(backend),
+    })
 }
@@ -0,0 +1,29 @@
+use openastroviz_core::{
+    DispatchTimes, ElementSet, GpuBackend, ObjectStatus, Sgp4Propagator, StateBuffer,
+};
+use openastroviz_webgpu_compute::WebGpuBackend;
+
+const LINE1: &str = "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753";
+const LINE2: &str = "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
+
+#[test]
+#[ignore = "needs a WebGPU adapter"]
+fn dispatch_reads_back_one_status_per_object() {
+    let mut backend = pollster::block_on(WebGpuBackend::initialize()).expect("no WebGPU adapter");
+
+    let propagator = Sgp4Propagator::from_tle(None, LINE1, LINE2).unwrap();
+    let elements = ElementSet::new(vec![propagator; 100]);
+    backend.upload_elements(&elements).unwrap();
+
+    let mut minutes = vec![0.0; 100];
+    minutes[7] = f64::NAN;
+    let mut states = StateBuffer::default();
+    pollster::block_on(backend.propagate(DispatchTimes::MinutesSinceEpoch(&minutes), &mut states))
+        .unwrap();
+
+    assert_eq!(states.len(), 100);
+    assert_eq!(states.status[7], ObjectStatus::InvalidTime);
+    assert!(states.x_km[7].is_nan());
+    assert_eq!(states.status[8], ObjectStatus::Ok);
+    assert_eq!(states.ok_count(), 99);
+}
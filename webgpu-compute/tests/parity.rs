@@ -0,0 +1,125 @@
+use openastroviz_core::{
+    fp32, DispatchTimes, ElementSet, GpuBackend, ObjectStatus, PackedElements, Sgp4Propagator,
+    StateBuffer,
+};
+use openastroviz_webgpu_compute::WebGpuBackend;
+
+const SHADER: &str = include_str!("../shaders/sgp4_fp32.wgsl");
+
+/// Vanguard 1 at the Vallado verification times, then a geosynchronous and a
+/// Molniya-type object to exercise both resonance integrators.
+const CASES: [(&str, &str, f64); 5] = [
+    (
+        "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753",
+        "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667",
+        0.0,
+    ),
+    (
+        "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753",
+        "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667",
+        360.0,
+    ),
+    (
+        "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753",
+        "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667",
+        1440.0,
+    ),
+    (
+        "1 28626U 05008A   06176.46683397 -.00000205  00000-0  10000-3 0  2190",
+        "2 28626   0.0019 286.9433 0000335  13.7918  55.6504  1.00270176  4891",
+        1440.0,
+    ),
+    (
+        "1 09880U 77021A   06176.56157475  .00000421  00000-0  10000-3 0  9814",
+        "2 09880  64.5968 349.3786 7069051 270.0229  16.3320  2.00813614112380",
+        1440.0,
+    ),
+];
+
+fn parse_shader() -> naga::Module {
+    naga::front::wgsl::parse_str(SHADER)
+        .unwrap_or_else(|err| panic!("{}", err.emit_to_string(SHADER)))
+}
+
+#[test]
+fn shader_passes_naga_validation() {
+    let module = parse_shader();
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::empty(),
+    )
+    .validate(&module)
+    .expect("shader should validate");
+}
+
+#[test]
+fn shader_element_layout_matches_core() {
+    let module = parse_shader();
+    let mut layouter = naga::proc::Layouter::default();
+    layouter.update(module.to_ctx()).unwrap();
+
+    let (handle, _) = module
+        .types
+        .iter()
+        .find(|(_, ty)| ty.name.as_deref() == Some("PackedElements"))
+        .expect("shader declares PackedElements");
+    assert_eq!(
+        layouter[handle].size as usize,
+        std::mem::size_of::<PackedElements>()
+    );
+}
+
+#[test]
+#[ignore = "needs a WebGPU adapter"]
+fn gpu_matches_fp32_reference() {
+    let mut backend = pollster::block_on(WebGpuBackend::initialize()).expect("no WebGPU adapter");
+
+    let elements: ElementSet = CASES
+        .iter()
+        .map(|(line1, line2, _)| Sgp4Propagator::from_tle(None, line1, line2).unwrap())
+        .collect();
+    let minutes: Vec<f64> = CASES.iter().map(|case| case.2).collect();
+    backend.upload_elements(&elements).unwrap();
+    let mut states = StateBuffer::default();
+    pollster::block_on(backend.propagate(DispatchTimes::MinutesSinceEpoch(&minutes), &mut states))
+        .unwrap();
+
+    for (index, packed) in elements.packed().iter().enumerate() {
+        let expected = fp32::propagate(packed, minutes[index] as f32).unwrap();
+        assert_eq!(states.status[index], ObjectStatus::Ok);
+        let actual = states.state(index).unwrap();
+        // Device sin/cos differ from libm in the last bits, which f32
+        // propagation amplifies to metres over a day.
+        for axis in 0..3 {
+            let dr = (actual.position_km[axis] - expected.position_km[axis]).abs();
+            let dv = (actual.velocity_km_s[axis] - expected.velocity_km_s[axis]).abs();
+            assert!(dr < 0.1, "object {index} axis {axis}: {dr} km");
+            assert!(dv < 1e-4, "object {index} axis {axis}: {dv} km/s");
+        }
+    }
+}
+
+#[test]
+#[ignore = "needs a WebGPU adapter"]
+fn gpu_flags_decayed_objects() {
+    let mut backend = pollster::block_on(WebGpuBackend::initialize()).expect("no WebGPU adapter");
+
+    // Sub-orbital verification object, below the surface an hour after epoch.
+    let decaying = Sgp4Propagator::from_tle(
+        None,
+        "1 28872U 05037B   05333.02012661  .25992681  00000-0  24476-3 0  1534",
+        "2 28872  96.4736 157.9986 0303955 244.0492 110.6523 16.46015938 10708",
+    )
+    .unwrap();
+    let vanguard = Sgp4Propagator::from_tle(None, CASES[0].0, CASES[0].1).unwrap();
+    let elements: ElementSet = [decaying, vanguard].into_iter().collect();
+    backend.upload_elements(&elements).unwrap();
+    let mut states = StateBuffer::default();
+    pollster::block_on(
+        backend.propagate(DispatchTimes::MinutesSinceEpoch(&[60.0, 0.0]), &mut states),
+    )
+    .unwrap();
+
+    assert_eq!(states.status, [ObjectStatus::Decayed, ObjectStatus::Ok]);
+    assert!(states.state(0).is_none());
+}